@@ -2,6 +2,7 @@ mod tools;
 mod utils;
 
 use rmcp::RoleServer;
+use std::collections::HashMap;
 use std::path::PathBuf;
 use rmcp::schemars;
 use rmcp::{
@@ -17,29 +18,45 @@ use rmcp::{
 };
 use rmcp::service::RequestContext;
 use serde::{Deserialize};
-use utils::RustPaths;
+use utils::{FeatureFlags, IgnoreList, RustPaths, ServerConfig, ToolError};
+use utils::error::to_mcp_error;
+use utils::process::command_timeout;
+use utils::metadata::cargo_metadata;
 use std::sync::Arc;
 use rmcp::handler::server::router::prompt::PromptRouter;
 use tokio::io::{stdin, stdout};
-
-use crate::tools::{CrateInfoProvider, RustDocsSearcher, CargoChecker, ErrorExplainer, ProjectManager, DependencyManager, FileSurgeon, TestRunner, McpToolScaffolder, McpPatterns, GitController, CodePolisher, SymbolAnalyzer};
-use crate::tools::analyzer::AnalyzeRequest;
+use tokio::sync::{mpsc, Semaphore, SemaphorePermit};
+use tokio_util::sync::CancellationToken;
+use tracing_subscriber::EnvFilter;
+use tracing_subscriber::fmt::format::FmtSpan;
+
+use crate::tools::{CrateInfoProvider, RustDocsSearcher, CargoChecker, ErrorExplainer, ProjectManager, DependencyManager, FileSurgeon, TestRunner, McpToolScaffolder, McpPatterns, GitController, CodePolisher, SymbolAnalyzer, HealthChecker, BenchRunner, SymbolIndex, ManifestManager, ExampleRunner, CargoFixer, CargoCommandRunner, CheckedEditor};
+use crate::tools::analyzer::{AnalyzeRequest, FindModuleCyclesRequest, PreviewSchemaRequest, PublicApiRequest, SearchCrateSourceRequest, ValidateToolRequest, VerifyRegistrationRequest, VisibilityFilter};
+use crate::tools::health::BuildEnvironmentRequest;
+use crate::tools::manifest::{DependencyConfigRequest, ProjectKindRequest, ReadManifestRequest, ResolvedVersionsRequest, SetManifestFieldRequest};
 use crate::tools::manual::SYSTEM_INSTRUCTIONS;
-use crate::tools::search_docs::SearchDocsRequest;
-use crate::tools::cargo_check::CheckCodeRequest;
-use crate::tools::crate_info::GetCrateInfoRequest;
+use crate::tools::search_docs::{DocSearchOutput, IndexStatus, ListTypeMethodsRequest, ReadStdSourceRequest, SearchDocsRequest, WaitForIndexRequest};
+use crate::tools::cargo_check::{ApplyFixesRequest, CheckCodeRequest, CheckFileRequest};
+use crate::tools::crate_info::{CrateFeatureGraphRequest, DiffCrateVersionsRequest, GetCrateExampleRequest, GetCrateInfoRequest, GetCrateReadmeRequest, SuggestCratesRequest};
 use crate::tools::dependencies::AddDepRequest;
-use crate::tools::explain::ExplainRequest;
+use crate::tools::explain::{ExplainOutputRequest, ExplainRequest};
+use crate::tools::fix::CargoFixRequest;
+use crate::tools::cargo_command::{is_mutating, CargoCommandRequest, DEFAULT_ALLOWED_SUBCOMMANDS};
 use crate::tools::git::GitRequest;
 use crate::tools::patterns::GetPatternRequest;
-use crate::tools::project::StructureRequest;
-use crate::tools::scaffolder::ScaffoldToolRequest;
-use crate::tools::surgeon::PatchFileRequest;
-use crate::tools::testing::RunTestsRequest;
+use crate::tools::project::{GrepRequest, ListTodosRequest, ListWorkspaceMembersRequest, RecentlyModifiedRequest, StructureRequest};
+use crate::tools::scaffolder::{InitMcpServerRequest, ScaffoldToolRequest};
+use crate::tools::surgeon::{CreateDirRequest, DiffFilesRequest, PatchFileRequest, PatchFilesRequest, ReplaceFunctionRequest, RestoreFileRequest, SuggestAnchorRequest, TouchFileRequest};
+use crate::tools::testing::{RunDoctestRequest, RunTestsRequest};
+use crate::tools::workflow::CheckedEditRequest;
+use crate::tools::bench::RunBenchRequest;
+use crate::tools::examples::{ListExamplesRequest, RunExampleRequest};
+use crate::tools::symbols::FindReferencesRequest;
 
 #[derive(Clone)]
 pub struct RustBuilderServer {
     paths: Arc<RustPaths>,
+    cargo_permits: Arc<Semaphore>, // Bounds concurrent cargo processes
     docs_searcher: Arc<Option<RustDocsSearcher>>,
     crate_provider: Arc<Option<CrateInfoProvider>>,
     checker: Arc<CargoChecker>,
@@ -54,6 +71,16 @@ pub struct RustBuilderServer {
     polisher: Arc<CodePolisher>,
     prompt_router: PromptRouter<Self>,
     analyzer: Arc<SymbolAnalyzer>,
+    health: Arc<HealthChecker>,
+    bench_runner: Arc<BenchRunner>,
+    symbol_index: Arc<SymbolIndex>,
+    manifest: Arc<ManifestManager>,
+    example_runner: Arc<ExampleRunner>,
+    fixer: Arc<CargoFixer>,
+    cargo_command: Arc<CargoCommandRunner>,
+    checked_editor: Arc<CheckedEditor>,
+    read_only: bool,
+    config: Arc<ServerConfig>,
     tool_router: ToolRouter<Self>,
 }
 
@@ -71,6 +98,17 @@ struct ReadFileRequest {
 }
 
 
+#[derive(Deserialize, JsonSchema)]
+struct ReadFilesRequest {
+    #[schemars(description = "Absolute paths of the files to read")]
+    paths: Vec<String>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct ListToolsRequest {
+    #[schemars(description = "Optional: Only tools whose name or description contains this text (case-insensitive), e.g. 'crate' or 'git'")]
+    filter: Option<String>,
+}
 
 #[derive(Deserialize, JsonSchema)]
 struct PolishRequest {
@@ -78,6 +116,93 @@ struct PolishRequest {
     path: String,
     #[schemars(description = "Mode: 'fmt' (Format code) or 'clippy' (Check for lint errors). Note: Clippy does NOT auto-fix.")]
     mode: String,
+    #[schemars(description = "Optional (fmt only): Format just this file (absolute or relative to the project root) instead of the whole project")]
+    file: Option<String>,
+    #[schemars(description = "Optional (clippy only): Also lint tests, examples and benches (--all-targets). Slower, default false.")]
+    all_targets: Option<bool>,
+    #[schemars(description = "Optional (clippy only): Activate all features (--all-features). Default false.")]
+    all_features: Option<bool>,
+    #[schemars(description = "Optional (clippy only): Disable the default features (--no-default-features). Default false.")]
+    no_default_features: Option<bool>,
+    #[schemars(description = "Optional (clippy only): Features to activate, e.g. [\"serde\", \"tokio/full\"] (--features)")]
+    features: Option<Vec<String>>,
+    #[schemars(description = "Optional: Timeout in seconds (default: server's --command-timeout-secs)")]
+    timeout_secs: Option<u64>,
+}
+
+/// Tools that exist only to change files; hidden from the tool list in `--read-only` mode.
+/// Partially mutating tools (git_operations, polish_code, add_dependency) stay listed and
+/// refuse just their mutating operations.
+const MUTATING_TOOLS: [&str; 12] = [
+    "patch_file",
+    "patch_files",
+    "create_dir",
+    "touch_file",
+    "run_checked_edit",
+    "restore_file",
+    "replace_function",
+    "apply_fixes",
+    "cargo_fix",
+    "set_manifest_field",
+    "scaffold_new_tool",
+    "init_mcp_server",
+];
+
+impl RustBuilderServer {
+    /// Refuses `tool` (or one of its operations) when the server runs with `--read-only`.
+    fn ensure_writable(&self, tool: &str) -> Result<(), McpError> {
+        if self.read_only {
+            return Err(McpError::new(
+                ErrorCode::INVALID_REQUEST,
+                format!("The server is in read-only mode (--read-only): '{}' is disabled.", tool),
+                Some(serde_json::json!({ "kind": "read_only" }))
+            ));
+        }
+        Ok(())
+    }
+
+    /// Waits for a free cargo slot. Hold the returned permit while the cargo process runs.
+    async fn acquire_cargo_permit(&self) -> Result<SemaphorePermit<'_>, McpError> {
+        self.cargo_permits
+            .acquire()
+            .await
+            .map_err(|e| McpError::new(ErrorCode::INTERNAL_ERROR, e.to_string(), None))
+    }
+
+    /// Directory of workspace member `member`, or `path` itself without one.
+    /// The lookup runs `cargo metadata`, so it holds a cargo permit for that long.
+    async fn resolve_member(&self, path: PathBuf, member: Option<&str>, cancel: &CancellationToken) -> Result<PathBuf, McpError> {
+        if member.is_none() {
+            return Ok(path);
+        }
+        let _permit = self.acquire_cargo_permit().await?;
+        self.project_manager.resolve_member(path, member, cancel, command_timeout(None))
+            .await
+            .map_err(to_mcp_error)
+    }
+
+    /// If the client sent a progress token, returns a sender whose lines are forwarded
+    /// as progress notifications. Clients without progress support get `None` (buffered output).
+    fn progress_forwarder(&self, context: &RequestContext<RoleServer>) -> Option<mpsc::UnboundedSender<String>> {
+        let progress_token = context.meta.get_progress_token()?;
+        let peer = context.peer.clone();
+        let (tx, mut rx) = mpsc::unbounded_channel::<String>();
+
+        tokio::spawn(async move {
+            let mut count = 0u32;
+            while let Some(line) = rx.recv().await {
+                count += 1;
+                let _ = peer.notify_progress(ProgressNotificationParam {
+                    progress_token: progress_token.clone(),
+                    progress: count as f64,
+                    total: None,
+                    message: Some(line),
+                }).await;
+            }
+        });
+
+        Some(tx)
+    }
 }
 
 // --- Prompt Router ---
@@ -101,19 +226,44 @@ impl RustBuilderServer {
     fn new() -> Self {
         // Discover Rust installation
         let paths = RustPaths::discover();
-        eprintln!("{}", paths.status_report());
+        tracing::info!("{}", paths.status_report());
+
+        if let Some(bin) = &paths.cargo_bin {
+            utils::process::set_cargo_bin(bin.clone());
+        }
+
+        let config = ServerConfig::load();
+        tracing::info!("Max concurrent cargo jobs: {}", config.max_cargo_jobs);
+        utils::process::set_max_output_bytes(config.max_output_bytes);
+        utils::process::set_cargo_attempts(config.cargo_attempts);
+        utils::process::set_command_timeout(config.command_timeout);
+
+        if config.read_only {
+            tracing::info!("Read-only mode: file-mutating tools are disabled");
+        }
+
+        let ignore = IgnoreList::new(config.ignore_dirs.as_deref());
+        tracing::info!("Ignored directories: {}", ignore.dirs().join(", "));
+
+        let mut tool_router = Self::tool_router();
+        if config.read_only {
+            for name in MUTATING_TOOLS {
+                tool_router.remove_route(name);
+            }
+        }
 
         // Initialize Tools
-        let docs_searcher = paths.docs_path.clone().map(|p| RustDocsSearcher::new(p));
+        let docs_searcher = paths.docs_path.clone().map(|p| RustDocsSearcher::new(p, ignore.clone(), config.doc_sources.clone()));
         let crate_provider = paths.cargo_registry.clone().map(|p| CrateInfoProvider::new(p));
 
         Self {
             paths: Arc::new(paths),
+            cargo_permits: Arc::new(Semaphore::new(config.max_cargo_jobs)),
             docs_searcher: Arc::new(docs_searcher),
             crate_provider: Arc::new(crate_provider),
             checker: Arc::new(CargoChecker::new()),
             explainer: Arc::new(ErrorExplainer::new()),
-            project_manager: Arc::new(ProjectManager::new()),
+            project_manager: Arc::new(ProjectManager::new(ignore.clone())),
             dep_manager: Arc::new(DependencyManager::new()),
             surgeon: Arc::new(FileSurgeon::new()),
             test_runner: Arc::new(TestRunner::new()),
@@ -123,13 +273,23 @@ impl RustBuilderServer {
             polisher: Arc::new(CodePolisher::new()),
             prompt_router: Self::prompt_router(),
             analyzer: Arc::new(SymbolAnalyzer::new()),
-            tool_router: Self::tool_router(),
+            health: Arc::new(HealthChecker::new()),
+            bench_runner: Arc::new(BenchRunner::new()),
+            symbol_index: Arc::new(SymbolIndex::new(ignore.clone())),
+            manifest: Arc::new(ManifestManager::new()),
+            example_runner: Arc::new(ExampleRunner::new()),
+            fixer: Arc::new(CargoFixer::new(ignore)),
+            cargo_command: Arc::new(CargoCommandRunner::new(config.cargo_allowlist.as_deref())),
+            checked_editor: Arc::new(CheckedEditor::new()),
+            read_only: config.read_only,
+            config: Arc::new(config),
+            tool_router,
         }
     }
 
-    #[tool(description = "Search the local Rust Standard Library documentation")]
+    #[tool(description = "Search the local Rust Standard Library documentation. If the server indexes more doc sets (--doc-sources, e.g. the reference, the book, the nomicon), conceptual pages are included; each result names its 'source'.")]
     async fn search_rust_docs(&self, params: Parameters<SearchDocsRequest>) -> Result<CallToolResult, McpError> {
-        let SearchDocsRequest { query } = params.0;
+        let SearchDocsRequest { query, min_score, fuzzy, module_prefix } = params.0;
 
         let searcher = self
             .docs_searcher
@@ -141,7 +301,7 @@ impl RustBuilderServer {
                 None
             ))?;
 
-        let results = searcher.search(&query)
+        let DocSearchOutput { results, corrections } = searcher.search(&query, min_score, fuzzy.unwrap_or(false), module_prefix.as_deref())
             .await
             .map_err(|e| McpError::new(ErrorCode::PARSE_ERROR, e.to_string(), None))?;
 
@@ -151,7 +311,8 @@ impl RustBuilderServer {
         let response = serde_json::json!({
             "results": json_results,
             "count": results.len(),
-            "query": query
+            "query": query,
+            "corrections": corrections
         });
 
         Ok(CallToolResult::success(vec![Content::text(
@@ -159,9 +320,84 @@ impl RustBuilderServer {
         )]))
     }
 
-    #[tool(description = "Retrieves information about a Rust Crate from the local registry")]
+    #[tool(description = "Shows the actual source code of a std item found via 'search_rust_docs' (pass its 'path'). Requires the rust-src component.")]
+    async fn read_std_source(&self, params: Parameters<ReadStdSourceRequest>) -> Result<CallToolResult, McpError> {
+        let searcher = self
+            .docs_searcher
+            .as_ref()
+            .as_ref()
+            .ok_or_else(|| McpError::new(
+                ErrorCode::INTERNAL_ERROR,
+                "Rust docs not installed. Run: rustup component add rust-docs",
+                None
+            ))?;
+
+        let src_root = self.paths.rust_src_path.as_ref().ok_or_else(|| McpError::new(
+            ErrorCode::RESOURCE_NOT_FOUND,
+            "Rust sources not installed. Run: rustup component add rust-src",
+            None
+        ))?;
+
+        let source = searcher.read_source(params.0.path.trim(), src_root)
+            .map_err(to_mcp_error)?;
+
+        Ok(CallToolResult::success(vec![Content::text(source)]))
+    }
+
+    #[tool(description = "Lists the method signatures of a std type or trait from its rustdoc page (e.g. 'std::collections::HashMap'). Inherent methods by default; set 'include_trait_methods' for trait impls too.")]
+    async fn list_type_methods(&self, params: Parameters<ListTypeMethodsRequest>) -> Result<CallToolResult, McpError> {
+        let searcher = self
+            .docs_searcher
+            .as_ref()
+            .as_ref()
+            .ok_or_else(|| McpError::new(
+                ErrorCode::INTERNAL_ERROR,
+                "Rust docs not installed. Run: rustup component add rust-docs",
+                None
+            ))?;
+
+        let ListTypeMethodsRequest { item, include_trait_methods } = params.0;
+        let methods = searcher.list_methods(&item, include_trait_methods.unwrap_or(false))
+            .map_err(to_mcp_error)?;
+
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::to_string_pretty(&methods).unwrap()
+        )]))
+    }
+
+    #[tool(description = "Blocks until the Rust docs search index has finished building (or the timeout elapses) and returns its status. Use this instead of retrying 'search_rust_docs' while it reports 'Indexing in progress'.")]
+    async fn wait_for_index(&self, params: Parameters<WaitForIndexRequest>, context: RequestContext<RoleServer>) -> Result<CallToolResult, McpError> {
+        let timeout = std::time::Duration::from_secs(params.0.timeout_secs.unwrap_or(60).min(600));
+
+        let searcher = self
+            .docs_searcher
+            .as_ref()
+            .as_ref()
+            .ok_or_else(|| McpError::new(
+                ErrorCode::INTERNAL_ERROR,
+                "Rust docs not installed. Run: rustup component add rust-docs",
+                None
+            ))?;
+
+        let status = searcher.wait_until_ready(timeout, &context.ct).await;
+
+        let mut response = serde_json::to_value(&status).unwrap();
+        if matches!(status, IndexStatus::Initializing) {
+            response["message"] = serde_json::json!(if context.ct.is_cancelled() {
+                "Cancelled by client before the index was ready."
+            } else {
+                "Timed out; indexing is still running. Call again to keep waiting."
+            });
+        }
+
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::to_string_pretty(&response).unwrap()
+        )]))
+    }
+
+    #[tool(description = "Retrieves information about a Rust Crate from the local registry: dependencies and features of the newest cached version, or of an exact 'version' (e.g. the one resolved in Cargo.lock).")]
     async fn get_crate_info(&self, params: Parameters<GetCrateInfoRequest>) -> Result<CallToolResult, McpError> {
-        let GetCrateInfoRequest { crate_name } = params.0;
+        let GetCrateInfoRequest { crate_name, registry, version } = params.0;
 
         let provider = self
             .crate_provider
@@ -173,8 +409,8 @@ impl RustBuilderServer {
                 None
             ))?;
 
-        let info = provider.get_crate_info(&crate_name)
-            .map_err(|e| McpError::new(ErrorCode::RESOURCE_NOT_FOUND, e.to_string(), None))?;
+        let info = provider.get_crate_info(&crate_name, registry.as_deref(), version.as_deref())
+            .map_err(to_mcp_error)?;
 
         let response = if let Some(info) = info {
             serde_json::json!({
@@ -182,9 +418,11 @@ impl RustBuilderServer {
                 "crate": info
             })
         } else {
+            let suggestions = provider.suggest(&crate_name, 5).unwrap_or_default();
             serde_json::json!({
                 "found": false,
-                "message": format!("Crate '{}' not found in local cache", crate_name)
+                "message": format!("Crate '{}' not found in local cache", crate_name),
+                "suggestions": suggestions
             })
         };
 
@@ -193,13 +431,175 @@ impl RustBuilderServer {
         )]))
     }
 
+    #[tool(description = "Returns the README of a crate from its locally unpacked sources (~/.cargo/registry/src). Long READMEs are truncated.")]
+    async fn get_crate_readme(&self, params: Parameters<GetCrateReadmeRequest>) -> Result<CallToolResult, McpError> {
+        let GetCrateReadmeRequest { crate_name } = params.0;
+
+        let provider = self
+            .crate_provider
+            .as_ref()
+            .as_ref()
+            .ok_or_else(|| McpError::new(
+                ErrorCode::RESOURCE_NOT_FOUND,
+                "Cargo registry not found",
+                None
+            ))?;
+
+        let readme = provider.get_readme(crate_name.trim())
+            .map_err(to_mcp_error)?;
+
+        Ok(CallToolResult::success(vec![Content::text(readme)]))
+    }
+
+    #[tool(description = "Finds where a type, trait, fn or macro of a dependency is defined by parsing its locally unpacked source (~/.cargo/registry/src), e.g. symbol 'Deserializer' or 'Deserializer::deserialize_any' in crate 'serde'. Returns file and line inside the crate. Works offline when no docs are available.")]
+    async fn search_crate_source(&self, params: Parameters<SearchCrateSourceRequest>) -> Result<CallToolResult, McpError> {
+        let SearchCrateSourceRequest { crate_name, symbol } = params.0;
+
+        let provider = self
+            .crate_provider
+            .as_ref()
+            .as_ref()
+            .ok_or_else(|| McpError::new(
+                ErrorCode::RESOURCE_NOT_FOUND,
+                "Cargo registry not found",
+                None
+            ))?;
+
+        let crate_dir = provider.source_dir(crate_name.trim())
+            .map_err(to_mcp_error)?;
+        let analyzer = Arc::clone(&self.analyzer);
+        let definitions = run_blocking(move || analyzer.find_definitions(&crate_dir, crate_name.trim(), &symbol)).await?;
+
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::to_string_pretty(&definitions).unwrap()
+        )]))
+    }
+
+    #[tool(description = "Lists the examples/ of a locally unpacked crate, or returns the source of one example if 'example' is given. Great for learning a dependency's API.")]
+    async fn get_crate_example(&self, params: Parameters<GetCrateExampleRequest>) -> Result<CallToolResult, McpError> {
+        let GetCrateExampleRequest { crate_name, example } = params.0;
+
+        let provider = self
+            .crate_provider
+            .as_ref()
+            .as_ref()
+            .ok_or_else(|| McpError::new(
+                ErrorCode::RESOURCE_NOT_FOUND,
+                "Cargo registry not found",
+                None
+            ))?;
+
+        let text = match example {
+            Some(example) => provider.read_example(crate_name.trim(), example.trim())
+                .map_err(to_mcp_error)?,
+            None => {
+                let examples = provider.list_examples(crate_name.trim())
+                    .map_err(to_mcp_error)?;
+                let response = serde_json::json!({
+                    "crate": crate_name,
+                    "count": examples.len(),
+                    "examples": examples
+                });
+                serde_json::to_string_pretty(&response).unwrap()
+            }
+        };
+
+        Ok(CallToolResult::success(vec![Content::text(text)]))
+    }
+
+    #[tool(description = "Resolves what each feature of a crate transitively enables (other features, optional dependencies, 'dep/feature' and weak 'dep?/feature' entries). Use before 'add_dependency' to pick the minimal feature set.")]
+    async fn crate_feature_graph(&self, params: Parameters<CrateFeatureGraphRequest>) -> Result<CallToolResult, McpError> {
+        let crate_name = params.0.crate_name.trim().to_string();
+
+        let provider = self
+            .crate_provider
+            .as_ref()
+            .as_ref()
+            .ok_or_else(|| McpError::new(
+                ErrorCode::RESOURCE_NOT_FOUND,
+                "Cargo registry not found",
+                None
+            ))?;
+
+        let graph = provider.feature_graph(&crate_name)
+            .map_err(to_mcp_error)?
+            .ok_or_else(|| McpError::new(
+                ErrorCode::RESOURCE_NOT_FOUND,
+                format!("Crate '{}' not found in the local registry index", crate_name),
+                None
+            ))?;
+
+        let response = serde_json::json!({
+            "crate": crate_name,
+            "feature_count": graph.len(),
+            "features": graph
+        });
+
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::to_string_pretty(&response).unwrap()
+        )]))
+    }
+
+    #[tool(description = "Compares two versions of a crate using the local registry index (offline): added, removed and changed dependencies and added/removed features. A quick upgrade-risk check; reports the cached versions if one is missing.")]
+    async fn diff_crate_versions(&self, params: Parameters<DiffCrateVersionsRequest>) -> Result<CallToolResult, McpError> {
+        let DiffCrateVersionsRequest { crate_name, from_version, to_version } = params.0;
+
+        let provider = self
+            .crate_provider
+            .as_ref()
+            .as_ref()
+            .ok_or_else(|| McpError::new(
+                ErrorCode::RESOURCE_NOT_FOUND,
+                "Cargo registry not found",
+                None
+            ))?;
+
+        let diff = provider.diff_versions(crate_name.trim(), &from_version, &to_version)
+            .map_err(to_mcp_error)?;
+
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::to_string_pretty(&diff).unwrap()
+        )]))
+    }
+
+    #[tool(description = "Suggests crate names from the local registry index that start with or closely match a (partial/misspelled) name. Use this when 'get_crate_info' reports 'not found'.")]
+    async fn suggest_crates(&self, params: Parameters<SuggestCratesRequest>) -> Result<CallToolResult, McpError> {
+        let SuggestCratesRequest { prefix, limit } = params.0;
+
+        let provider = self
+            .crate_provider
+            .as_ref()
+            .as_ref()
+            .ok_or_else(|| McpError::new(
+                ErrorCode::RESOURCE_NOT_FOUND,
+                "Cargo registry not found",
+                None
+            ))?;
+
+        let suggestions = provider.suggest(&prefix, limit.unwrap_or(10))
+            .map_err(to_mcp_error)?;
+
+        let response = serde_json::json!({
+            "prefix": prefix,
+            "count": suggestions.len(),
+            "suggestions": suggestions
+        });
+
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::to_string_pretty(&response).unwrap()
+        )]))
+    }
+
     #[tool(description = "Returns the status of the Rust installation")]
     async fn get_installation_status(&self) -> Result<CallToolResult, McpError> {
         let response = serde_json::json!({
             "rustup_home": self.paths.rustup_home.as_ref().map(|p| p.display().to_string()),
             "docs_installed": self.paths.has_docs(),
             "docs_path": self.paths.docs_path.as_ref().map(|p| p.display().to_string()),
+            "rust_src_path": self.paths.rust_src_path.as_ref().map(|p| p.display().to_string()),
             "cargo_registry": self.paths.cargo_registry.as_ref().map(|p| p.display().to_string()),
+            "rustc_version": self.paths.rustc_version,
+            "cargo_version": self.paths.cargo_version,
             "status_report": self.paths.status_report()
         });
 
@@ -208,9 +608,106 @@ impl RustBuilderServer {
         )]))
     }
 
-    #[tool(description = "Runs 'cargo check' and returns compiler errors")]
-    async fn check_code(&self, params: Parameters<CheckCodeRequest>) -> Result<CallToolResult, McpError> {
-        let path = PathBuf::from(params.0.path);
+    #[tool(description = "Probes the external binaries the tools depend on (cargo, rustc, git, rustfmt, clippy, cargo-expand, cargo-audit) and reports availability and versions.")]
+    async fn health_check(&self) -> Result<CallToolResult, McpError> {
+        let binaries = self.health.probe_all().await;
+
+        let mut report = self.paths.status_report();
+        report.push_str(&HealthChecker::format_report(&binaries));
+
+        let response = serde_json::json!({
+            "binaries": binaries,
+            "docs_installed": self.paths.has_docs(),
+            "status_report": report
+        });
+
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::to_string_pretty(&response).unwrap()
+        )]))
+    }
+
+    #[tool(description = "Shows what cargo builds with: host target triple and rustc release (from 'rustc -vV'), the resolved cargo and rustc paths, build-relevant environment variables in effect (CARGO_BUILD_TARGET, RUSTFLAGS, RUSTC_WRAPPER, ...) and the .cargo/config.toml files that apply to 'path'. Read-only and fast; use it to diagnose cross-compilation or custom-flag discrepancies.")]
+    async fn build_environment(&self, params: Parameters<BuildEnvironmentRequest>) -> Result<CallToolResult, McpError> {
+        let project = params.0.path.map(PathBuf::from);
+        let environment = self.health.build_environment(self.paths.cargo_bin.as_deref(), project.as_deref()).await;
+
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::to_string_pretty(&environment).unwrap()
+        )]))
+    }
+
+    #[tool(description = "Lists the tools this server currently exposes with their descriptions, read from the tool router (so newly scaffolded tools and --read-only hiding are reflected). Use it to discover capabilities instead of relying on the manual.")]
+    async fn list_tools(&self, params: Parameters<ListToolsRequest>) -> Result<CallToolResult, McpError> {
+        let filter = params.0.filter.map(|f| f.trim().to_lowercase()).filter(|f| !f.is_empty());
+
+        let mut tools: Vec<serde_json::Value> = self.tool_router.list_all().into_iter()
+            .map(|tool| (tool.name.to_string(), tool.description.map(|d| d.to_string()).unwrap_or_default()))
+            .filter(|(name, description)| filter.as_ref().is_none_or(|f| {
+                name.to_lowercase().contains(f) || description.to_lowercase().contains(f)
+            }))
+            .map(|(name, description)| serde_json::json!({ "name": name, "description": description }))
+            .collect();
+        tools.sort_by(|a, b| a["name"].as_str().cmp(&b["name"].as_str()));
+
+        let response = serde_json::json!({
+            "count": tools.len(),
+            "read_only": self.config.read_only,
+            "tools": tools
+        });
+
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::to_string_pretty(&response).unwrap()
+        )]))
+    }
+
+    #[tool(description = "Structured status of the whole server in one call: Rust installation paths and versions, docs index state, external binaries (cargo, rustc, git, rustfmt, clippy, ...) and the effective configuration (offline, read-only, timeouts, limits). Use it to see why a tool might fail before calling it.")]
+    async fn server_status(&self) -> Result<CallToolResult, McpError> {
+        let binaries = self.health.probe_all().await;
+
+        let docs_index = match self.docs_searcher.as_ref() {
+            Some(searcher) => serde_json::to_value(searcher.status().await).unwrap(),
+            None => serde_json::json!({ "status": "not_installed" }),
+        };
+
+        let config = &self.config;
+        let response = serde_json::json!({
+            "installation": {
+                "rustup_home": self.paths.rustup_home.as_ref().map(|p| p.display().to_string()),
+                "docs_path": self.paths.docs_path.as_ref().map(|p| p.display().to_string()),
+                "rust_src_path": self.paths.rust_src_path.as_ref().map(|p| p.display().to_string()),
+                "cargo_registry": self.paths.cargo_registry.as_ref().map(|p| p.display().to_string()),
+                "cargo_bin": self.paths.cargo_bin.as_ref().map(|p| p.display().to_string()),
+                "rustc_version": self.paths.rustc_version,
+                "cargo_version": self.paths.cargo_version,
+            },
+            "docs_index": docs_index,
+            "binaries": binaries,
+            "config": {
+                "offline": utils::process::env_offline(),
+                "read_only": config.read_only,
+                "command_timeout_secs": config.command_timeout.as_secs(),
+                "max_cargo_jobs": config.max_cargo_jobs,
+                "cargo_attempts": config.cargo_attempts,
+                "max_output_bytes": config.max_output_bytes,
+                "ignore_dirs": IgnoreList::new(config.ignore_dirs.as_deref()).dirs(),
+                "doc_sources": config.doc_sources,
+                "cargo_allowlist": config.cargo_allowlist.clone().unwrap_or_else(|| {
+                    DEFAULT_ALLOWED_SUBCOMMANDS.iter().map(|s| s.to_string()).collect()
+                }),
+            },
+            "status_report": self.paths.status_report() + &HealthChecker::format_report(&binaries)
+        });
+
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::to_string_pretty(&response).unwrap()
+        )]))
+    }
+
+    #[tool(description = "Runs 'cargo check' and returns compiler errors. Set 'all_targets' to also check tests, examples and benches (increases build time); 'all_features', 'no_default_features' and 'features' select the feature set; 'env' sets extra environment variables (e.g. RUSTFLAGS, which forces a full rebuild). From the second call on, 'changes_since_last_check' lists new and fixed issues compared to the previous check of the same project. Set 'summarize' on a badly broken build for a triage view (counts per error code, most frequent messages, files with the most errors).")]
+    async fn check_code(&self, params: Parameters<CheckCodeRequest>, context: RequestContext<RoleServer>) -> Result<CallToolResult, McpError> {
+        let CheckCodeRequest { path, all_targets, all_features, no_default_features, features, env, summarize, member, timeout_secs } = params.0;
+        let features = FeatureFlags::new(all_features, no_default_features, features);
+        let path = PathBuf::from(path);
 
         if !path.exists() {
             return Err(McpError::new(
@@ -219,13 +716,24 @@ impl RustBuilderServer {
                 None
             ));
         }
+        let path = self.resolve_member(path, member.as_deref(), &context.ct).await?;
 
-        let result = self.checker.check(path)
-            .map_err(|e| McpError::new(ErrorCode::INTERNAL_ERROR, e.to_string(), None))?;
+        let _permit = self.acquire_cargo_permit().await?;
+        let all_targets = all_targets.unwrap_or(false);
+        let env = env.unwrap_or_default();
+        let result = self.checker.check(path.clone(), all_targets, &features, &env, &context.ct, command_timeout(timeout_secs))
+            .await
+            .map_err(to_mcp_error)?;
+        let changes = self.checker.diff_with_previous(&path, all_targets, &features, &env, &result);
 
         let response = serde_json::json!({
-            "status": if result.success { "success" } else { "error" },
+            "status": if result.cancelled { "cancelled" } else if result.has_errors { "error" } else { "success" },
+            "has_errors": result.has_errors,
             "issue_count": result.messages.len(),
+            "error_count": result.error_count,
+            "warning_count": result.warning_count,
+            "changes_since_last_check": changes,
+            "summary": summarize.unwrap_or(false).then(|| result.summary()),
             "issues": result.messages
         });
 
@@ -234,35 +742,252 @@ impl RustBuilderServer {
         )]))
     }
 
-    #[tool(description = "Explains a Rust error code (e.g., E0308). Use this when 'check_code' returns an error code.")]
-    async fn explain_error(&self, params: Parameters<ExplainRequest>) -> Result<CallToolResult, McpError> {
-        let raw_code = params.0.error_code.trim().to_uppercase();
+    #[tool(description = "Runs 'cargo check' but only returns issues located in the given file. The build is still crate-wide; use 'include_all' to also get issues from other files.")]
+    async fn check_file(&self, params: Parameters<CheckFileRequest>, context: RequestContext<RoleServer>) -> Result<CallToolResult, McpError> {
+        let CheckFileRequest { path, file, include_all } = params.0;
+        let path = PathBuf::from(path);
 
-        if !raw_code.starts_with('E') {
+        if !path.exists() {
             return Err(McpError::new(
                 ErrorCode::INVALID_PARAMS,
-                format!("The error code '{}' is invalid. It must start with 'E' (e.g., E0308).", raw_code),
+                format!("The path '{}' does not exist. Please check the structure using 'get_project_structure'.", path.display()),
                 None
             ));
         }
 
-        let explanation = self.explainer.explain(&raw_code)
-            .map_err(|e| McpError::new(ErrorCode::INTERNAL_ERROR, e.to_string(), None))?;
+        let _permit = self.acquire_cargo_permit().await?;
+        let full = self.checker.check(path.clone(), false, &FeatureFlags::default(), &HashMap::new(), &context.ct, command_timeout(None))
+            .await
+            .map_err(to_mcp_error)?;
 
-        Ok(CallToolResult::success(vec![Content::text(explanation)]))
+        let project_has_errors = full.has_errors;
+        let all_issues = if include_all.unwrap_or(false) { Some(full.messages.clone()) } else { None };
+        let (result, other_count) = full.only_file(&path, &file);
+
+        let mut response = serde_json::json!({
+            "status": if result.cancelled { "cancelled" } else if result.has_errors { "error" } else { "success" },
+            "file": file,
+            "issue_count": result.messages.len(),
+            "error_count": result.error_count,
+            "warning_count": result.warning_count,
+            "issues": result.messages,
+            "other_files_issue_count": other_count,
+            "project_has_errors": project_has_errors
+        });
+        if let Some(all) = all_issues {
+            response["all_issues"] = serde_json::to_value(all).unwrap();
+        }
+
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::to_string_pretty(&response).unwrap()
+        )]))
     }
 
-    #[tool(description = "Displays the file structure of a project (ignores target/ and .git/).")]
-    async fn get_project_structure(&self, params: Parameters<StructureRequest>) -> Result<CallToolResult, McpError> {
+    #[tool(description = "Runs 'cargo check', applies every machine-applicable compiler suggestion (e.g. unused imports, missing 'mut') and re-checks. A suggestion overlapping an earlier one is skipped as a whole. Every changed file is backed up first. Safe to run repeatedly.")]
+    async fn apply_fixes(&self, params: Parameters<ApplyFixesRequest>, context: RequestContext<RoleServer>) -> Result<CallToolResult, McpError> {
+        self.ensure_writable("apply_fixes")?;
         let path = PathBuf::from(params.0.path);
 
-        if !path.exists() {
-            return Err(McpError::new(
-                ErrorCode::INVALID_PARAMS,
-                format!("The path '{}' was not found.", path.display()),
-                None
-            ));
-        }
+        let _permit = self.acquire_cargo_permit().await?;
+        let before = self.checker.check(path.clone(), false, &FeatureFlags::default(), &HashMap::new(), &context.ct, command_timeout(None))
+            .await
+            .map_err(to_mcp_error)?;
+
+        if before.cancelled {
+            return Ok(CallToolResult::success(vec![Content::text("Check cancelled by client. No fixes applied.")]));
+        }
+
+        if before.messages.iter().all(|m| m.suggestions.is_empty()) {
+            let response = serde_json::json!({
+                "status": "nothing_to_fix",
+                "applied": [],
+                "error_count": before.error_count,
+                "warning_count": before.warning_count,
+                "issues": before.messages
+            });
+            return Ok(CallToolResult::success(vec![Content::text(
+                serde_json::to_string_pretty(&response).unwrap()
+            )]));
+        }
+
+        // Span paths are relative to the workspace root, which differs from `path` for members
+        let workspace_root = cargo_metadata(&path, &context.ct, command_timeout(None))
+            .await
+            .map_err(to_mcp_error)?
+            .workspace_root;
+
+        // One fix per suggestion, so its edits are applied together or not at all
+        let fixes: Vec<Vec<_>> = before.messages.iter()
+            .flat_map(|m| {
+                let mut groups: std::collections::BTreeMap<usize, Vec<_>> = Default::default();
+                for s in &m.suggestions {
+                    groups.entry(s.group)
+                        .or_default()
+                        .push((workspace_root.join(&s.file), s.byte_start, s.byte_end, s.replacement.clone()));
+                }
+                groups.into_values()
+            })
+            .collect();
+
+        // All files are written together or not at all
+        let outcome = self.surgeon.apply_edits(fixes)
+            .await
+            .map_err(to_mcp_error)?;
+        let applied: Vec<_> = outcome.per_file.iter()
+            .map(|(file, count)| serde_json::json!({
+                "file": file.strip_prefix(&workspace_root).unwrap_or(file).display().to_string(),
+                "fixes": count,
+            }))
+            .collect();
+
+        let after = self.checker.check(path, false, &FeatureFlags::default(), &HashMap::new(), &context.ct, command_timeout(None))
+            .await
+            .map_err(to_mcp_error)?;
+
+        let response = serde_json::json!({
+            "status": if after.cancelled { "cancelled" } else if after.has_errors { "error" } else { "success" },
+            "applied": applied,
+            "skipped_overlapping": outcome.skipped,
+            "error_count": after.error_count,
+            "warning_count": after.warning_count,
+            "issues": after.messages
+        });
+
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::to_string_pretty(&response).unwrap()
+        )]))
+    }
+
+    #[tool(description = "Runs 'cargo check' and attaches the 'rustc --explain' text for every distinct error code found. One call for the whole failure loop.")]
+    async fn diagnose(&self, params: Parameters<CheckCodeRequest>, context: RequestContext<RoleServer>) -> Result<CallToolResult, McpError> {
+        let CheckCodeRequest { path, all_targets, all_features, no_default_features, features, env, summarize, member, timeout_secs } = params.0;
+        let features = FeatureFlags::new(all_features, no_default_features, features);
+        let path = PathBuf::from(path);
+
+        if !path.exists() {
+            return Err(McpError::new(
+                ErrorCode::INVALID_PARAMS,
+                format!("The path '{}' does not exist. Please check the structure using 'get_project_structure'.", path.display()),
+                None
+            ));
+        }
+        let path = self.resolve_member(path, member.as_deref(), &context.ct).await?;
+
+        let _permit = self.acquire_cargo_permit().await?;
+        let result = self.checker.check(path, all_targets.unwrap_or(false), &features, &env.unwrap_or_default(), &context.ct, command_timeout(timeout_secs))
+            .await
+            .map_err(to_mcp_error)?;
+
+        // Each code is explained once, no matter how often it occurs
+        let codes: std::collections::BTreeSet<String> = result.messages.iter()
+            .filter_map(|m| m.code.clone())
+            .filter(|c| c.starts_with('E'))
+            .collect();
+
+        let mut explanations = serde_json::Map::new();
+        for code in codes {
+            let text = self.explainer.explain(&code, &context.ct, command_timeout(timeout_secs))
+                .await
+                .unwrap_or_else(|e| format!("Could not explain {}: {}", code, e));
+            explanations.insert(code, serde_json::Value::String(text));
+        }
+
+        let response = serde_json::json!({
+            "status": if result.cancelled { "cancelled" } else if result.has_errors { "error" } else { "success" },
+            "error_count": result.error_count,
+            "warning_count": result.warning_count,
+            "summary": summarize.unwrap_or(false).then(|| result.summary()),
+            "issues": result.messages,
+            "explanations": explanations
+        });
+
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::to_string_pretty(&response).unwrap()
+        )]))
+    }
+
+    #[tool(description = "Runs 'cargo fix' to apply rustc's own machine-applicable suggestions (not clippy's); with 'edition_migration' it applies the lints for the next edition. MODIFIES FILES and returns the diff. Refuses on uncommitted changes unless 'allow_dirty' is true - commit first so the result can be reviewed and undone.")]
+    async fn cargo_fix(&self, params: Parameters<CargoFixRequest>, context: RequestContext<RoleServer>) -> Result<CallToolResult, McpError> {
+        self.ensure_writable("cargo_fix")?;
+        let CargoFixRequest { path, edition_migration, allow_dirty, timeout_secs } = params.0;
+
+        let _permit = self.acquire_cargo_permit().await?;
+        let output = self.fixer.fix(
+            PathBuf::from(path),
+            edition_migration.unwrap_or(false),
+            allow_dirty.unwrap_or(false),
+            &context.ct,
+            command_timeout(timeout_secs),
+        )
+            .await
+            .map_err(to_mcp_error)?;
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(description = "Explains raw compiler output (e.g. a pasted build log): finds error codes like E0308, deduplicates them and returns the 'rustc --explain' text with the matching lines, plus error:/warning: lines without a code. At most 5 codes are explained.")]
+    async fn explain_output(&self, params: Parameters<ExplainOutputRequest>, context: RequestContext<RoleServer>) -> Result<CallToolResult, McpError> {
+        let explanation = self.explainer.explain_output(&params.0.output, &context.ct, command_timeout(None)).await;
+
+        Ok(CallToolResult::success(vec![Content::text(serde_json::to_string_pretty(&explanation).unwrap())]))
+    }
+
+    #[tool(description = "Runs a cargo subcommand without a dedicated tool (e.g. 'tree' with args [\"-i\", \"serde\"]) in the project directory and returns its output. Only subcommands on the server's allowlist are permitted; no shell is involved. 'env' adds environment variables (e.g. RUSTFLAGS for 'build'; changing it forces a full rebuild). Prefer the dedicated tools (check_code, run_tests, ...) when they exist.")]
+    async fn cargo_command(&self, params: Parameters<CargoCommandRequest>, context: RequestContext<RoleServer>) -> Result<CallToolResult, McpError> {
+        let CargoCommandRequest { path, subcommand, args, toolchain, env, timeout_secs } = params.0;
+        let args = args.unwrap_or_default();
+
+        if is_mutating(&subcommand, &args) {
+            self.ensure_writable(&format!("cargo_command {}", subcommand))?;
+        }
+
+        let _permit = self.acquire_cargo_permit().await?;
+        let output = self.cargo_command.run(
+            PathBuf::from(path),
+            &subcommand,
+            &args,
+            toolchain.as_deref(),
+            &env.unwrap_or_default(),
+            &context.ct,
+            command_timeout(timeout_secs),
+        )
+            .await
+            .map_err(to_mcp_error)?;
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(description = "Explains a Rust error code (e.g., E0308). Use this when 'check_code' returns an error code.")]
+    async fn explain_error(&self, params: Parameters<ExplainRequest>, context: RequestContext<RoleServer>) -> Result<CallToolResult, McpError> {
+        let raw_code = params.0.error_code.trim().to_uppercase();
+
+        if !raw_code.starts_with('E') {
+            return Err(McpError::new(
+                ErrorCode::INVALID_PARAMS,
+                format!("The error code '{}' is invalid. It must start with 'E' (e.g., E0308).", raw_code),
+                None
+            ));
+        }
+
+        let explanation = self.explainer.explain(&raw_code, &context.ct, command_timeout(None))
+            .await
+            .map_err(to_mcp_error)?;
+
+        Ok(CallToolResult::success(vec![Content::text(explanation)]))
+    }
+
+    #[tool(description = "Displays the file structure of a project (ignores target/ and .git/). Use 'extensions' (e.g. [\"rs\", \"toml\"]) to hide other files. Large trees are paginated: pass the returned 'next_offset' as 'offset' to continue.")]
+    async fn get_project_structure(&self, params: Parameters<StructureRequest>, context: RequestContext<RoleServer>) -> Result<CallToolResult, McpError> {
+        let StructureRequest { path, extensions, member, offset, limit } = params.0;
+        let path = PathBuf::from(path);
+
+        if !path.exists() {
+            return Err(McpError::new(
+                ErrorCode::INVALID_PARAMS,
+                format!("The path '{}' was not found.", path.display()),
+                None
+            ));
+        }
 
         if !path.is_dir() {
             return Err(McpError::new(
@@ -271,21 +996,132 @@ impl RustBuilderServer {
                 None
             ));
         }
+        let path = self.resolve_member(path, member.as_deref(), &context.ct).await?;
 
-        let structure = self.project_manager.get_structure(path)
-            .map_err(|e| McpError::new(ErrorCode::INTERNAL_ERROR, e.to_string(), None))?;
+        let structure = self.project_manager.get_structure(path, extensions.as_deref(), offset.unwrap_or(0), limit)
+            .map_err(to_mcp_error)?;
 
         Ok(CallToolResult::success(vec![Content::text(structure)]))
     }
 
-    #[tool(description = "Adds a dependency to a project via 'cargo add'.")]
-    async fn add_dependency(&self, params: Parameters<AddDepRequest>) -> Result<CallToolResult, McpError> {
-        let AddDepRequest { project_path, crate_name, features } = params.0;
+    #[tool(description = "Lists the packages of a Cargo workspace (name, version, manifest path, directory) via 'cargo metadata'. Pass a member's name as 'member' to get_project_structure, check_code, diagnose or run_tests to target it.")]
+    async fn list_workspace_members(&self, params: Parameters<ListWorkspaceMembersRequest>, context: RequestContext<RoleServer>) -> Result<CallToolResult, McpError> {
+        let _permit = self.acquire_cargo_permit().await?;
+        let members = self.project_manager.workspace_members(&PathBuf::from(params.0.path), &context.ct, command_timeout(None))
+            .await
+            .map_err(to_mcp_error)?;
+
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::to_string_pretty(&members).unwrap()
+        )]))
+    }
+
+    #[tool(description = "Searches the project's files line by line (ignores target/ and .git/). Set 'count_only' to get per-file match counts and a total first, then fetch the lines only if needed.")]
+    async fn grep_project(&self, params: Parameters<GrepRequest>) -> Result<CallToolResult, McpError> {
+        let GrepRequest { path, pattern, regex, count_only } = params.0;
+
+        let output = self.project_manager.grep(&PathBuf::from(path), &pattern, regex.unwrap_or(false), count_only.unwrap_or(false))
+            .map_err(to_mcp_error)?;
+
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::to_string_pretty(&output).unwrap()
+        )]))
+    }
+
+    #[tool(description = "Lists unfinished work in the project's .rs files: '// TODO', '// FIXME', and todo!()/unimplemented!() calls (runtime panics). Returns file, line, kind and the line text.")]
+    async fn list_todos(&self, params: Parameters<ListTodosRequest>) -> Result<CallToolResult, McpError> {
+        let todos = self.project_manager.list_todos(&PathBuf::from(params.0.path))
+            .map_err(to_mcp_error)?;
+
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::to_string_pretty(&todos).unwrap()
+        )]))
+    }
+
+    #[tool(description = "Lists the project's .rs files sorted by modification time, newest first, with relative paths and timestamps ('modified', 'modified_ago'). Use it to re-orient when resuming work; complements git history for uncommitted changes.")]
+    async fn recently_modified(&self, params: Parameters<RecentlyModifiedRequest>) -> Result<CallToolResult, McpError> {
+        let RecentlyModifiedRequest { path, limit } = params.0;
+
+        let files = self.project_manager.recently_modified(&PathBuf::from(path), limit)
+            .map_err(to_mcp_error)?;
+
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::to_string_pretty(&files).unwrap()
+        )]))
+    }
+
+    #[tool(description = "Parses a project's Cargo.toml and returns structured JSON: package, dependencies, dev-dependencies, build-dependencies, features and workspace. Prefer this over read_file when you need manifest values.")]
+    async fn read_manifest(&self, params: Parameters<ReadManifestRequest>) -> Result<CallToolResult, McpError> {
+        let manifest = self.manifest.read(PathBuf::from(params.0.path))
+            .await
+            .map_err(to_mcp_error)?;
+
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::to_string_pretty(&manifest).unwrap()
+        )]))
+    }
+
+    #[tool(description = "Reports what kind of project a Cargo.toml describes: 'binary', 'library', 'both', or 'workspace' (virtual root without [package]), with the crate name, edition, bin and example targets (declared and auto-discovered). Use it to decide how to build, test and run the project.")]
+    async fn project_kind(&self, params: Parameters<ProjectKindRequest>) -> Result<CallToolResult, McpError> {
+        let kind = self.manifest.project_kind(PathBuf::from(params.0.path))
+            .await
+            .map_err(to_mcp_error)?;
+
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::to_string_pretty(&kind).unwrap()
+        )]))
+    }
+
+    #[tool(description = "Reads Cargo.lock (of the project or its workspace) and returns the resolved version of each package, optionally only for 'name' together with the packages that depend on it. Explains which version was actually picked, as opposed to the manifest requirement. Local parsing only; reports gracefully when there is no lockfile.")]
+    async fn resolved_versions(&self, params: Parameters<ResolvedVersionsRequest>) -> Result<CallToolResult, McpError> {
+        let ResolvedVersionsRequest { path, name } = params.0;
+
+        let resolved = self.manifest.resolved_versions(PathBuf::from(path), name.as_deref())
+            .await
+            .map_err(to_mcp_error)?;
+
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::to_string_pretty(&resolved).unwrap()
+        )]))
+    }
+
+    #[tool(description = "Shows how the project's Cargo.toml declares one dependency: version requirement, enabled 'features', whether default features are disabled, optional/workspace flags. Covers dev-, build- and target-specific sections. Check this before relying on feature-gated APIs.")]
+    async fn get_dependency_config(&self, params: Parameters<DependencyConfigRequest>) -> Result<CallToolResult, McpError> {
+        let DependencyConfigRequest { path, name } = params.0;
+
+        let config = self.manifest.dependency_config(PathBuf::from(path), &name)
+            .await
+            .map_err(to_mcp_error)?;
+
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::to_string_pretty(&config).unwrap()
+        )]))
+    }
+
+    #[tool(description = "Sets a single Cargo.toml field by dotted path (e.g. field='dependencies.serde.features', value='[\"derive\"]') while preserving formatting and comments. Safer than patch_file for manifest edits.")]
+    async fn set_manifest_field(&self, params: Parameters<SetManifestFieldRequest>) -> Result<CallToolResult, McpError> {
+        self.ensure_writable("set_manifest_field")?;
+        let SetManifestFieldRequest { path, field, value } = params.0;
+
+        let result = self.manifest.set_field(PathBuf::from(path), &field, &value)
+            .await
+            .map_err(to_mcp_error)?;
+
+        Ok(CallToolResult::success(vec![Content::text(result)]))
+    }
+
+    #[tool(description = "Adds a dependency to a project via 'cargo add'. Set 'dry_run' to preview the resolved version and features without touching Cargo.toml.")]
+    async fn add_dependency(&self, params: Parameters<AddDepRequest>, context: RequestContext<RoleServer>) -> Result<CallToolResult, McpError> {
+        let AddDepRequest { project_path, crate_name, features, dry_run, timeout_secs } = params.0;
         let path = PathBuf::from(project_path);
+        if !dry_run.unwrap_or(false) {
+            self.ensure_writable("add_dependency")?;
+        }
 
-        let result = self.dep_manager.add_dependency(path, &crate_name, features)
+        let _permit = self.acquire_cargo_permit().await?;
+        let result = self.dep_manager.add_dependency(path, &crate_name, features, dry_run.unwrap_or(false), &context.ct, command_timeout(timeout_secs))
             .await
-            .map_err(|e| McpError::new(ErrorCode::INTERNAL_ERROR, e.to_string(), None))?;
+            .map_err(to_mcp_error)?;
 
         Ok(CallToolResult::success(vec![Content::text(result)]))
     }
@@ -301,42 +1137,247 @@ impl RustBuilderServer {
         let content = tokio::fs::read_to_string(&path).await
             .map_err(|e| McpError::new(ErrorCode::INTERNAL_ERROR, e.to_string(), None))?;
 
-        // Add line numbers for the AI
-        let numbered_lines: String = content.lines()
-            .enumerate()
-            .map(|(i, line)| format!("{:04} | {}", i + 1, line))
-            .collect::<Vec<_>>()
-            .join("\n");
+        Ok(CallToolResult::success(vec![Content::text(number_lines(&content))]))
+    }
+
+    #[tool(description = "Reads several files at once (e.g. main.rs, mod.rs, Cargo.toml) with line numbers. Missing files are reported per path instead of failing the batch.")]
+    async fn read_files(&self, params: Parameters<ReadFilesRequest>) -> Result<CallToolResult, McpError> {
+        // Keep the combined response within a reasonable context budget
+        const MAX_TOTAL_BYTES: usize = 200_000;
+
+        let mut files = serde_json::Map::new();
+        let mut total_bytes = 0;
+        let mut skipped = Vec::new();
+
+        for path in params.0.paths {
+            if total_bytes >= MAX_TOTAL_BYTES {
+                skipped.push(path);
+                continue;
+            }
 
-        Ok(CallToolResult::success(vec![Content::text(numbered_lines)]))
+            let entry = match tokio::fs::read_to_string(&path).await {
+                Ok(content) => {
+                    let mut numbered = number_lines(&content);
+                    let remaining = MAX_TOTAL_BYTES - total_bytes;
+                    if numbered.len() > remaining {
+                        let mut cut = remaining;
+                        while !numbered.is_char_boundary(cut) {
+                            cut -= 1;
+                        }
+                        numbered.truncate(cut);
+                        numbered.push_str("\n[... truncated: total size limit reached ...]");
+                    }
+                    total_bytes += numbered.len();
+                    serde_json::json!({ "content": numbered })
+                }
+                Err(e) => serde_json::json!({ "error": format!("{}: {}", path, e) }),
+            };
+            files.insert(path, entry);
+        }
+
+        let response = serde_json::json!({
+            "files": files,
+            "truncated": !skipped.is_empty() || total_bytes >= MAX_TOTAL_BYTES,
+            "not_read_due_to_size_limit": skipped
+        });
+
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::to_string_pretty(&response).unwrap()
+        )]))
+    }
+
+    #[tool(description = "Replaces the body of a function (or with 'whole_item' the entire function) located by name via the syntax tree. Safer than patch_file for whole functions: nested braces can't break the match. The new code is validated and formatted with prettyplease (plain // comments in it are dropped).")]
+    async fn replace_function(&self, params: Parameters<ReplaceFunctionRequest>) -> Result<CallToolResult, McpError> {
+        self.ensure_writable("replace_function")?;
+        let ReplaceFunctionRequest { path, name, new_source, whole_item } = params.0;
+
+        let result = self.surgeon.replace_function(PathBuf::from(path), &name, &new_source, whole_item.unwrap_or(false))
+            .await
+            .map_err(to_mcp_error)?;
+
+        Ok(CallToolResult::success(vec![Content::text(result)]))
     }
 
-    #[tool(description = "Patches a file using search and replace (File Surgeon). More secure than complete overwriting. Paths must always include the file, e.g., \"/home/.../.../tools/test.rs\".")]
+    #[tool(description = "Patches a file using search and replace (File Surgeon). More secure than complete overwriting. Replaces the first match; set 'require_unique' to fail (listing all match lines) when the snippet is ambiguous. Paths must always include the file, e.g., \"/home/.../.../tools/test.rs\".")]
     async fn patch_file(&self, params: Parameters<PatchFileRequest>) -> Result<CallToolResult, McpError> {
-        let PatchFileRequest { path, original_snippet, modified_snippet } = params.0;
+        self.ensure_writable("patch_file")?;
+        let PatchFileRequest { path, original_snippet, modified_snippet, regex, require_unique } = params.0;
         let file_path = PathBuf::from(path);
 
-        let result = self.surgeon.patch_file(file_path, &original_snippet, &modified_snippet)
+        let result = if regex.unwrap_or(false) {
+            self.surgeon.patch_file_regex(file_path, &original_snippet, &modified_snippet).await
+        } else {
+            self.surgeon.patch_file(file_path, &original_snippet, &modified_snippet, require_unique.unwrap_or(false)).await
+        };
+
+        let result = result
+            .map_err(to_mcp_error)?;
+
+        Ok(CallToolResult::success(vec![Content::text(result)]))
+    }
+
+    #[tool(description = "The safe-edit loop in one call: commits the current state (tracked files and the patched file only; untracked files are never committed), applies a patch (as 'patch_file'), runs 'cargo fmt' and 'cargo check', and resets to the pre-edit commit if the check reports errors. Returns every step's output ('commit', 'patch', 'fmt', 'check', 'revert') so you can see where it stopped. Requires a git repository.")]
+    async fn run_checked_edit(&self, params: Parameters<CheckedEditRequest>, context: RequestContext<RoleServer>) -> Result<CallToolResult, McpError> {
+        self.ensure_writable("run_checked_edit")?;
+
+        let _permit = self.acquire_cargo_permit().await?;
+        let report = self.checked_editor
+            .run(params.0, &self.git, &self.surgeon, &self.polisher, &self.checker, &context.ct)
             .await
-            .map_err(|e| McpError::new(ErrorCode::INTERNAL_ERROR, e.to_string(), None))?;
+            .map_err(to_mcp_error)?;
+
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::to_string_pretty(&report).unwrap()
+        )]))
+    }
+
+    #[tool(description = "Returns a reliable 'original_snippet' for patch_file: the given lines, extended line by line until the snippet occurs exactly once in the file. Use it before patching code that may repeat (closing braces, common statements).")]
+    async fn suggest_patch_anchor(&self, params: Parameters<SuggestAnchorRequest>) -> Result<CallToolResult, McpError> {
+        let SuggestAnchorRequest { path, start_line, end_line } = params.0;
+
+        let anchor = self.surgeon.suggest_anchor(PathBuf::from(path), start_line, end_line.unwrap_or(start_line))
+            .await
+            .map_err(to_mcp_error)?;
+
+        Ok(CallToolResult::success(vec![Content::text(serde_json::to_string_pretty(&anchor).unwrap())]))
+    }
+
+    #[tool(description = "Applies several patch_file operations across files as one transaction: all snippets are validated first, and nothing is written unless every operation matches. Returns per-file summaries.")]
+    async fn patch_files(&self, params: Parameters<PatchFilesRequest>) -> Result<CallToolResult, McpError> {
+        self.ensure_writable("patch_files")?;
+        let summaries = self.surgeon.patch_files(params.0.operations)
+            .await
+            .map_err(to_mcp_error)?;
+
+        let response = serde_json::json!({
+            "status": "success",
+            "files": summaries
+        });
+
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::to_string_pretty(&response).unwrap()
+        )]))
+    }
+
+    #[tool(description = "Reverts a single file to the state before its last patch_file/patch_files edit (from .mcp-backups/). Call repeatedly to step further back. Does not touch git or other files.")]
+    async fn restore_file(&self, params: Parameters<RestoreFileRequest>) -> Result<CallToolResult, McpError> {
+        self.ensure_writable("restore_file")?;
+        let result = self.surgeon.restore_file(PathBuf::from(params.0.path))
+            .await
+            .map_err(to_mcp_error)?;
 
         Ok(CallToolResult::success(vec![Content::text(result)]))
     }
 
-    #[tool(description = "Runs 'cargo test'. Use this to verify code changes.")]
-    async fn run_tests(&self, params: Parameters<RunTestsRequest>) -> Result<CallToolResult, McpError> {
-        let RunTestsRequest { path, filter } = params.0;
-        let project_path = PathBuf::from(path);
+    #[tool(description = "Creates a directory and any missing parents (mkdir -p), e.g. before adding a nested module. Returns whether it was newly created.")]
+    async fn create_dir(&self, params: Parameters<CreateDirRequest>) -> Result<CallToolResult, McpError> {
+        self.ensure_writable("create_dir")?;
+        let created = self.surgeon.create_dir(PathBuf::from(params.0.path))
+            .await
+            .map_err(to_mcp_error)?;
 
-        let output = self.test_runner.run(project_path, filter)
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::to_string_pretty(&created).unwrap()
+        )]))
+    }
+
+    #[tool(description = "Creates an empty file together with its missing parent directories (e.g. 'src/tools/net/http.rs'), ready to be filled via patch_file. An existing file is left unchanged. Returns whether it was newly created.")]
+    async fn touch_file(&self, params: Parameters<TouchFileRequest>) -> Result<CallToolResult, McpError> {
+        self.ensure_writable("touch_file")?;
+        let created = self.surgeon.touch_file(PathBuf::from(params.0.path))
             .await
-            .map_err(|e| McpError::new(ErrorCode::INTERNAL_ERROR, e.to_string(), None))?;
+            .map_err(to_mcp_error)?;
+
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::to_string_pretty(&created).unwrap()
+        )]))
+    }
+
+    #[tool(description = "Compares two arbitrary files (e.g. old vs. new implementation) and returns a unified diff. Works on files outside version control; use git_operations 'diff' for uncommitted changes.")]
+    async fn diff_files(&self, params: Parameters<DiffFilesRequest>) -> Result<CallToolResult, McpError> {
+        let DiffFilesRequest { old_path, new_path } = params.0;
+
+        let diff = self.surgeon.diff_files(PathBuf::from(old_path), PathBuf::from(new_path))
+            .await
+            .map_err(to_mcp_error)?;
+
+        Ok(CallToolResult::success(vec![Content::text(diff)]))
+    }
+
+    #[tool(description = "Runs 'cargo test'. Use this to verify code changes. 'all_features', 'no_default_features' and 'features' select the feature set. Streams output as progress notifications if the client provides a progress token.")]
+    async fn run_tests(&self, params: Parameters<RunTestsRequest>, context: RequestContext<RoleServer>) -> Result<CallToolResult, McpError> {
+        let RunTestsRequest { path, member, filter, release, include_ignored, all_features, no_default_features, features, timeout_secs } = params.0;
+        let features = FeatureFlags::new(all_features, no_default_features, features);
+        let project_path = self.resolve_member(PathBuf::from(path), member.as_deref(), &context.ct).await?;
+
+        let progress = self.progress_forwarder(&context);
+
+        let _permit = self.acquire_cargo_permit().await?;
+        let output = self.test_runner.run(project_path, filter, release.unwrap_or(false), include_ignored.unwrap_or(false), &features, &context.ct, command_timeout(timeout_secs), progress)
+            .await
+            .map_err(to_mcp_error)?;
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(description = "Runs the doc examples of a single item ('cargo test --doc <item>'), e.g. 'parser::Parser::parse', to iterate on one failing doctest. Reports clearly when no doctest matches.")]
+    async fn run_doctest(&self, params: Parameters<RunDoctestRequest>, context: RequestContext<RoleServer>) -> Result<CallToolResult, McpError> {
+        let RunDoctestRequest { path, member, item, timeout_secs } = params.0;
+        let project_path = self.resolve_member(PathBuf::from(path), member.as_deref(), &context.ct).await?;
+
+        let _permit = self.acquire_cargo_permit().await?;
+        let output = self.test_runner.run_doctest(project_path, &item, &context.ct, command_timeout(timeout_secs))
+            .await
+            .map_err(to_mcp_error)?;
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(description = "Runs 'cargo bench' (optionally filtered) with a generous, killable timeout. Note: built-in #[bench] requires a nightly toolchain; stable projects typically use criterion benches.")]
+    async fn run_benchmarks(&self, params: Parameters<RunBenchRequest>, context: RequestContext<RoleServer>) -> Result<CallToolResult, McpError> {
+        let RunBenchRequest { path, filter, timeout_secs } = params.0;
+        let timeout = timeout_secs
+            .map(std::time::Duration::from_secs)
+            .unwrap_or(BenchRunner::DEFAULT_TIMEOUT);
+
+        let _permit = self.acquire_cargo_permit().await?;
+        let output = self.bench_runner.run(PathBuf::from(path), filter, timeout, &context.ct)
+            .await
+            .map_err(to_mcp_error)?;
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(description = "Lists the example targets ('examples/') of the project and its workspace members, as reported by 'cargo metadata'.")]
+    async fn list_examples(&self, params: Parameters<ListExamplesRequest>, context: RequestContext<RoleServer>) -> Result<CallToolResult, McpError> {
+        let _permit = self.acquire_cargo_permit().await?;
+        let examples = self.example_runner.list(PathBuf::from(params.0.path), &context.ct, command_timeout(None))
+            .await
+            .map_err(to_mcp_error)?;
+
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::to_string_pretty(&examples).unwrap()
+        )]))
+    }
+
+    #[tool(description = "Runs 'cargo run --example <name>' with optional arguments and a killable timeout (default 120s). WARNING: Examples execute arbitrary code from the project - only run examples of projects you trust.")]
+    async fn run_example(&self, params: Parameters<RunExampleRequest>, context: RequestContext<RoleServer>) -> Result<CallToolResult, McpError> {
+        let RunExampleRequest { path, name, args, timeout_secs } = params.0;
+        let timeout = timeout_secs
+            .map(std::time::Duration::from_secs)
+            .unwrap_or(ExampleRunner::DEFAULT_TIMEOUT);
+
+        let _permit = self.acquire_cargo_permit().await?;
+        let output = self.example_runner.run(PathBuf::from(path), name.trim(), &args.unwrap_or_default(), timeout, &context.ct)
+            .await
+            .map_err(to_mcp_error)?;
 
         Ok(CallToolResult::success(vec![Content::text(output)]))
     }
 
     #[tool(description = "Creates the basic framework for a new MCP tool (create file + mod.rs update). Returns instructions for main.rs.")]
     async fn scaffold_new_tool(&self, params: Parameters<ScaffoldToolRequest>) -> Result<CallToolResult, McpError> {
+        self.ensure_writable("scaffold_new_tool")?;
         let ScaffoldToolRequest { project_path, tool_name, struct_name, description } = params.0;
 
         let result = self.scaffolder.create_tool(
@@ -344,7 +1385,20 @@ impl RustBuilderServer {
             tool_name,
             struct_name,
             description
-        ).await.map_err(|e| McpError::new(ErrorCode::INTERNAL_ERROR, e.to_string(), None))?;
+        ).await.map_err(to_mcp_error)?;
+
+        Ok(CallToolResult::success(vec![Content::text(result)]))
+    }
+
+    #[tool(description = "Creates a new binary crate ready to be an MCP server: runs 'cargo new', adds rmcp/tokio/serde/schemars/anyhow and writes a compiling starter main.rs with a src/tools/ module.")]
+    async fn init_mcp_server(&self, params: Parameters<InitMcpServerRequest>, context: RequestContext<RoleServer>) -> Result<CallToolResult, McpError> {
+        self.ensure_writable("init_mcp_server")?;
+        let InitMcpServerRequest { parent_path, name, timeout_secs } = params.0;
+
+        let _permit = self.acquire_cargo_permit().await?;
+        let result = self.scaffolder.init_server(PathBuf::from(parent_path), &name, &context.ct, command_timeout(timeout_secs))
+            .await
+            .map_err(to_mcp_error)?;
 
         Ok(CallToolResult::success(vec![Content::text(result)]))
     }
@@ -353,58 +1407,225 @@ impl RustBuilderServer {
     async fn get_mcp_template(&self, params: Parameters<GetPatternRequest>) -> Result<CallToolResult, McpError> {
         let topic = params.0.topic.to_lowercase();
         let template = self.patterns.get_template(&topic)
-            .map_err(|e| McpError::new(ErrorCode::INVALID_PARAMS, e.to_string(), None))?;
+            .map_err(to_mcp_error)?;
 
         Ok(CallToolResult::success(vec![Content::text(template)]))
     }
 
-    #[tool(description = "Manages version control. Use 'commit' to save progress, and 'undo' to revert the last edit if it broke the build.")]
-    async fn git_operations(&self, params: Parameters<GitRequest>) -> Result<CallToolResult, McpError> {
-        let GitRequest { path, operation, message } = params.0;
+    #[tool(description = "Manages version control. Use 'change_summary' for a per-file count of uncommitted changes, 'commit' to save progress (returns the commit hash), 'undo' to revert uncommitted edits, and 'reset_to' with a hash to roll back a failed experiment.")]
+    async fn git_operations(&self, params: Parameters<GitRequest>, context: RequestContext<RoleServer>) -> Result<CallToolResult, McpError> {
+        let GitRequest { path, operation, message, hash, author_name, author_email, require_fmt, timeout_secs } = params.0;
+        let timeout = command_timeout(timeout_secs);
         let path_buf = PathBuf::from(path);
 
+        if matches!(operation.as_str(), "commit" | "undo" | "reset_to") {
+            self.ensure_writable(&format!("git_operations({})", operation))?;
+        }
+
         let result = match operation.as_str() {
-            "status" => self.git.status(path_buf).await,
-            "diff" => self.git.diff(path_buf).await,
-            "undo" => self.git.undo(path_buf).await,
+            "status" => self.git.status(path_buf, &context.ct, timeout).await,
+            "diff" => self.git.diff(path_buf, &context.ct, timeout).await,
+            "change_summary" => self.git.change_summary(path_buf, &context.ct, timeout).await,
+            "undo" => self.git.undo(path_buf, &context.ct, timeout).await,
             "commit" => {
                 let msg = message.unwrap_or_else(|| "WIP: Auto-commit".to_string());
-                self.git.commit(path_buf, msg).await
+                let author = match (author_name, author_email) {
+                    (Some(name), Some(email)) => Some((name, email)),
+                    (None, None) => None,
+                    _ => return Err(McpError::new(
+                        ErrorCode::INVALID_PARAMS,
+                        "Provide both 'author_name' and 'author_email', or neither.",
+                        None
+                    )),
+                };
+
+                if require_fmt.unwrap_or(false) {
+                    let unformatted = {
+                        let _permit = self.acquire_cargo_permit().await?;
+                        self.polisher.unformatted_files(path_buf.clone(), &context.ct, timeout)
+                            .await
+                            .map_err(to_mcp_error)?
+                    };
+                    if !unformatted.is_empty() {
+                        return Err(McpError::new(
+                            ErrorCode::INVALID_REQUEST,
+                            format!(
+                                "Commit refused: {} file(s) need formatting:\n{}\nRun 'polish_code' with mode='fmt', then commit again.",
+                                unformatted.len(),
+                                unformatted.join("\n")
+                            ),
+                            Some(serde_json::json!({ "kind": "needs_fmt", "files": unformatted }))
+                        ));
+                    }
+                }
+
+                self.git.commit(path_buf, msg, author, &context.ct, timeout).await
+            },
+            "reset_to" => match hash {
+                Some(hash) => self.git.reset_to(path_buf, hash.trim(), &context.ct, timeout).await,
+                None => Err(ToolError::InvalidInput("'reset_to' requires a 'hash' (returned by 'commit').".to_string()).into()),
             },
-            _ => Err(anyhow::anyhow!("Unknown git operation. Use status, diff, commit, or undo.")),
+            _ => Err(ToolError::InvalidInput("Unknown git operation. Use status, diff, change_summary, commit, undo, or reset_to.".to_string()).into()),
         };
 
-        let text = result.map_err(|e| McpError::new(ErrorCode::INTERNAL_ERROR, e.to_string(), None))?;
+        let text = result.map_err(to_mcp_error)?;
         Ok(CallToolResult::success(vec![Content::text(text)]))
     }
 
-    #[tool(description = "Checks code quality. 'fmt' cleans up whitespace (Safe). 'clippy' reports lints/errors but does NOT change code (Safe).")]
-    async fn polish_code(&self, params: Parameters<PolishRequest>) -> Result<CallToolResult, McpError> {
-        let path_buf = PathBuf::from(params.0.path);
+    #[tool(description = "Checks code quality. 'fmt' cleans up whitespace (Safe; pass 'file' to only format one file). 'clippy' reports lints/errors but does NOT change code (Safe); by default a fast pass over the default targets and features, set 'all_targets' and 'all_features'/'no_default_features'/'features' for a thorough review.")]
+    async fn polish_code(&self, params: Parameters<PolishRequest>, context: RequestContext<RoleServer>) -> Result<CallToolResult, McpError> {
+        let PolishRequest { path, mode, file, all_targets, all_features, no_default_features, features, timeout_secs } = params.0;
+        let timeout = command_timeout(timeout_secs);
+        let path_buf = PathBuf::from(path);
+        if mode == "fmt" {
+            self.ensure_writable("polish_code(fmt)")?;
+        }
 
-        let result = match params.0.mode.as_str() {
-            "fmt" => self.polisher.run_fmt(path_buf).await,
-            "clippy" => self.polisher.run_clippy(path_buf).await,
-            _ => Err(anyhow::anyhow!("Unknown polish mode. Use 'fmt' or 'clippy'")),
+        let _permit = self.acquire_cargo_permit().await?;
+        let result = match mode.as_str() {
+            "fmt" => match file {
+                Some(file) => self.polisher.fmt_file(path_buf, &file, &context.ct, timeout).await,
+                None => self.polisher.run_fmt(path_buf, &context.ct, timeout).await,
+            },
+            "clippy" => {
+                let features = FeatureFlags::new(all_features, no_default_features, features);
+                self.polisher.run_clippy(path_buf, all_targets.unwrap_or(false), &features, &context.ct, timeout).await
+            },
+            _ => Err(ToolError::InvalidInput("Unknown polish mode. Use 'fmt' or 'clippy'".to_string()).into()),
         };
 
-        let text = result.map_err(|e| McpError::new(ErrorCode::INTERNAL_ERROR, e.to_string(), None))?;
+        let text = result.map_err(to_mcp_error)?;
         Ok(CallToolResult::success(vec![Content::text(text)]))
     }
 
-    #[tool(description = "Parses a Rust file and returns a high-level outline (structs, fields, function signatures) ignoring function bodies. Use this to understand large files quickly.")]
+    #[tool(description = "Parses a Rust file and returns a high-level outline (structs, fields, function signatures) ignoring function bodies. Use this to understand large files quickly. Optionally restrict to a line range or filter by visibility (e.g. only the public API).")]
     async fn analyze_code(&self, params: Parameters<AnalyzeRequest>) -> Result<CallToolResult, McpError> {
-        let path = PathBuf::from(params.0.path);
-        let outline = self.analyzer.analyze(path)
-            .await
-            .map_err(|e| McpError::new(ErrorCode::INTERNAL_ERROR, e.to_string(), None))?;
+        let AnalyzeRequest { path, start_line, end_line, format, visibility_filter } = params.0;
+        let path = PathBuf::from(path);
+
+        let visibility = match visibility_filter.as_deref() {
+            None => VisibilityFilter::All,
+            Some(value) => VisibilityFilter::parse(value).ok_or_else(|| McpError::new(
+                ErrorCode::INVALID_PARAMS,
+                format!("Unknown visibility_filter '{}'. Use 'all', 'crate' or 'public'.", value),
+                None
+            ))?,
+        };
+
+        let range = match (start_line, end_line) {
+            (None, None) => None,
+            (start, end) => Some((start.unwrap_or(1), end.unwrap_or(usize::MAX))),
+        };
+
+        let outline = match format.as_deref().unwrap_or("text") {
+            "text" => self.analyzer.analyze(path, range, visibility)
+                .await
+                .map_err(to_mcp_error)?,
+            "json" => {
+                let nodes = self.analyzer.analyze_json(path, range, visibility)
+                    .await
+                    .map_err(to_mcp_error)?;
+                serde_json::to_string_pretty(&nodes).unwrap()
+            }
+            other => return Err(McpError::new(
+                ErrorCode::INVALID_PARAMS,
+                format!("Unknown format '{}'. Use 'text' or 'json'.", other),
+                None
+            )),
+        };
 
         Ok(CallToolResult::success(vec![Content::text(outline)]))
     }
 
+    #[tool(description = "Reports circular 'use' dependencies between the modules of a crate (follows 'mod' declarations from src/lib.rs and src/main.rs). Approximate: resolution is identifier-based, so imports of parent re-exports can also appear as cycles.")]
+    async fn find_module_cycles(&self, params: Parameters<FindModuleCyclesRequest>) -> Result<CallToolResult, McpError> {
+        let cycles = self.analyzer.find_module_cycles(&PathBuf::from(params.0.path))
+            .map_err(to_mcp_error)?;
+
+        Ok(CallToolResult::success(vec![Content::text(serde_json::to_string_pretty(&cycles).unwrap())]))
+    }
+
+    #[tool(description = "One-call overview of what a crate exposes: every 'pub' item (structs with pub fields, enums, traits, type aliases, fns, impl methods) across all files below src/, grouped by module. Items are listed as written, so pub items inside private modules appear too. Huge crates are truncated with a note.")]
+    async fn public_api(&self, params: Parameters<PublicApiRequest>) -> Result<CallToolResult, McpError> {
+        let ignore = IgnoreList::new(self.config.ignore_dirs.as_deref());
+        let analyzer = Arc::clone(&self.analyzer);
+        let summary = run_blocking(move || analyzer.public_api(&PathBuf::from(params.0.path), &ignore)).await?;
+
+        Ok(CallToolResult::success(vec![Content::text(summary)]))
+    }
+
+    #[tool(description = "Checks the signature of an rmcp #[tool] method without compiling: #[tool] attribute with description inside a #[tool_router] impl, async, '&self', a Parameters<T> argument and a Result<CallToolResult, McpError> return type. Reports each mismatch.")]
+    async fn validate_tool_signature(&self, params: Parameters<ValidateToolRequest>) -> Result<CallToolResult, McpError> {
+        let ValidateToolRequest { path, name } = params.0;
+
+        let validation = self.analyzer.validate_tool(PathBuf::from(path), &name)
+            .await
+            .map_err(to_mcp_error)?;
+
+        Ok(CallToolResult::success(vec![Content::text(serde_json::to_string_pretty(&validation).unwrap())]))
+    }
+
+    #[tool(description = "Verifies that a tool is fully wired into the server after scaffold_new_tool: a #[tool] method inside the #[tool_router] impl, the field on the server struct and its initialization in new(). Reports exactly which step is missing instead of a generic compile error.")]
+    async fn verify_tool_registered(&self, params: Parameters<VerifyRegistrationRequest>) -> Result<CallToolResult, McpError> {
+        let VerifyRegistrationRequest { path, tool_name, field } = params.0;
+
+        let registration = self.analyzer.verify_registration(PathBuf::from(path), &tool_name, field.as_deref())
+            .await
+            .map_err(to_mcp_error)?;
+
+        Ok(CallToolResult::success(vec![Content::text(serde_json::to_string_pretty(&registration).unwrap())]))
+    }
+
+    #[tool(description = "Previews the JSON Schema a #[derive(JsonSchema)] request struct will expose, without running the server: field types, descriptions from #[schemars(description)] or doc comments, and which fields are required. Approximate (reconstructed from the source, not generated by schemars); see 'notes' for parts it could not resolve.")]
+    async fn preview_tool_schema(&self, params: Parameters<PreviewSchemaRequest>) -> Result<CallToolResult, McpError> {
+        let PreviewSchemaRequest { path, name } = params.0;
+
+        let preview = self.analyzer.preview_schema(PathBuf::from(path), &name)
+            .await
+            .map_err(to_mcp_error)?;
+
+        Ok(CallToolResult::success(vec![Content::text(serde_json::to_string_pretty(&preview).unwrap())]))
+    }
+
+    #[tool(description = "Finds usages (calls, paths, method calls, field accesses) of an identifier across all .rs files of a project. Approximate: matches by name only, without type resolution, so shadowed or unrelated items with the same name are included.")]
+    async fn find_references(&self, params: Parameters<FindReferencesRequest>) -> Result<CallToolResult, McpError> {
+        let FindReferencesRequest { path, name } = params.0;
+
+        let references = self.symbol_index.find_references(&PathBuf::from(path), name.trim())
+            .map_err(to_mcp_error)?;
+
+        let response = serde_json::json!({
+            "name": name,
+            "count": references.len(),
+            "references": references
+        });
+
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::to_string_pretty(&response).unwrap()
+        )]))
+    }
+
 
 }
 
+/// Runs synchronous, CPU-heavy work (walking and parsing a source tree) on tokio's blocking
+/// pool, so a large crate doesn't stall the async workers serving other requests.
+async fn run_blocking<T: Send + 'static>(work: impl FnOnce() -> anyhow::Result<T> + Send + 'static) -> Result<T, McpError> {
+    tokio::task::spawn_blocking(work)
+        .await
+        .map_err(|e| to_mcp_error(e.into()))?
+        .map_err(to_mcp_error)
+}
+
+/// Adds line numbers for the AI (`0001 | ...`)
+fn number_lines(content: &str) -> String {
+    content.lines()
+        .enumerate()
+        .map(|(i, line)| format!("{:04} | {}", i + 1, line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 #[tool_handler]
 #[prompt_handler]
 impl ServerHandler for RustBuilderServer {
@@ -425,9 +1646,18 @@ impl ServerHandler for RustBuilderServer {
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    // Logs go to stderr only: stdout is reserved for the MCP protocol.
+    // Level is configurable via RUST_LOG (e.g. RUST_LOG=debug), default "info".
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")))
+        .with_span_events(FmtSpan::CLOSE) // Prints timing when heavy operations finish
+        .with_writer(std::io::stderr)
+        .with_ansi(false)
+        .init();
+
     let server = RustBuilderServer::new();
 
-    eprintln!("MCP Rust Builder Server started!");
+    tracing::info!("MCP Rust Builder Server started");
 
     server.serve((stdin(), stdout())).await?.waiting().await?;
 
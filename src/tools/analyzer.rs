@@ -1,17 +1,194 @@
 // src/tools/analyzer.rs
-use std::path::PathBuf;
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::path::{Path, PathBuf};
 use anyhow::{Context, Result};
 use tokio::fs;
-use syn::{Item, Type, ReturnType, FnArg, Visibility};
+use syn::{Item, Type, ReturnType, FnArg, UseTree, Visibility};
+use syn::spanned::Spanned;
+use syn::visit::{self, Visit};
 use quote::ToTokens;
 use rmcp::schemars::JsonSchema;
-use serde::{Deserialize};
+use serde::{Deserialize, Serialize};
 use rmcp::schemars;
+use crate::utils::walk::rust_files;
+use crate::utils::{IgnoreList, ToolError};
 
 #[derive(Deserialize, JsonSchema)]
 pub struct AnalyzeRequest {
     #[schemars(description = "Absolute path to the Rust file")]
     pub path: String,
+    #[schemars(description = "Optional: First line of the range to outline (1-based). Only items overlapping the range are shown.")]
+    pub start_line: Option<usize>,
+    #[schemars(description = "Optional: Last line of the range to outline (inclusive)")]
+    pub end_line: Option<usize>,
+    #[schemars(description = "Optional: Output format, 'text' (default, pseudo-Rust outline) or 'json' (symbol tree)")]
+    pub format: Option<String>,
+    #[schemars(description = "Optional: 'all' (default, every item), 'crate' (any pub incl. pub(crate)/pub(super)) or 'public' (only plain pub, i.e. the public API)")]
+    pub visibility_filter: Option<String>,
+}
+
+/// Minimum visibility an item needs to appear in the outline (ordered from least to most visible).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum VisibilityFilter {
+    All,
+    Crate,
+    Public,
+}
+
+impl VisibilityFilter {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "all" => Some(Self::All),
+            "crate" => Some(Self::Crate),
+            "public" => Some(Self::Public),
+            _ => None,
+        }
+    }
+
+    fn admits(self, vis: &Visibility) -> bool {
+        let level = match vis {
+            Visibility::Public(_) => Self::Public,
+            // `pub(self)` is just an explicit private
+            Visibility::Restricted(r) if r.path.is_ident("self") => Self::All,
+            Visibility::Restricted(_) => Self::Crate,
+            Visibility::Inherited => Self::All,
+        };
+        level >= self
+    }
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ValidateToolRequest {
+    #[schemars(description = "Absolute path to the Rust file containing the #[tool_router] impl")]
+    pub path: String,
+    #[schemars(description = "Name of the tool method (e.g., 'run_tests')")]
+    pub name: String,
+}
+
+/// Outcome of `validate_tool`: `issues` break the rmcp wiring, `notes` are informational.
+#[derive(Debug, Serialize)]
+pub struct ToolValidation {
+    pub function: String,
+    pub line: usize,
+    pub valid: bool,
+    pub issues: Vec<String>,
+    pub notes: Vec<String>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct VerifyRegistrationRequest {
+    #[schemars(description = "Absolute path to the project root (src/main.rs is read) or directly to the file with the #[tool_router] impl")]
+    pub path: String,
+    #[schemars(description = "Name of the tool method (e.g., 'weather_fetcher')")]
+    pub tool_name: String,
+    #[schemars(description = "Optional: Name of the server struct field holding the tool logic (default: tool_name, as generated by scaffold_new_tool)")]
+    pub field: Option<String>,
+}
+
+/// One wiring step checked by `verify_registration`
+#[derive(Debug, Serialize)]
+pub struct WiringStep {
+    pub step: String,
+    pub ok: bool,
+    pub detail: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ToolRegistration {
+    pub tool_name: String,
+    pub field: String,
+    /// All wiring steps are in place
+    pub registered: bool,
+    pub steps: Vec<WiringStep>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct SearchCrateSourceRequest {
+    #[schemars(description = "Name of a crate unpacked in ~/.cargo/registry/src (e.g. 'serde'); the highest unpacked version is searched")]
+    pub crate_name: String,
+    #[schemars(description = "Type, trait, fn or macro name, optionally qualified: 'Deserializer', 'de::Deserializer' or 'Deserializer::deserialize_any'")]
+    pub symbol: String,
+}
+
+/// Where an item is defined inside a crate's source
+#[derive(Debug, Serialize)]
+pub struct SourceDefinition {
+    /// "struct", "enum", "union", "trait", "type", "fn", "method", "macro"
+    pub kind: String,
+    /// Module path derived from the file layout, e.g. `serde::de::Deserializer`
+    pub path: String,
+    /// Relative to the crate directory, e.g. `src/de/mod.rs`
+    pub file: String,
+    pub line: usize,
+    pub signature: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SourceDefinitions {
+    pub crate_dir: String,
+    pub definitions: Vec<SourceDefinition>,
+    pub files_scanned: usize,
+    /// Files syn could not parse (e.g. nightly-only syntax); they were skipped
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub unparsed_files: Vec<String>,
+    pub truncated: bool,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PublicApiRequest {
+    #[schemars(description = "Absolute path to the project root (all .rs files below src/ are read)")]
+    pub path: String,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct FindModuleCyclesRequest {
+    #[schemars(description = "Absolute path to the project root (containing src/lib.rs and/or src/main.rs)")]
+    pub path: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModuleCycle {
+    /// Crate root the modules belong to, e.g. `src/lib.rs`
+    pub crate_root: String,
+    /// Module paths along the cycle; the first module is repeated at the end
+    pub path: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModuleCycles {
+    pub modules: usize,
+    pub cycles: Vec<ModuleCycle>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PreviewSchemaRequest {
+    #[schemars(description = "Absolute path to the Rust file defining the request struct")]
+    pub path: String,
+    #[schemars(description = "Name of the struct (e.g., 'RunTestsRequest')")]
+    pub name: String,
+}
+
+/// Approximate JSON Schema of a request struct, derived from its source
+#[derive(Debug, Serialize)]
+pub struct SchemaPreview {
+    pub name: String,
+    pub line: usize,
+    pub schema: serde_json::Value,
+    /// Always true: the schema is reconstructed from the fields, not produced by `schemars`
+    pub approximate: bool,
+    /// Parts the preview could not represent exactly (foreign types, `flatten`, ...)
+    pub notes: Vec<String>,
+}
+
+/// A single symbol in the structured outline.
+#[derive(Debug, Serialize, Clone)]
+pub struct SymbolNode {
+    pub kind: String, // "struct", "enum", "trait", "type", "fn", "impl", "mod", "field", "variant", "method"
+    pub name: String,
+    pub visibility: String,
+    pub signature: String,
+    pub line: usize,
+    pub children: Vec<SymbolNode>,
 }
 
 pub struct SymbolAnalyzer;
@@ -21,9 +198,29 @@ impl SymbolAnalyzer {
         Self
     }
 
-    pub async fn analyze(&self, path: PathBuf) -> Result<String> {
+    /// Builds the text outline. If `range` is given (1-based, inclusive), only items
+    /// whose span overlaps those lines are emitted; `visibility` drops less visible items.
+    /// Note: Line info requires proc-macro2's `span-locations` feature.
+    pub async fn analyze(&self, path: PathBuf, range: Option<(usize, usize)>, visibility: VisibilityFilter) -> Result<String> {
+        let nodes = self.analyze_json(path.clone(), range, visibility).await?;
+
+        let mut outline = String::new();
+        outline.push_str(&format!("// OUTLINE: {}\n", path.display()));
+        if let Some((start, end)) = range {
+            outline.push_str(&format!("// RANGE: lines {}-{}\n", start, end));
+        }
+
+        for node in &nodes {
+            render_node(node, &mut outline);
+        }
+
+        Ok(outline)
+    }
+
+    /// Same analysis as `analyze`, but returns a symbol tree for programmatic clients.
+    pub async fn analyze_json(&self, path: PathBuf, range: Option<(usize, usize)>, visibility: VisibilityFilter) -> Result<Vec<SymbolNode>> {
         if !path.exists() {
-            anyhow::bail!("File '{}' does not exist", path.display());
+            return Err(ToolError::NotFound(format!("File '{}' does not exist", path.display())).into());
         }
 
         let content = fs::read_to_string(&path)
@@ -34,63 +231,1132 @@ impl SymbolAnalyzer {
         let syntax = syn::parse_file(&content)
             .context("Failed to parse Rust code. Is the syntax valid?")?;
 
-        let mut outline = String::new();
-        outline.push_str(&format!("// OUTLINE: {}\n", path.display()));
+        Ok(collect_nodes(syntax.items, range, visibility))
+    }
+}
+
+impl SymbolAnalyzer {
+    /// Checks the structural expectations of an rmcp `#[tool]` method without compiling:
+    /// `#[tool]` inside a `#[tool_router]` impl, `async`, `&self`, a `Parameters<T>` argument
+    /// (plus optionally `RequestContext<RoleServer>`) and `Result<CallToolResult, McpError>`.
+    pub async fn validate_tool(&self, path: PathBuf, name: &str) -> Result<ToolValidation> {
+        if !path.exists() {
+            return Err(ToolError::NotFound(format!("File '{}' does not exist", path.display())).into());
+        }
+
+        let content = fs::read_to_string(&path)
+            .await
+            .context("Failed to read file")?;
+        let syntax = syn::parse_file(&content)
+            .context("Failed to parse Rust code. Is the syntax valid?")?;
+
+        let (method, in_tool_router) = find_method(&syntax.items, name).ok_or_else(|| ToolError::NotFound(format!(
+            "No method '{}' found in an impl block of '{}'",
+            name,
+            path.display()
+        )))?;
+
+        let mut issues = Vec::new();
+        let mut notes = Vec::new();
+        let has_attr = |attrs: &[syn::Attribute], wanted: &str| attrs.iter()
+            .find(|a| a.path().segments.last().is_some_and(|s| s.ident == wanted))
+            .cloned();
+
+        // 1. Attributes
+        match has_attr(&method.attrs, "tool") {
+            None => issues.push("Missing #[tool(description = \"...\")] attribute: the method is not registered as a tool.".to_string()),
+            Some(attr) => {
+                if !attr.to_token_stream().to_string().contains("description") {
+                    issues.push("#[tool] has no 'description': the LLM won't know when to call this tool.".to_string());
+                }
+            }
+        }
+        if !in_tool_router {
+            issues.push("The surrounding impl block has no #[tool_router] attribute, so the tool is never routed.".to_string());
+        }
+
+        // 2. async
+        if method.sig.asyncness.is_none() {
+            issues.push("The method is not 'async'. rmcp tool handlers must be async fn.".to_string());
+        }
+
+        // 3. Receiver and arguments
+        let mut inputs = method.sig.inputs.iter();
+        match inputs.next() {
+            Some(FnArg::Receiver(r)) if r.reference.is_some() && r.mutability.is_none() => {}
+            Some(FnArg::Receiver(r)) => issues.push(format!(
+                "The receiver is '{}', expected '&self' (tools are called through a shared reference).",
+                r.to_token_stream().to_string().replace(' ', "")
+            )),
+            _ => issues.push("Missing '&self' receiver: tools must be methods of the server struct.".to_string()),
+        }
+
+        let mut parameters = 0;
+        for input in inputs {
+            let FnArg::Typed(arg) = input else { continue };
+            let type_name = match &*arg.ty {
+                Type::Path(p) => p.path.segments.last().map(|s| (s.ident.to_string(), !s.arguments.is_empty())),
+                _ => None,
+            };
+            match type_name {
+                Some((ident, true)) if ident == "Parameters" => parameters += 1,
+                Some((ident, false)) if ident == "Parameters" => {
+                    issues.push("'Parameters' needs the request struct as type argument: Parameters<MyRequest>.".to_string());
+                }
+                Some((ident, _)) if ident == "RequestContext" => notes.push("Takes a RequestContext (cancellation/progress support).".to_string()),
+                _ => issues.push(format!(
+                    "Unexpected argument '{}'. Tool inputs belong in a #[derive(Deserialize, JsonSchema)] struct passed as Parameters<T>.",
+                    arg.to_token_stream()
+                )),
+            }
+        }
+        match parameters {
+            0 => notes.push("No Parameters<T> argument: the tool takes no input (fine for status-style tools).".to_string()),
+            1 => {}
+            n => issues.push(format!("{} Parameters<..> arguments; combine all inputs into one request struct.", n)),
+        }
+
+        // 4. Return type
+        if !returns_tool_result(&method.sig.output) {
+            let actual = match &method.sig.output {
+                ReturnType::Default => "()".to_string(),
+                ReturnType::Type(_, ty) => type_to_string(ty),
+            };
+            issues.push(format!("Returns '{}', expected 'Result<CallToolResult, McpError>'.", actual));
+        }
+
+        Ok(ToolValidation {
+            function: name.to_string(),
+            line: method.span().start().line,
+            valid: issues.is_empty(),
+            issues,
+            notes,
+        })
+    }
+
+    /// Checks the three manual wiring steps after `scaffold_new_tool`: a `#[tool]` method in
+    /// the `#[tool_router]` impl, the field on the server struct and its initialization in `new()`.
+    pub async fn verify_registration(&self, path: PathBuf, tool_name: &str, field: Option<&str>) -> Result<ToolRegistration> {
+        let file = if path.is_dir() { path.join("src").join("main.rs") } else { path };
+        if !file.exists() {
+            return Err(ToolError::NotFound(format!("File '{}' does not exist", file.display())).into());
+        }
+
+        let content = fs::read_to_string(&file)
+            .await
+            .context("Failed to read file")?;
+        let syntax = syn::parse_file(&content)
+            .context("Failed to parse Rust code. Is the syntax valid?")?;
 
-        for item in syntax.items {
+        let field = field.unwrap_or(tool_name);
+        let is_attr = |attr: &syn::Attribute, wanted: &str| attr.path().segments.last().is_some_and(|s| s.ident == wanted);
+        let self_type = |i: &syn::ItemImpl| match &*i.self_ty {
+            Type::Path(p) => p.path.segments.last().map(|s| s.ident.to_string()),
+            _ => None,
+        };
+
+        let impls: Vec<&syn::ItemImpl> = syntax.items.iter()
+            .filter_map(|item| match item {
+                Item::Impl(i) if i.trait_.is_none() => Some(i),
+                _ => None,
+            })
+            .collect();
+        let router = impls.iter().copied().find(|i| i.attrs.iter().any(|a| is_attr(a, "tool_router")));
+        let Some(router) = router else {
+            return Err(ToolError::NotFound(format!(
+                "No #[tool_router] impl found in '{}'. Tools can only be registered inside one.",
+                file.display()
+            )).into());
+        };
+        let server = self_type(router).unwrap_or_default();
+        let method = |i: &syn::ItemImpl, name: &str| i.items.iter().find_map(|item| match item {
+            syn::ImplItem::Fn(f) if f.sig.ident == name => Some(f.clone()),
+            _ => None,
+        });
+
+        // 1. #[tool] method inside the router
+        let tool_step = match method(router, tool_name) {
+            Some(f) if f.attrs.iter().any(|a| is_attr(a, "tool")) => (true, format!("'{}' is a #[tool] method of the #[tool_router] impl (line {}).", tool_name, f.span().start().line)),
+            Some(_) => (false, format!("'{}' is in the #[tool_router] impl but has no #[tool(description = \"...\")] attribute.", tool_name)),
+            None => match impls.iter().copied().find_map(|i| method(i, tool_name)) {
+                Some(_) => (false, format!("'{}' exists, but in an impl block without #[tool_router]. Move it into the #[tool_router] impl of '{}'.", tool_name, server)),
+                None => (false, format!("No method '{}' found. Add the #[tool] async fn to the #[tool_router] impl of '{}'.", tool_name, server)),
+            },
+        };
+
+        // 2. Field on the server struct
+        let server_struct = syntax.items.iter().find_map(|item| match item {
+            Item::Struct(s) if s.ident == server => Some(s),
+            _ => None,
+        });
+        let field_step = match server_struct {
+            None => (false, format!("Struct '{}' not found in '{}'.", server, file.display())),
+            Some(s) => match s.fields.iter().find(|f| f.ident.as_ref().is_some_and(|i| i == field)) {
+                Some(f) => (true, format!("Field '{}: {}' exists.", field, type_to_string(&f.ty))),
+                None => (false, format!("Struct '{}' has no field '{}'. Add e.g. '{}: Arc<MyTool>,'.", server, field, field)),
+            },
+        };
+
+        // 3. Initialization in new()
+        let new_fn = impls.iter()
+            .copied()
+            .filter(|i| self_type(i).as_deref() == Some(server.as_str()))
+            .find_map(|i| method(i, "new"));
+        let init_step = match new_fn {
+            None => (false, format!("No 'fn new' found in an impl of '{}'.", server)),
+            Some(f) => {
+                let mut literals = StructLiterals { target: server.clone(), fields: Vec::new() };
+                literals.visit_block(&f.block);
+                if literals.fields.iter().any(|name| name == field) {
+                    (true, format!("'{}' is initialized in new().", field))
+                } else {
+                    (false, format!("new() does not initialize '{}'. Add e.g. '{}: Arc::new(MyTool::new()),' to the 'Self {{ ... }}' literal.", field, field))
+                }
+            }
+        };
+
+        let steps: Vec<WiringStep> = [("tool_method", tool_step), ("struct_field", field_step), ("new_initialization", init_step)]
+            .into_iter()
+            .map(|(step, (ok, detail))| WiringStep { step: step.to_string(), ok, detail })
+            .collect();
+
+        Ok(ToolRegistration {
+            tool_name: tool_name.to_string(),
+            field: field.to_string(),
+            registered: steps.iter().all(|s| s.ok),
+            steps,
+        })
+    }
+}
+
+impl SymbolAnalyzer {
+    /// Finds where `symbol` is defined in the `src/` tree of an unpacked crate. Module paths
+    /// follow the file layout (`src/de/mod.rs` is `<crate>::de`) plus inline modules, without
+    /// resolving `#[path]` attributes or re-exports.
+    pub fn find_definitions(&self, crate_dir: &Path, crate_name: &str, symbol: &str) -> Result<SourceDefinitions> {
+        const MAX_DEFINITIONS: usize = 50;
+
+        let segments: Vec<&str> = symbol.split("::").map(str::trim).filter(|s| !s.is_empty()).collect();
+        if segments.is_empty() {
+            return Err(ToolError::InvalidInput("'symbol' must name a type, trait or fn, e.g. 'Deserializer'.".to_string()).into());
+        }
+
+        let src = crate_dir.join("src");
+        if !src.is_dir() {
+            return Err(ToolError::NotFound(format!("No src/ directory in '{}'", crate_dir.display())).into());
+        }
+
+        let crate_ident = crate_name.replace('-', "_");
+        let files = rust_files(&src, &IgnoreList::new(None));
+        let mut definitions = Vec::new();
+        let mut unparsed_files = Vec::new();
+
+        for file in &files {
+            let relative = file.strip_prefix(crate_dir).unwrap_or(file).display().to_string();
+            let Some(syntax) = std::fs::read_to_string(file).ok().and_then(|c| syn::parse_file(&c).ok()) else {
+                unparsed_files.push(relative);
+                continue;
+            };
+
+            let mut module = vec![crate_ident.clone()];
+            module.extend(file_module_path(file.strip_prefix(&src).unwrap_or(file)));
+
+            let mut found = Vec::new();
+            collect_definitions(&syntax.items, &module, &mut found);
+            definitions.extend(found.into_iter()
+                .filter(|d| d.path.len() >= segments.len()
+                    && d.path[d.path.len() - segments.len()..].iter().zip(&segments).all(|(a, b)| a == b))
+                .map(|d| SourceDefinition {
+                    kind: d.kind.to_string(),
+                    path: d.path.join("::"),
+                    file: relative.clone(),
+                    line: d.line,
+                    signature: d.signature,
+                }));
+        }
+
+        let truncated = definitions.len() > MAX_DEFINITIONS;
+        definitions.truncate(MAX_DEFINITIONS);
+
+        Ok(SourceDefinitions {
+            crate_dir: crate_dir.display().to_string(),
+            definitions,
+            files_scanned: files.len(),
+            unparsed_files,
+            truncated,
+        })
+    }
+
+    /// Outline of every `pub` item below `src/`, grouped by module (derived from the file layout).
+    /// Items are taken as written: a `pub` item in a private module is listed too. Stops after
+    /// `MAX_PUBLIC_ITEMS` top-level items and says how much was left out.
+    pub fn public_api(&self, project_path: &Path, ignore: &IgnoreList) -> Result<String> {
+        const MAX_PUBLIC_ITEMS: usize = 400;
+
+        let src = project_path.join("src");
+        if !src.is_dir() {
+            return Err(ToolError::NotFound(format!("No src/ directory in '{}'", project_path.display())).into());
+        }
+
+        let mut summary = format!("// PUBLIC API: {}\n", project_path.display());
+        let mut items = 0;
+        let mut skipped_items = 0;
+        let mut unparsed = Vec::new();
+
+        for file in rust_files(&src, ignore) {
+            let relative = file.strip_prefix(project_path).unwrap_or(&file).display().to_string();
+            let Some(syntax) = std::fs::read_to_string(&file).ok().and_then(|c| syn::parse_file(&c).ok()) else {
+                unparsed.push(relative);
+                continue;
+            };
+
+            let nodes = collect_nodes(syntax.items, None, VisibilityFilter::Public);
+            if nodes.is_empty() {
+                continue;
+            }
+            if items >= MAX_PUBLIC_ITEMS {
+                skipped_items += nodes.len();
+                continue;
+            }
+
+            let mut module = vec!["crate".to_string()];
+            module.extend(file_module_path(file.strip_prefix(&src).unwrap_or(&file)));
+            summary.push_str(&format!("\n// ===== mod {} ({}) =====\n", module.join("::"), relative));
+
+            for node in nodes {
+                if items >= MAX_PUBLIC_ITEMS {
+                    skipped_items += 1;
+                    continue;
+                }
+                render_node(&node, &mut summary);
+                items += 1;
+            }
+        }
+
+        if items == 0 {
+            summary.push_str("\n// No pub items found.\n");
+        }
+        if skipped_items > 0 {
+            summary.push_str(&format!(
+                "\n// ... truncated: {} more pub items not shown (limit {}). Use 'analyze_code' with visibility_filter 'public' on single files.\n",
+                skipped_items, MAX_PUBLIC_ITEMS
+            ));
+        }
+        if !unparsed.is_empty() {
+            summary.push_str(&format!("\n// Could not parse: {}\n", unparsed.join(", ")));
+        }
+
+        Ok(summary)
+    }
+
+    /// Reports `use` cycles between the modules of each crate root (src/lib.rs, src/main.rs).
+    /// Approximate: paths are resolved by identifier only (`crate::`, `self::`, `super::` and
+    /// child modules), without name resolution of re-exports or `#[path]` attributes. A module
+    /// importing a re-export of its parent's `mod.rs` therefore shows up as a cycle, too.
+    pub fn find_module_cycles(&self, project_path: &Path) -> Result<ModuleCycles> {
+        let roots: Vec<PathBuf> = ["src/lib.rs", "src/main.rs"].iter()
+            .map(|root| project_path.join(root))
+            .filter(|root| root.exists())
+            .collect();
+        if roots.is_empty() {
+            return Err(ToolError::NotFound(format!(
+                "Neither src/lib.rs nor src/main.rs found in '{}'.",
+                project_path.display()
+            )).into());
+        }
+
+        let mut modules = 0;
+        let mut cycles = Vec::new();
+        for root in roots {
+            let mut graph = ModuleGraph::default();
+            graph.load(&root, vec!["crate".to_string()], true)?;
+            modules += graph.modules.len();
+
+            let crate_root = root.strip_prefix(project_path).unwrap_or(&root).display().to_string();
+            for path in graph.cycles() {
+                cycles.push(ModuleCycle { crate_root: crate_root.clone(), path });
+            }
+        }
+
+        Ok(ModuleCycles { modules, cycles })
+    }
+}
+
+impl SymbolAnalyzer {
+    /// Builds the JSON Schema `#[derive(JsonSchema)]` would roughly produce for struct `name`:
+    /// field types, `#[schemars(description)]` or doc comments, `Option`/`#[serde(default)]`
+    /// fields as optional, and `#[serde(rename, skip)]`. Structs and unit enums from the same
+    /// file become `$defs`; other types are left open and listed in `notes`.
+    pub async fn preview_schema(&self, path: PathBuf, name: &str) -> Result<SchemaPreview> {
+        if !path.exists() {
+            return Err(ToolError::NotFound(format!("File '{}' does not exist", path.display())).into());
+        }
+
+        let content = fs::read_to_string(&path)
+            .await
+            .context("Failed to read file")?;
+        let syntax = syn::parse_file(&content)
+            .context("Failed to parse Rust code. Is the syntax valid?")?;
+
+        let mut builder = SchemaBuilder::default();
+        builder.collect_types(&syntax.items);
+        let Some(Item::Struct(item)) = builder.types.get(name).copied() else {
+            return Err(ToolError::NotFound(format!("No struct '{}' found in '{}'", name, path.display())).into());
+        };
+
+        let mut schema = builder.struct_schema(item);
+        schema["$schema"] = serde_json::json!("https://json-schema.org/draft/2020-12/schema");
+        schema["title"] = serde_json::json!(name);
+        if !builder.defs.is_empty() {
+            schema["$defs"] = serde_json::json!(builder.defs);
+        }
+
+        Ok(SchemaPreview {
+            name: name.to_string(),
+            line: item.span().start().line,
+            schema,
+            approximate: true,
+            notes: builder.notes,
+        })
+    }
+}
+
+/// State of one `preview_schema` run: the file's named types and the `$defs` built so far.
+#[derive(Default)]
+struct SchemaBuilder<'a> {
+    types: HashMap<String, &'a Item>,
+    defs: BTreeMap<String, serde_json::Value>,
+    notes: Vec<String>,
+}
+
+impl<'a> SchemaBuilder<'a> {
+    fn collect_types(&mut self, items: &'a [Item]) {
+        for item in items {
             match item {
-                Item::Struct(s) => {
-                    outline.push_str(&format!("\n{}struct {} {{\n", vis_to_string(&s.vis), s.ident));
-                    for field in s.fields {
-                        if let Some(ident) = field.ident {
-                            let type_name = type_to_string(&field.ty);
-                            outline.push_str(&format!("    {}: {},\n", ident, type_name));
-                        }
+                Item::Struct(s) => { self.types.insert(s.ident.to_string(), item); }
+                Item::Enum(e) => { self.types.insert(e.ident.to_string(), item); }
+                Item::Mod(m) => {
+                    if let Some((_, items)) = &m.content {
+                        self.collect_types(items);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn struct_schema(&mut self, item: &'a syn::ItemStruct) -> serde_json::Value {
+        let mut properties = serde_json::Map::new();
+        let mut required = Vec::new();
+
+        if serde_value(&item.attrs, "rename_all").is_some() {
+            self.notes.push(format!("'{}' uses #[serde(rename_all)]; property names are shown as written.", item.ident));
+        }
+
+        for field in &item.fields {
+            let Some(ident) = &field.ident else {
+                self.notes.push(format!("'{}' is a tuple struct; only named fields are shown.", item.ident));
+                break;
+            };
+            if serde_flag(&field.attrs, "skip") || serde_flag(&field.attrs, "skip_deserializing") {
+                continue;
+            }
+            if serde_flag(&field.attrs, "flatten") {
+                self.notes.push(format!("Field '{}.{}' is #[serde(flatten)]; its properties are not merged.", item.ident, ident));
+            }
+
+            let name = serde_value(&field.attrs, "rename").unwrap_or_else(|| ident.to_string());
+            let (ty, optional) = match option_inner(&field.ty) {
+                Some(inner) => (inner, true),
+                None => (&field.ty, false),
+            };
+            let mut schema = self.type_schema(ty);
+            if let Some(description) = description(&field.attrs) {
+                schema["description"] = serde_json::json!(description);
+            }
+
+            if !optional && !serde_flag(&field.attrs, "default") {
+                required.push(name.clone());
+            }
+            properties.insert(name, schema);
+        }
+
+        let mut schema = serde_json::json!({
+            "type": "object",
+            "properties": properties,
+            "required": required,
+        });
+        if let Some(description) = description(&item.attrs) {
+            schema["description"] = serde_json::json!(description);
+        }
+        schema
+    }
+
+    fn type_schema(&mut self, ty: &'a Type) -> serde_json::Value {
+        let path = match ty {
+            Type::Reference(r) => return self.type_schema(&r.elem),
+            Type::Slice(s) => return serde_json::json!({ "type": "array", "items": self.type_schema(&s.elem) }),
+            Type::Array(a) => return serde_json::json!({ "type": "array", "items": self.type_schema(&a.elem) }),
+            Type::Path(p) => &p.path,
+            other => {
+                self.notes.push(format!("Type '{}' is not supported; left open.", type_to_string(other)));
+                return serde_json::json!({});
+            }
+        };
+
+        let Some(segment) = path.segments.last() else { return serde_json::json!({}) };
+        let ident = segment.ident.to_string();
+        let args: Vec<&Type> = match &segment.arguments {
+            syn::PathArguments::AngleBracketed(a) => a.args.iter()
+                .filter_map(|arg| match arg {
+                    syn::GenericArgument::Type(t) => Some(t),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        match (ident.as_str(), args.as_slice()) {
+            ("String" | "str" | "char" | "PathBuf" | "Path", _) => serde_json::json!({ "type": "string" }),
+            ("bool", _) => serde_json::json!({ "type": "boolean" }),
+            ("u8" | "u16" | "u32" | "u64" | "u128" | "usize", _) => serde_json::json!({ "type": "integer", "format": format!("uint{}", ident[1..].trim_start_matches("size")), "minimum": 0 }),
+            ("i8" | "i16" | "i32" | "i64" | "i128" | "isize", _) => serde_json::json!({ "type": "integer", "format": format!("int{}", ident[1..].trim_start_matches("size")) }),
+            ("f32" | "f64", _) => serde_json::json!({ "type": "number", "format": if ident == "f32" { "float" } else { "double" } }),
+            ("Value", _) => serde_json::json!({}),
+            ("Option", [inner]) => serde_json::json!({ "anyOf": [self.type_schema(inner), { "type": "null" }] }),
+            ("Box" | "Arc" | "Rc", [inner]) => self.type_schema(inner),
+            ("Vec" | "VecDeque", [inner]) => serde_json::json!({ "type": "array", "items": self.type_schema(inner) }),
+            ("HashSet" | "BTreeSet", [inner]) => serde_json::json!({ "type": "array", "items": self.type_schema(inner), "uniqueItems": true }),
+            ("HashMap" | "BTreeMap", [_, value]) => serde_json::json!({ "type": "object", "additionalProperties": self.type_schema(value) }),
+            _ => self.named_schema(&ident),
+        }
+    }
+
+    /// `$ref` to a struct or enum of the same file, building its definition on first use
+    fn named_schema(&mut self, name: &str) -> serde_json::Value {
+        let Some(item) = self.types.get(name).copied() else {
+            self.notes.push(format!("Type '{}' is not defined in this file; left open.", name));
+            return serde_json::json!({});
+        };
+
+        if !self.defs.contains_key(name) {
+            // Placeholder first, so recursive types terminate
+            self.defs.insert(name.to_string(), serde_json::json!({}));
+            let schema = match item {
+                Item::Struct(s) => self.struct_schema(s),
+                Item::Enum(e) if e.variants.iter().all(|v| v.fields.is_empty()) => {
+                    let variants: Vec<String> = e.variants.iter()
+                        .map(|v| serde_value(&v.attrs, "rename").unwrap_or_else(|| v.ident.to_string()))
+                        .collect();
+                    serde_json::json!({ "type": "string", "enum": variants })
+                }
+                _ => {
+                    self.notes.push(format!("Enum '{}' has data-carrying variants; its schema is left open.", name));
+                    serde_json::json!({})
+                }
+            };
+            self.defs.insert(name.to_string(), schema);
+        }
+
+        serde_json::json!({ "$ref": format!("#/$defs/{}", name) })
+    }
+}
+
+/// Inner type of `Option<T>`
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(p) = ty else { return None };
+    let segment = p.path.segments.last().filter(|s| s.ident == "Option")?;
+    let syn::PathArguments::AngleBracketed(args) = &segment.arguments else { return None };
+    match args.args.first()? {
+        syn::GenericArgument::Type(inner) => Some(inner),
+        _ => None,
+    }
+}
+
+/// `#[schemars(description = "...")]`, falling back to the doc comment like `schemars` does
+fn description(attrs: &[syn::Attribute]) -> Option<String> {
+    attr_value(attrs, "schemars", "description").or_else(|| {
+        let lines: Vec<String> = attrs.iter()
+            .filter(|a| a.path().is_ident("doc"))
+            .filter_map(|a| match &a.meta {
+                syn::Meta::NameValue(nv) => match &nv.value {
+                    syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(s), .. }) => Some(s.value().trim().to_string()),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect();
+        (!lines.is_empty()).then(|| lines.join(" ").trim().to_string())
+    })
+}
+
+fn serde_value(attrs: &[syn::Attribute], key: &str) -> Option<String> {
+    attr_value(attrs, "serde", key)
+}
+
+/// `#[serde(key)]` or `#[serde(key = ...)]`
+fn serde_flag(attrs: &[syn::Attribute], key: &str) -> bool {
+    let mut found = false;
+    for attr in attrs.iter().filter(|a| a.path().is_ident("serde")) {
+        let _ = attr.parse_nested_meta(|meta| {
+            found |= meta.path.is_ident(key);
+            skip_meta_value(&meta)
+        });
+    }
+    found
+}
+
+/// String value of `key` in `#[attr_name(key = "...")]`
+fn attr_value(attrs: &[syn::Attribute], attr_name: &str, key: &str) -> Option<String> {
+    let mut value = None;
+    for attr in attrs.iter().filter(|a| a.path().is_ident(attr_name)) {
+        let _ = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident(key) && meta.input.peek(syn::Token![=]) {
+                let lit: syn::LitStr = meta.value()?.parse()?;
+                value = Some(lit.value());
+                Ok(())
+            } else {
+                skip_meta_value(&meta)
+            }
+        });
+    }
+    value
+}
+
+/// Consumes `= expr` or `(...)` after a nested meta key we are not interested in
+fn skip_meta_value(meta: &syn::meta::ParseNestedMeta) -> syn::Result<()> {
+    if meta.input.peek(syn::Token![=]) {
+        meta.value()?.parse::<syn::Expr>()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        meta.parse_nested_meta(|nested| skip_meta_value(&nested))?;
+    }
+    Ok(())
+}
+
+/// Module tree of one crate plus the `use` paths (as written) found in each module.
+#[derive(Default)]
+struct ModuleGraph {
+    modules: BTreeMap<String, Vec<Vec<String>>>,
+}
+
+impl ModuleGraph {
+    /// Parses `file` as `module` and follows its `mod foo;` declarations.
+    /// `owns_dir` is true for crate roots and mod.rs files, whose children live next to them.
+    fn load(&mut self, file: &Path, module: Vec<String>, owns_dir: bool) -> Result<()> {
+        let content = std::fs::read_to_string(file)
+            .with_context(|| format!("Failed to read '{}'", file.display()))?;
+        let syntax = syn::parse_file(&content)
+            .with_context(|| format!("Failed to parse '{}'", file.display()))?;
+
+        let dir = if owns_dir {
+            file.parent().unwrap_or(Path::new(".")).to_path_buf()
+        } else {
+            file.with_extension("")
+        };
+        self.load_items(&syntax.items, module, &dir)
+    }
+
+    fn load_items(&mut self, items: &[Item], module: Vec<String>, dir: &Path) -> Result<()> {
+        let mut uses = Vec::new();
+
+        for item in items {
+            match item {
+                Item::Use(u) => flatten_use(&u.tree, Vec::new(), &mut uses),
+                Item::Mod(m) => {
+                    let name = m.ident.to_string();
+                    let mut child = module.clone();
+                    child.push(name.clone());
+
+                    if let Some((_, items)) = &m.content {
+                        self.load_items(items, child, &dir.join(&name))?;
+                        continue;
                     }
-                    outline.push_str("}\n");
+
+                    let flat = dir.join(format!("{}.rs", name));
+                    let nested = dir.join(&name).join("mod.rs");
+                    if flat.exists() {
+                        self.load(&flat, child, false)?;
+                    } else if nested.exists() {
+                        self.load(&nested, child, true)?;
+                    }
+                    // A missing module file is reported by the compiler, not here
                 }
-                Item::Enum(e) => {
-                    outline.push_str(&format!("\n{}enum {} {{\n", vis_to_string(&e.vis), e.ident));
-                    for variant in e.variants {
-                        outline.push_str(&format!("    {},\n", variant.ident));
+                _ => {}
+            }
+        }
+
+        self.modules.entry(module.join("::")).or_default().extend(uses);
+        Ok(())
+    }
+
+    /// Resolves a `use` path to the innermost known module it points into.
+    /// Returns `None` for paths into other crates.
+    fn resolve(&self, module: &str, path: &[String]) -> Option<String> {
+        let mut current: Vec<&str> = module.split("::").collect();
+        let mut rest = path;
+
+        match path.first().map(String::as_str) {
+            Some("crate") => {
+                current.truncate(1);
+                rest = &path[1..];
+            }
+            Some("self") => rest = &path[1..],
+            Some("super") => {
+                while rest.first().map(String::as_str) == Some("super") {
+                    if current.len() > 1 {
+                        current.pop();
                     }
-                    outline.push_str("}\n");
-                }
-                Item::Fn(f) => {
-                    let sig = sig_to_string(&f.sig);
-                    outline.push_str(&format!("\n{}{};\n", vis_to_string(&f.vis), sig));
-                }
-                Item::Impl(i) => {
-                    let trait_part = if let Some((_, path, _)) = i.trait_ {
-                        format!("{} for ", path.to_token_stream())
-                    } else {
-                        String::new()
-                    };
-                    let self_ty = type_to_string(&i.self_ty);
-
-                    outline.push_str(&format!("\nimpl {}{} {{\n", trait_part, self_ty));
-
-                    for item in i.items {
-                        if let syn::ImplItem::Fn(method) = item {
-                            let sig = sig_to_string(&method.sig);
-                            outline.push_str(&format!("    {};\n", sig));
+                    rest = &rest[1..];
+                }
+            }
+            // Since the 2018 edition a bare path starts at a child module or names an extern crate
+            Some(first) if self.modules.contains_key(&format!("{}::{}", module, first)) => {}
+            _ => return None,
+        }
+
+        let mut resolved = current.join("::");
+        for segment in rest {
+            let candidate = format!("{}::{}", resolved, segment);
+            if !self.modules.contains_key(&candidate) {
+                break;
+            }
+            resolved = candidate;
+        }
+        Some(resolved)
+    }
+
+    /// One concrete (shortest) cycle per group of mutually dependent modules.
+    fn cycles(&self) -> Vec<Vec<String>> {
+        let edges: BTreeMap<&str, BTreeSet<String>> = self.modules.iter()
+            .map(|(module, uses)| {
+                let targets = uses.iter()
+                    .filter_map(|path| self.resolve(module, path))
+                    .filter(|target| target != module)
+                    .collect();
+                (module.as_str(), targets)
+            })
+            .collect();
+
+        // Module counts are small, so plain reachability per module is good enough
+        let reachable: BTreeMap<&str, BTreeSet<String>> = edges.keys()
+            .map(|&start| {
+                let mut seen = BTreeSet::new();
+                let mut stack = vec![start.to_string()];
+                while let Some(node) = stack.pop() {
+                    for next in edges.get(node.as_str()).into_iter().flatten() {
+                        if seen.insert(next.clone()) {
+                            stack.push(next.clone());
                         }
                     }
-                    outline.push_str("}\n");
                 }
-                Item::Mod(m) => {
-                    outline.push_str(&format!("\n{}mod {};\n", vis_to_string(&m.vis), m.ident));
+                (start, seen)
+            })
+            .collect();
+
+        let mut covered = BTreeSet::new();
+        let mut cycles = Vec::new();
+        for (&module, reached) in &reachable {
+            if covered.contains(module) || !reached.contains(module) {
+                continue;
+            }
+            let component: BTreeSet<&str> = reached.iter()
+                .map(String::as_str)
+                .filter(|other| reachable.get(other).is_some_and(|r| r.contains(module)))
+                .collect();
+            covered.extend(component.iter().copied());
+            cycles.push(shortest_cycle(module, &edges, &component));
+        }
+        cycles
+    }
+}
+
+/// Breadth-first search from `start` back to itself, staying inside `component`.
+fn shortest_cycle<'a>(start: &'a str, edges: &'a BTreeMap<&'a str, BTreeSet<String>>, component: &BTreeSet<&str>) -> Vec<String> {
+    let mut parent: HashMap<&str, &str> = HashMap::new();
+    let mut queue = VecDeque::from([start]);
+
+    while let Some(node) = queue.pop_front() {
+        for next in edges.get(node).into_iter().flatten() {
+            let next = next.as_str();
+            if next == start {
+                let mut path = vec![start.to_string()];
+                let mut current = node;
+                while current != start {
+                    path.push(current.to_string());
+                    current = parent[current];
+                }
+                path.push(start.to_string());
+                path.reverse();
+                return path;
+            }
+            if component.contains(next) && !parent.contains_key(next) {
+                parent.insert(next, node);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    vec![start.to_string()]
+}
+
+/// Expands `use a::{b, c::*}` into `[a, b]` and `[a, c]`.
+fn flatten_use(tree: &UseTree, mut prefix: Vec<String>, out: &mut Vec<Vec<String>>) {
+    match tree {
+        UseTree::Path(p) => {
+            prefix.push(p.ident.to_string());
+            flatten_use(&p.tree, prefix, out);
+        }
+        UseTree::Name(n) => {
+            prefix.push(n.ident.to_string());
+            out.push(prefix);
+        }
+        UseTree::Rename(r) => {
+            prefix.push(r.ident.to_string());
+            out.push(prefix);
+        }
+        UseTree::Glob(_) => out.push(prefix),
+        UseTree::Group(g) => {
+            for tree in &g.items {
+                flatten_use(tree, prefix.clone(), out);
+            }
+        }
+    }
+}
+
+/// A definition found by `collect_definitions`, before it is matched against the query
+struct FoundDefinition {
+    kind: &'static str,
+    path: Vec<String>,
+    line: usize,
+    signature: String,
+}
+
+/// Module path of a file below `src/`: `de/mod.rs` and `de.rs` are `de`, `lib.rs`/`main.rs` the root.
+fn file_module_path(relative: &Path) -> Vec<String> {
+    let mut segments: Vec<String> = relative.with_extension("")
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect();
+    match segments.last().map(String::as_str) {
+        Some("mod") => { segments.pop(); }
+        Some("lib") | Some("main") if segments.len() == 1 => { segments.pop(); }
+        _ => {}
+    }
+    segments
+}
+
+/// Type, trait, fn and macro definitions (with impl and trait methods), descending into inline modules.
+fn collect_definitions(items: &[Item], module: &[String], out: &mut Vec<FoundDefinition>) {
+    let path = |names: &[String]| module.iter().chain(names).cloned().collect::<Vec<_>>();
+    let mut push = |kind: &'static str, names: &[String], line: usize, signature: String| {
+        out.push(FoundDefinition { kind, path: path(names), line, signature });
+    };
+
+    let mut inline_modules = Vec::new();
+    for item in items {
+        let line = item.span().start().line;
+        match item {
+            Item::Struct(s) => push("struct", &[s.ident.to_string()], line, format!("struct {}{}", s.ident, s.generics.to_token_stream())),
+            Item::Enum(e) => push("enum", &[e.ident.to_string()], line, format!("enum {}{}", e.ident, e.generics.to_token_stream())),
+            Item::Union(u) => push("union", &[u.ident.to_string()], line, format!("union {}{}", u.ident, u.generics.to_token_stream())),
+            Item::Type(t) => push("type", &[t.ident.to_string()], line, format!("type {} = {}", t.ident, type_to_string(&t.ty))),
+            Item::Fn(f) => push("fn", &[f.sig.ident.to_string()], line, sig_to_string(&f.sig)),
+            Item::Macro(m) => {
+                if let Some(ident) = &m.ident {
+                    push("macro", &[ident.to_string()], line, format!("macro_rules! {}", ident));
                 }
-                Item::Use(u) => {
-                    // Todo: -> Optional
-                    // Optional: include imports? usually too noisy.
-                    // outline.push_str(&format!("use ...;\n"));
+            }
+            Item::Trait(t) => {
+                let name = t.ident.to_string();
+                push("trait", &[name.clone()], line, format!("trait {}{}", t.ident, t.generics.to_token_stream()));
+                for trait_item in &t.items {
+                    if let syn::TraitItem::Fn(f) = trait_item {
+                        push("method", &[name.clone(), f.sig.ident.to_string()], trait_item.span().start().line, sig_to_string(&f.sig));
+                    }
+                }
+            }
+            Item::Impl(i) => {
+                let Type::Path(self_ty) = &*i.self_ty else { continue };
+                let Some(owner) = self_ty.path.segments.last().map(|s| s.ident.to_string()) else { continue };
+                for impl_item in &i.items {
+                    if let syn::ImplItem::Fn(f) = impl_item {
+                        push("method", &[owner.clone(), f.sig.ident.to_string()], impl_item.span().start().line, sig_to_string(&f.sig));
+                    }
+                }
+            }
+            Item::Mod(m) => {
+                if let Some((_, items)) = &m.content {
+                    inline_modules.push((m.ident.to_string(), items));
                 }
-                _ => {} // Ignore macros, consts, externs for brevity
             }
+            _ => {}
         }
+    }
 
-        Ok(outline)
+    for (name, items) in inline_modules {
+        collect_definitions(items, &path(&[name]), out);
+    }
+}
+
+/// Collects the field names of `Self { .. }` / `Target { .. }` literals, e.g. in `new()`.
+struct StructLiterals {
+    target: String,
+    fields: Vec<String>,
+}
+
+impl<'ast> Visit<'ast> for StructLiterals {
+    fn visit_expr_struct(&mut self, node: &'ast syn::ExprStruct) {
+        let name = node.path.segments.last().map(|s| s.ident.to_string());
+        if matches!(name.as_deref(), Some(n) if n == "Self" || n == self.target) {
+            for field in &node.fields {
+                if let syn::Member::Named(ident) = &field.member {
+                    self.fields.push(ident.to_string());
+                }
+            }
+        }
+        visit::visit_expr_struct(self, node);
+    }
+}
+
+/// Finds a method by name in any impl block (also inside inline modules).
+/// Returns it together with whether that impl carries `#[tool_router]`.
+fn find_method<'a>(items: &'a [Item], name: &str) -> Option<(&'a syn::ImplItemFn, bool)> {
+    for item in items {
+        match item {
+            Item::Impl(i) => {
+                let method = i.items.iter().find_map(|impl_item| match impl_item {
+                    syn::ImplItem::Fn(f) if f.sig.ident == name => Some(f),
+                    _ => None,
+                });
+                if let Some(method) = method {
+                    let in_tool_router = i.attrs.iter().any(|a| a.path().segments.last().is_some_and(|s| s.ident == "tool_router"));
+                    return Some((method, in_tool_router));
+                }
+            }
+            Item::Mod(m) => {
+                if let Some(found) = m.content.as_ref().and_then(|(_, items)| find_method(items, name)) {
+                    return Some(found);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// `Result<CallToolResult, McpError>` (or `ErrorData`), matched by the last path segments
+fn returns_tool_result(output: &ReturnType) -> bool {
+    let ReturnType::Type(_, ty) = output else { return false };
+    let Type::Path(p) = &**ty else { return false };
+    let Some(result) = p.path.segments.last().filter(|s| s.ident == "Result") else { return false };
+    let syn::PathArguments::AngleBracketed(args) = &result.arguments else { return false };
+
+    let last_ident = |arg: Option<&syn::GenericArgument>| match arg {
+        Some(syn::GenericArgument::Type(Type::Path(p))) => p.path.segments.last().map(|s| s.ident.to_string()),
+        _ => None,
+    };
+    let mut args = args.args.iter();
+    let ok = last_ident(args.next());
+    let err = last_ident(args.next());
+    ok.as_deref() == Some("CallToolResult") && matches!(err.as_deref(), Some("McpError") | Some("ErrorData"))
+}
+
+fn collect_nodes(items: Vec<Item>, range: Option<(usize, usize)>, visibility: VisibilityFilter) -> Vec<SymbolNode> {
+    let in_range = |first: usize, last: usize| match range {
+        Some((start, end)) => first <= end && last >= start,
+        None => true,
+    };
+
+    let mut nodes = Vec::new();
+
+    for item in items {
+        let span = item.span();
+        if !in_range(span.start().line, span.end().line) {
+            continue;
+        }
+        let line = span.start().line;
+
+        match item {
+            Item::Struct(s) if visibility.admits(&s.vis) => {
+                let children = s.fields.iter()
+                    .filter(|field| visibility.admits(&field.vis))
+                    .filter_map(|field| {
+                        field.ident.as_ref().map(|ident| SymbolNode {
+                            kind: "field".to_string(),
+                            name: ident.to_string(),
+                            visibility: vis_to_string(&field.vis).trim().to_string(),
+                            signature: type_to_string(&field.ty),
+                            line: field.span().start().line,
+                            children: Vec::new(),
+                        })
+                    })
+                    .collect();
+
+                nodes.push(SymbolNode {
+                    kind: "struct".to_string(),
+                    name: s.ident.to_string(),
+                    visibility: vis_to_string(&s.vis).trim().to_string(),
+                    signature: format!("struct {}", s.ident),
+                    line,
+                    children,
+                });
+            }
+            Item::Enum(e) if visibility.admits(&e.vis) => {
+                let children = e.variants.iter()
+                    .map(|variant| SymbolNode {
+                        kind: "variant".to_string(),
+                        name: variant.ident.to_string(),
+                        visibility: String::new(),
+                        signature: variant.ident.to_string(),
+                        line: variant.span().start().line,
+                        children: Vec::new(),
+                    })
+                    .collect();
+
+                nodes.push(SymbolNode {
+                    kind: "enum".to_string(),
+                    name: e.ident.to_string(),
+                    visibility: vis_to_string(&e.vis).trim().to_string(),
+                    signature: format!("enum {}", e.ident),
+                    line,
+                    children,
+                });
+            }
+            Item::Fn(f) if visibility.admits(&f.vis) => {
+                nodes.push(SymbolNode {
+                    kind: "fn".to_string(),
+                    name: f.sig.ident.to_string(),
+                    visibility: vis_to_string(&f.vis).trim().to_string(),
+                    signature: sig_to_string(&f.sig),
+                    line,
+                    children: Vec::new(),
+                });
+            }
+            Item::Trait(t) if visibility.admits(&t.vis) => {
+                // Trait methods are as visible as the trait itself
+                let children = t.items.iter()
+                    .filter_map(|item| match item {
+                        syn::TraitItem::Fn(method) => Some(SymbolNode {
+                            kind: "method".to_string(),
+                            name: method.sig.ident.to_string(),
+                            visibility: String::new(),
+                            signature: sig_to_string(&method.sig),
+                            line: item.span().start().line,
+                            children: Vec::new(),
+                        }),
+                        _ => None,
+                    })
+                    .collect();
+
+                nodes.push(SymbolNode {
+                    kind: "trait".to_string(),
+                    name: t.ident.to_string(),
+                    visibility: vis_to_string(&t.vis).trim().to_string(),
+                    signature: format!("trait {}", t.ident),
+                    line,
+                    children,
+                });
+            }
+            Item::Type(t) if visibility.admits(&t.vis) => {
+                nodes.push(SymbolNode {
+                    kind: "type".to_string(),
+                    name: t.ident.to_string(),
+                    visibility: vis_to_string(&t.vis).trim().to_string(),
+                    signature: format!("type {} = {}", t.ident, type_to_string(&t.ty)),
+                    line,
+                    children: Vec::new(),
+                });
+            }
+            Item::Impl(i) => {
+                let trait_part = if let Some((_, path, _)) = &i.trait_ {
+                    format!("{} for ", path.to_token_stream())
+                } else {
+                    String::new()
+                };
+                let self_ty = type_to_string(&i.self_ty);
+
+                let mut children = Vec::new();
+                for item in &i.items {
+                    let span = item.span();
+                    if !in_range(span.start().line, span.end().line) {
+                        continue;
+                    }
+                    if let syn::ImplItem::Fn(method) = item {
+                        // Trait methods carry no visibility of their own; they are as visible as the trait
+                        if i.trait_.is_none() && !visibility.admits(&method.vis) {
+                            continue;
+                        }
+                        children.push(SymbolNode {
+                            kind: "method".to_string(),
+                            name: method.sig.ident.to_string(),
+                            visibility: vis_to_string(&method.vis).trim().to_string(),
+                            signature: sig_to_string(&method.sig),
+                            line: span.start().line,
+                            children: Vec::new(),
+                        });
+                    }
+                }
+
+                if visibility != VisibilityFilter::All && i.trait_.is_none() && children.is_empty() {
+                    continue;
+                }
+
+                nodes.push(SymbolNode {
+                    kind: "impl".to_string(),
+                    name: self_ty.clone(),
+                    visibility: String::new(),
+                    signature: format!("impl {}{}", trait_part, self_ty),
+                    line,
+                    children,
+                });
+            }
+            Item::Mod(m) if visibility.admits(&m.vis) => {
+                nodes.push(SymbolNode {
+                    kind: "mod".to_string(),
+                    name: m.ident.to_string(),
+                    visibility: vis_to_string(&m.vis).trim().to_string(),
+                    signature: format!("mod {}", m.ident),
+                    line,
+                    children: Vec::new(),
+                });
+            }
+            Item::Use(_) => {
+                // Optional: include imports? usually too noisy.
+            }
+            _ => {} // Ignore macros, consts, externs for brevity
+        }
+    }
+
+    nodes
+}
+
+/// Renders a node back into the pseudo-Rust text outline.
+fn render_node(node: &SymbolNode, outline: &mut String) {
+    let vis = if node.visibility.is_empty() { String::new() } else { format!("{} ", node.visibility) };
+
+    match node.kind.as_str() {
+        "struct" | "enum" => {
+            outline.push_str(&format!("\n{}{} {{\n", vis, node.signature));
+            for child in &node.children {
+                if child.kind == "field" {
+                    outline.push_str(&format!("    {}: {},\n", child.name, child.signature));
+                } else {
+                    outline.push_str(&format!("    {},\n", child.name));
+                }
+            }
+            outline.push_str("}\n");
+        }
+        // impl nodes carry no visibility
+        "impl" | "trait" => {
+            outline.push_str(&format!("\n{}{} {{\n", vis, node.signature));
+            for child in &node.children {
+                outline.push_str(&format!("    {};\n", child.signature));
+            }
+            outline.push_str("}\n");
+        }
+        _ => {
+            outline.push_str(&format!("\n{}{};\n", vis, node.signature));
+        }
     }
 }
 
@@ -99,7 +1365,11 @@ impl SymbolAnalyzer {
 fn vis_to_string(vis: &Visibility) -> String {
     match vis {
         Visibility::Public(_) => "pub ".to_string(),
-        Visibility::Restricted(_) => "pub(crate) ".to_string(), // Simplified
+        Visibility::Restricted(r) => format!(
+            "pub({}{}) ",
+            if r.in_token.is_some() { "in " } else { "" },
+            r.path.to_token_stream().to_string().replace(' ', "")
+        ),
         Visibility::Inherited => "".to_string(),
     }
 }
@@ -132,4 +1402,228 @@ fn sig_to_string(sig: &syn::Signature) -> String {
     };
 
     format!("{}fn {}({}){}", async_prefix, name, inputs, output)
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_support::TempDir;
+
+    #[test]
+    fn test_visibility_filter_admits() {
+        let vis = |src: &str| syn::parse_str::<Visibility>(src).unwrap();
+        let levels = ["", "pub(self)", "pub(super)", "pub(crate)", "pub(in crate::net)", "pub"];
+
+        let admitted = |filter: VisibilityFilter| levels.iter().filter(|v| filter.admits(&vis(v))).count();
+        assert_eq!(admitted(VisibilityFilter::All), 6);
+        // Everything restricted to more than the module counts as crate-visible
+        assert_eq!(admitted(VisibilityFilter::Crate), 4);
+        assert_eq!(admitted(VisibilityFilter::Public), 1);
+        assert!(!VisibilityFilter::Crate.admits(&vis("pub(self)")));
+        assert!(VisibilityFilter::Public.admits(&vis("pub")));
+    }
+
+    #[test]
+    fn test_vis_to_string() {
+        let render = |src: &str| vis_to_string(&syn::parse_str::<Visibility>(src).unwrap());
+        assert_eq!(render("pub"), "pub ");
+        assert_eq!(render(""), "");
+        assert_eq!(render("pub(crate)"), "pub(crate) ");
+        assert_eq!(render("pub(super)"), "pub(super) ");
+        assert_eq!(render("pub(in crate::net::tcp)"), "pub(in crate::net::tcp) ");
+    }
+
+    #[test]
+    fn test_find_module_cycles() {
+        let root = TempDir::new("module_cycles");
+        std::fs::create_dir_all(root.join("src/b")).unwrap();
+        std::fs::write(root.join("src/lib.rs"), "mod a;\nmod b;\nmod c;\n").unwrap();
+        std::fs::write(root.join("src/a.rs"), "use crate::b::inner::Thing;\n").unwrap();
+        std::fs::write(root.join("src/b/mod.rs"), "pub mod inner;\n").unwrap();
+        std::fs::write(root.join("src/b/inner.rs"), "use super::super::a::*;\nuse serde::Serialize;\n").unwrap();
+        std::fs::write(root.join("src/c.rs"), "use crate::a;\n").unwrap();
+
+        let result = SymbolAnalyzer::new().find_module_cycles(root.path()).unwrap();
+
+        assert_eq!(result.modules, 5);
+        assert_eq!(result.cycles.len(), 1);
+        assert_eq!(result.cycles[0].crate_root, "src/lib.rs");
+        assert_eq!(result.cycles[0].path, vec!["crate::a", "crate::b::inner", "crate::a"]);
+    }
+
+    #[test]
+    fn test_public_api() {
+        let root = TempDir::new("public_api");
+        std::fs::create_dir_all(root.join("src/net")).unwrap();
+        std::fs::write(root.join("src/lib.rs"), "pub mod net;\npub struct Config { pub port: u16, secret: String }\nfn helper() {}\npub trait Handler { fn handle(&self); }\n").unwrap();
+        std::fs::write(root.join("src/net/mod.rs"), "pub fn connect() {}\npub(crate) fn internal() {}\n").unwrap();
+
+        let api = SymbolAnalyzer::new().public_api(root.path(), &IgnoreList::new(None)).unwrap();
+
+        assert!(api.contains("// ===== mod crate (src/lib.rs) ====="));
+        assert!(api.contains("// ===== mod crate::net (src/net/mod.rs) ====="));
+        assert!(api.contains("pub struct Config {\n    port: u16,\n}"));
+        assert!(api.contains("pub trait Handler {\n    fn handle("));
+        assert!(api.contains("pub fn connect()"));
+        assert!(!api.contains("helper") && !api.contains("internal") && !api.contains("secret"));
+    }
+
+    #[test]
+    fn test_find_definitions() {
+        let root = TempDir::new("find_definitions");
+        std::fs::create_dir_all(root.join("src/de")).unwrap();
+        std::fs::write(root.join("src/lib.rs"), "pub mod de;\npub mod ser { pub fn deserialize_any() {} }\n").unwrap();
+        std::fs::write(root.join("src/de/mod.rs"), "pub trait Deserializer {\n    fn deserialize_any(&self);\n}\n").unwrap();
+
+        let analyzer = SymbolAnalyzer::new();
+        let traits = analyzer.find_definitions(root.path(), "demo-crate", "Deserializer").unwrap();
+        let methods = analyzer.find_definitions(root.path(), "demo-crate", "deserialize_any").unwrap();
+        let qualified = analyzer.find_definitions(root.path(), "demo-crate", "Deserializer::deserialize_any").unwrap();
+
+        assert_eq!(traits.files_scanned, 2);
+        assert_eq!(traits.definitions.len(), 1);
+        assert_eq!(traits.definitions[0].path, "demo_crate::de::Deserializer");
+        assert_eq!(traits.definitions[0].file, "src/de/mod.rs");
+        assert_eq!(traits.definitions[0].line, 1);
+
+        let paths: Vec<&str> = methods.definitions.iter().map(|d| d.path.as_str()).collect();
+        assert_eq!(paths, vec!["demo_crate::de::Deserializer::deserialize_any", "demo_crate::ser::deserialize_any"]);
+        assert_eq!(qualified.definitions.len(), 1);
+        assert_eq!(qualified.definitions[0].line, 2);
+    }
+
+    #[tokio::test]
+    async fn test_validate_tool() {
+        let dir = TempDir::new("validate_tool");
+        let file = dir.join("main.rs");
+        std::fs::write(&file, r#"
+            #[tool_router]
+            impl Server {
+                #[tool(description = "Good")]
+                async fn good(&self, Parameters(req): Parameters<Req>, context: RequestContext<RoleServer>) -> Result<CallToolResult, McpError> { todo!() }
+
+                #[tool(description = "Status")]
+                async fn status(&self) -> Result<CallToolResult, McpError> { todo!() }
+
+                #[tool(description = "Sync")]
+                fn sync_tool(&self, params: Parameters<Req>) -> Result<CallToolResult, McpError> { todo!() }
+
+                #[tool(description = "No receiver")]
+                async fn no_self(params: Parameters<Req>) -> Result<CallToolResult, McpError> { todo!() }
+
+                #[tool(description = "Bare")]
+                async fn bare(&self, params: Parameters) -> Result<CallToolResult, McpError> { todo!() }
+
+                #[tool(description = "Loose")]
+                async fn loose(&self, city: String) -> Result<CallToolResult, McpError> { todo!() }
+
+                #[tool(description = "Text")]
+                async fn text(&self, params: Parameters<Req>) -> String { todo!() }
+            }
+
+            impl Server {
+                #[tool(description = "Outside")]
+                async fn outside(&self, params: Parameters<Req>) -> Result<CallToolResult, McpError> { todo!() }
+            }
+        "#).unwrap();
+
+        let analyzer = SymbolAnalyzer::new();
+        let check = |name: &'static str| {
+            let file = file.clone();
+            let analyzer = &analyzer;
+            async move { analyzer.validate_tool(file, name).await.unwrap() }
+        };
+        let only_issue = |v: &ToolValidation, expected: &str| {
+            assert!(!v.valid, "{} should be invalid", v.function);
+            assert_eq!(v.issues.len(), 1, "{}: {:?}", v.function, v.issues);
+            assert!(v.issues[0].contains(expected), "{}: {:?}", v.function, v.issues);
+        };
+
+        let good = check("good").await;
+        assert!(good.valid && good.issues.is_empty());
+        assert_eq!(good.notes, vec!["Takes a RequestContext (cancellation/progress support)."]);
+
+        // Without Parameters<T> the tool simply takes no input
+        let status = check("status").await;
+        assert!(status.valid);
+        assert!(status.notes[0].starts_with("No Parameters<T> argument"));
+
+        only_issue(&check("sync_tool").await, "not 'async'");
+        only_issue(&check("no_self").await, "Missing '&self'");
+        only_issue(&check("bare").await, "Parameters<MyRequest>");
+        only_issue(&check("loose").await, "Unexpected argument 'city : String'");
+        only_issue(&check("text").await, "Returns 'String'");
+        only_issue(&check("outside").await, "no #[tool_router]");
+
+        assert!(analyzer.validate_tool(file.clone(), "missing").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_verify_registration() {
+        let dir = TempDir::new("verify_registration");
+        let file = dir.join("main.rs");
+        std::fs::write(&file, r#"
+            struct Server {
+                weather: Arc<Weather>,
+                other: Arc<Other>,
+            }
+
+            #[tool_router]
+            impl Server {
+                fn new() -> Self {
+                    Self { weather: Arc::new(Weather::new()), other: Arc::new(Other::new()) }
+                }
+
+                #[tool(description = "Weather")]
+                async fn weather(&self) -> Result<CallToolResult, McpError> { todo!() }
+
+                async fn forecast(&self) -> Result<CallToolResult, McpError> { todo!() }
+            }
+        "#).unwrap();
+
+        let analyzer = SymbolAnalyzer::new();
+        let complete = analyzer.verify_registration(file.clone(), "weather", None).await.unwrap();
+        let partial = analyzer.verify_registration(file.clone(), "forecast", None).await.unwrap();
+
+        assert!(complete.registered);
+        assert!(!partial.registered);
+        let ok: Vec<bool> = partial.steps.iter().map(|s| s.ok).collect();
+        assert_eq!(ok, vec![false, false, false]);
+        assert!(partial.steps[0].detail.contains("no #[tool"));
+    }
+
+    #[tokio::test]
+    async fn test_preview_schema() {
+        let dir = TempDir::new("preview_schema");
+        let file = dir.join("requests.rs");
+        std::fs::write(&file, r#"
+            #[derive(Deserialize, JsonSchema)]
+            pub struct RunRequest {
+                #[schemars(description = "Project root")]
+                pub path: String,
+                /// Extra arguments
+                pub args: Option<Vec<String>>,
+                #[serde(rename = "mode", default)]
+                pub kind: Mode,
+                #[serde(skip)]
+                pub internal: u32,
+                pub limit: Option<usize>,
+            }
+
+            #[derive(Deserialize, JsonSchema)]
+            pub enum Mode { Fast, Full }
+        "#).unwrap();
+
+        let preview = SymbolAnalyzer::new().preview_schema(file.clone(), "RunRequest").await.unwrap();
+
+        let schema = &preview.schema;
+        assert_eq!(schema["required"], serde_json::json!(["path"]));
+        assert_eq!(schema["properties"]["path"], serde_json::json!({ "type": "string", "description": "Project root" }));
+        assert_eq!(schema["properties"]["args"]["items"]["type"], "string");
+        assert_eq!(schema["properties"]["args"]["description"], "Extra arguments");
+        assert_eq!(schema["properties"]["mode"]["$ref"], "#/$defs/Mode");
+        assert_eq!(schema["properties"]["limit"]["minimum"], 0);
+        assert!(schema["properties"].get("internal").is_none());
+        assert_eq!(schema["$defs"]["Mode"]["enum"], serde_json::json!(["Fast", "Full"]));
+        assert!(preview.notes.is_empty());
+    }
+}
@@ -0,0 +1,88 @@
+// src/tools/bench.rs
+use std::path::PathBuf;
+use std::time::Duration;
+use anyhow::{Context, Result};
+use rmcp::schemars::JsonSchema;
+use serde::Deserialize;
+use rmcp::schemars;
+use tokio_util::sync::CancellationToken;
+use tracing::Instrument;
+use crate::utils::process::{capped_output, cargo_command, output_with_timeout, ProcessOutcome};
+use crate::utils::ToolError;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct RunBenchRequest {
+    #[schemars(description = "Absolute path to the project root")]
+    pub path: String,
+    #[schemars(description = "Optional filter: Name of the benchmark (e.g., 'parse_large_file')")]
+    pub filter: Option<String>,
+    #[schemars(description = "Optional: Timeout in seconds (default 1800)")]
+    pub timeout_secs: Option<u64>,
+}
+
+pub struct BenchRunner;
+
+impl BenchRunner {
+    /// Benches are slow by nature, so the default limit is generous.
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Runs cargo bench in the specified directory.
+    /// The process is killed when `timeout` elapses or `cancel` fires.
+    pub async fn run(
+        &self,
+        project_path: PathBuf,
+        filter: Option<String>,
+        timeout: Duration,
+        cancel: &CancellationToken,
+    ) -> Result<String> {
+        // 1. Validation
+        if !project_path.join("Cargo.toml").exists() {
+            return Err(ToolError::NotFound(format!("No Cargo.toml found at '{}'. cannot run benchmarks.", project_path.display())).into());
+        }
+
+        // 2. Build Command
+        let mut cmd = cargo_command();
+        cmd.current_dir(&project_path)
+            .arg("bench")
+            .arg("--color").arg("never");
+
+        if let Some(bench_name) = filter {
+            if !bench_name.trim().is_empty() {
+                cmd.arg(&bench_name);
+            }
+        }
+
+        // 3. Execute
+        let output = match output_with_timeout(cmd, cancel, timeout)
+            .instrument(tracing::info_span!("cargo_bench", path = %project_path.display()))
+            .await
+            .context("Failed to execute 'cargo bench'")?
+        {
+            ProcessOutcome::Completed(output) => output,
+            ProcessOutcome::Cancelled => return Ok("Benchmarks cancelled by client. No results.".to_string()),
+            ProcessOutcome::TimedOut(limit) => return Err(ToolError::Timeout(format!(
+                "'cargo bench' timed out after {}s and was killed. Use a filter or a larger 'timeout_secs'.",
+                limit.as_secs()
+            )).into()),
+        };
+
+        let stdout = capped_output(&output.stdout);
+        let stderr = capped_output(&output.stderr);
+
+        // 4. Format Output
+        let status_msg = if output.status.success() {
+            "Benchmarks finished."
+        } else {
+            "Benchmarks failed."
+        };
+
+        Ok(format!(
+            "{}\n\n=== STDOUT ===\n{}\n=== STDERR ===\n{}",
+            status_msg, stdout, stderr
+        ))
+    }
+}
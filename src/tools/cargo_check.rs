@@ -1,14 +1,46 @@
 use serde::{Deserialize, Serialize};
-use std::process::Command;
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
 use anyhow::Result;
+use tokio_util::sync::CancellationToken;
+use tracing::Instrument;
+use crate::utils::process::{capped_output, cargo_command, stdout_lines_with_retry, validate_env, ProcessOutcome};
 use rmcp::schemars;
 use rmcp::schemars::JsonSchema;
+use crate::utils::{FeatureFlags, ToolError};
 
 #[derive(Deserialize, JsonSchema)]
 pub struct CheckCodeRequest {
     #[schemars(description = "Absolute path to the Rust project")]
     pub path: String,
+    #[schemars(description = "Optional: Also check tests, examples and benches (--all-targets). Slower, default false.")]
+    pub all_targets: Option<bool>,
+    #[schemars(description = "Optional: Activate all features (--all-features). Default false.")]
+    pub all_features: Option<bool>,
+    #[schemars(description = "Optional: Disable the default features (--no-default-features). Default false.")]
+    pub no_default_features: Option<bool>,
+    #[schemars(description = "Optional: Features to activate, e.g. [\"serde\", \"tokio/full\"] (--features)")]
+    pub features: Option<Vec<String>>,
+    #[schemars(description = "Optional: Extra environment variables for cargo and build scripts, e.g. {\"RUSTFLAGS\": \"--cfg tokio_unstable\"}. Note: changing RUSTFLAGS invalidates the build cache and triggers a full rebuild. Variables that select programs (RUSTC_WRAPPER, CARGO_*, PATH, ...) are refused.")]
+    pub env: Option<HashMap<String, String>>,
+    #[schemars(description = "Optional: Add a triage 'summary' (counts per error code, most frequent messages, files with the most errors) to the full list. Useful with dozens of cascading errors. Default false.")]
+    pub summarize: Option<bool>,
+    #[schemars(description = "Optional: Workspace member (package name or directory, see 'list_workspace_members') to check instead of the root")]
+    pub member: Option<String>,
+    #[schemars(description = "Optional: Timeout in seconds; cargo is killed afterwards (default: server's --command-timeout-secs)")]
+    pub timeout_secs: Option<u64>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct CheckFileRequest {
+    #[schemars(description = "Absolute path to the Rust project")]
+    pub path: String,
+    #[schemars(description = "The file you are editing (absolute, or relative to the project root, e.g. 'src/tools/git.rs')")]
+    pub file: String,
+    #[schemars(description = "Optional: Also return issues from other files (default false)")]
+    pub include_all: Option<bool>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -18,83 +50,476 @@ pub struct CompilerMessage {
     pub file: Option<String>,
     pub line: Option<usize>,
     pub code: Option<String>, // e.g., "E0308"
+    /// Fixes rustc marked as `MachineApplicable` (safe to apply without review)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub suggestions: Vec<Suggestion>,
+}
+
+/// A compiler-suggested replacement of the byte range `byte_start..byte_end` in `file`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Suggestion {
+    /// Edits of a message with the same `group` form one suggestion and are applied together
+    #[serde(default)]
+    pub group: usize,
+    pub file: String,
+    pub line: usize,
+    pub byte_start: usize,
+    pub byte_end: usize,
+    pub replacement: String,
 }
 
-pub struct CargoChecker;
+#[derive(Deserialize, JsonSchema)]
+pub struct ApplyFixesRequest {
+    #[schemars(description = "Absolute path to the Rust project")]
+    pub path: String,
+}
+
+/// Messages compared to the previous `check_code` run of the same project.
+#[derive(Debug, Serialize)]
+pub struct CheckDiff {
+    pub new: Vec<CompilerMessage>,
+    pub fixed: Vec<CompilerMessage>,
+    pub unchanged: usize,
+}
+
+/// Triage view of a check result: where most of the errors are and which ones repeat.
+#[derive(Debug, Serialize)]
+pub struct CheckSummary {
+    pub by_code: Vec<CodeCount>,
+    pub top_messages: Vec<MessageCount>,
+    pub top_files: Vec<FileCount>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CodeCount {
+    /// `None` for messages without a code (most warnings, some errors)
+    pub code: Option<String>,
+    pub level: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MessageCount {
+    pub message: String,
+    pub code: Option<String>,
+    pub count: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileCount {
+    pub file: String,
+    pub errors: usize,
+    pub warnings: usize,
+}
+
+/// (canonicalized project path, all_targets, feature args, sorted env): everything that decides
+/// which diagnostics a check can produce
+type CheckKey = (PathBuf, bool, Vec<String>, Vec<(String, String)>);
+
+pub struct CargoChecker {
+    // Last messages per check mode, for "what changed since last check". --all-targets adds
+    // test-only diagnostics, features and RUSTFLAGS (`--cfg`) gate code, so each combination
+    // is tracked separately.
+    previous: Mutex<HashMap<CheckKey, Vec<CompilerMessage>>>,
+}
 
 impl CargoChecker {
     pub fn new() -> Self {
-        Self
+        Self {
+            previous: Mutex::new(HashMap::new()),
+        }
     }
 
-    pub fn check(&self, project_path: PathBuf) -> Result<CheckResult> {
-        // 1. Run cargo check with JSON output
-        let output = Command::new("cargo")
-            .arg("check")
-            .arg("--message-format=json")
-            .current_dir(&project_path)
-            .output()
-            .map_err(|e| anyhow::anyhow!("Failed to execute cargo: {}", e))?;
+    /// Stores `result` as the latest state of `project_path` and diffs it against the
+    /// previous run with the same `all_targets`, features and `env`. Returns `None` on the first check (or after a cancelled one).
+    /// Messages are matched without their line, so an edit that only shifts lines
+    /// doesn't turn an old error into "fixed" + "new".
+    pub fn diff_with_previous(
+        &self,
+        project_path: &Path,
+        all_targets: bool,
+        features: &FeatureFlags,
+        env: &HashMap<String, String>,
+        result: &CheckResult,
+    ) -> Option<CheckDiff> {
+        if result.cancelled {
+            return None;
+        }
 
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let stderr = String::from_utf8_lossy(&output.stderr);
+        let path = project_path.canonicalize().unwrap_or_else(|_| project_path.to_path_buf());
+        let mut env: Vec<(String, String)> = env.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+        env.sort();
+        let key = (path, all_targets, features.args(), env);
+        let previous = self.previous.lock().unwrap().insert(key, result.messages.clone())?;
 
-        let mut messages = Vec::new();
-        let success = output.status.success();
+        let identity = |m: &CompilerMessage| (m.level.clone(), m.code.clone(), m.file.clone(), m.message.clone());
+
+        // Multiset difference: the same warning can legitimately occur several times
+        let mut remaining: HashMap<_, usize> = HashMap::new();
+        for m in &previous {
+            *remaining.entry(identity(m)).or_default() += 1;
+        }
 
-        // 2. Parse the JSON stream
-        for line in stdout.lines() {
-            // Skip non-JSON lines
-            if !line.starts_with('{') { continue; }
-
-            if let Ok(json) = serde_json::from_str::<serde_json::Value>(line) {
-                if json["reason"] == "compiler-message" {
-                    if let Some(msg) = json.get("message") {
-                        let level = msg["level"].as_str().unwrap_or("unknown").to_string();
-
-                        if level == "error" || level == "warning" {
-                            let (file, line) = if let Some(spans) = msg["spans"].as_array() {
-                                if let Some(first) = spans.first() {
-                                    (
-                                        first["file_name"].as_str().map(|s| s.to_string()),
-                                        first["line_start"].as_u64().map(|n| n as usize),
-                                    )
-                                } else { (None, None) }
-                            } else { (None, None) };
-
-                            messages.push(CompilerMessage {
-                                level,
-                                message: msg["message"].as_str().unwrap_or("").to_string(),
-                                code: msg["code"]["code"].as_str().map(|s| s.to_string()),
-                                file,
-                                line,
-                            });
-                        }
-                    }
+        let mut new = Vec::new();
+        let mut unchanged = 0;
+        for m in &result.messages {
+            match remaining.get_mut(&identity(m)) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    unchanged += 1;
                 }
+                _ => new.push(m.clone()),
             }
         }
 
-        // Fallback for non-JSON errors (like missing Cargo.toml)
+        let fixed = previous.into_iter()
+            .filter(|m| match remaining.get_mut(&identity(m)) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    true
+                }
+                _ => false,
+            })
+            .collect();
+
+        Some(CheckDiff { new, fixed, unchanged })
+    }
+
+    /// Runs `cargo check`. If `cancel` fires, cargo is killed and a result with
+    /// `cancelled: true` (and no messages) is returned. `env` is passed to cargo (and thus rustc
+    /// and build scripts) on top of the server's environment.
+    pub async fn check(&self, project_path: PathBuf, all_targets: bool, features: &FeatureFlags, env: &HashMap<String, String>, cancel: &CancellationToken, timeout: Duration) -> Result<CheckResult> {
+        // 0. Validation: cargo needs a manifest, otherwise its error is cryptic
+        if !project_path.join("Cargo.toml").exists() {
+            return Err(ToolError::NotFound(format!(
+                "No Cargo.toml found at '{}'. This is not the project root.\n\
+                Action: Use `get_project_structure` to locate the directory containing Cargo.toml and pass that path.",
+                project_path.display()
+            )).into());
+        }
+        validate_env(env)?;
+
+        // 1. Run cargo check with JSON output
+        let build = || {
+            let mut cmd = cargo_command();
+            cmd.arg("check")
+                .arg("--message-format=json")
+                .current_dir(&project_path);
+
+            // lib, bins, tests, examples and benches in one pass
+            if all_targets {
+                cmd.arg("--all-targets");
+            }
+            features.apply(&mut cmd);
+            cmd.envs(env);
+            cmd
+        };
+
+        // 2. Parse the JSON stream as it arrives, so huge diagnostic volumes never sit in memory
+        // as raw text. A retried attempt starts over with an empty list.
+        let parsed: Mutex<(u32, Vec<CompilerMessage>)> = Mutex::new((0, Vec::new()));
+        let on_line = |attempt: u32, line: &str| {
+            let mut parsed = parsed.lock().unwrap();
+            if parsed.0 != attempt {
+                *parsed = (attempt, Vec::new());
+            }
+            if let Some(msg) = Self::parse_message(line) {
+                parsed.1.push(msg);
+            }
+        };
+
+        // Fetching dependencies can hit registry blips; those are retried, compile errors are not
+        let span = tracing::info_span!("cargo_check", path = %project_path.display(), all_targets);
+        let output = match stdout_lines_with_retry(build, cancel, timeout, &on_line)
+            .instrument(span)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to execute cargo: {}", e))?
+        {
+            ProcessOutcome::Completed(output) => output,
+            ProcessOutcome::Cancelled => return Ok(CheckResult::cancelled()),
+            ProcessOutcome::TimedOut(limit) => return Err(ToolError::Timeout(format!("'cargo check' timed out after {}s", limit.as_secs())).into()),
+        };
+
+        // Only used for the fallback message, so it's capped like other tool output
+        let stderr = capped_output(&output.stderr);
+
+        let mut messages = parsed.into_inner().unwrap().1;
+        let success = output.status.success();
+
+        // Fallback for non-JSON errors (e.g. invalid manifest, resolution failures)
         if !success && messages.is_empty() {
             messages.push(CompilerMessage {
                 level: "error".to_string(),
                 message: stderr.to_string(),
-                file: Some("Cargo.toml".to_string()),
+                file: None,
                 line: None,
                 code: None,
+                suggestions: Vec::new(),
             });
         }
 
+        let error_count = messages.iter().filter(|m| m.level == "error").count();
+        let warning_count = messages.iter().filter(|m| m.level == "warning").count();
+
         Ok(CheckResult {
             success,
+            cancelled: false,
+            has_errors: error_count > 0,
+            error_count,
+            warning_count,
             messages,
         })
     }
+
+    /// Turns one line of `--message-format=json` output into a `CompilerMessage`.
+    /// Returns `None` for non-JSON lines, other reasons (artifacts, build scripts) and notes.
+    fn parse_message(line: &str) -> Option<CompilerMessage> {
+        if !line.starts_with('{') { return None; }
+
+        let json = serde_json::from_str::<serde_json::Value>(line).ok()?;
+        if json["reason"] != "compiler-message" { return None; }
+        let msg = json.get("message")?;
+
+        let level = msg["level"].as_str().unwrap_or("unknown").to_string();
+        if level != "error" && level != "warning" { return None; }
+
+        let first = msg["spans"].as_array().and_then(|spans| spans.first());
+        Some(CompilerMessage {
+            level,
+            message: msg["message"].as_str().unwrap_or("").to_string(),
+            code: msg["code"]["code"].as_str().map(|s| s.to_string()),
+            file: first.and_then(|s| s["file_name"].as_str()).map(|s| s.to_string()),
+            line: first.and_then(|s| s["line_start"].as_u64()).map(|n| n as usize),
+            suggestions: Self::machine_applicable(msg),
+        })
+    }
+
+    /// Collects `MachineApplicable` replacements from the message's own spans and
+    /// from its children (rustc puts most "help: ..." suggestions there).
+    fn machine_applicable(msg: &serde_json::Value) -> Vec<Suggestion> {
+        let children = msg["children"].as_array().map(|c| c.as_slice()).unwrap_or_default();
+        let mut suggestions = Vec::new();
+
+        // The message's own spans are one suggestion, each child's spans another
+        for (group, spans) in std::iter::once(&msg["spans"]).chain(children.iter().map(|c| &c["spans"])).enumerate() {
+            for span in spans.as_array().map(|s| s.as_slice()).unwrap_or_default() {
+                if span["suggestion_applicability"] != "MachineApplicable" {
+                    continue;
+                }
+                let (Some(file), Some(line), Some(start), Some(end), Some(replacement)) = (
+                    span["file_name"].as_str(),
+                    span["line_start"].as_u64(),
+                    span["byte_start"].as_u64(),
+                    span["byte_end"].as_u64(),
+                    span["suggested_replacement"].as_str(),
+                ) else { continue };
+
+                let suggestion = Suggestion {
+                    group,
+                    file: file.to_string(),
+                    line: line as usize,
+                    byte_start: start as usize,
+                    byte_end: end as usize,
+                    replacement: replacement.to_string(),
+                };
+                if !suggestions.contains(&suggestion) {
+                    suggestions.push(suggestion);
+                }
+            }
+        }
+
+        suggestions
+    }
 }
 
 #[derive(Serialize)]
 pub struct CheckResult {
-    pub success: bool,
+    pub success: bool, // cargo's exit code
+    pub cancelled: bool,
+    pub has_errors: bool, // warnings alone do not count as failure
+    pub error_count: usize,
+    pub warning_count: usize,
     pub messages: Vec<CompilerMessage>,
-}
\ No newline at end of file
+}
+
+impl CheckResult {
+    fn cancelled() -> Self {
+        Self {
+            success: false,
+            cancelled: true,
+            has_errors: false,
+            error_count: 0,
+            warning_count: 0,
+            messages: Vec::new(),
+        }
+    }
+
+    /// Groups the messages for triage: all codes by frequency, plus the `SUMMARY_TOP`
+    /// most frequent distinct messages and the files with the most errors (then warnings).
+    pub fn summary(&self) -> CheckSummary {
+        const SUMMARY_TOP: usize = 5;
+
+        let mut codes: HashMap<(Option<String>, String), usize> = HashMap::new();
+        let mut messages: HashMap<(String, Option<String>), usize> = HashMap::new();
+        let mut files: HashMap<String, (usize, usize)> = HashMap::new();
+
+        for m in &self.messages {
+            *codes.entry((m.code.clone(), m.level.clone())).or_default() += 1;
+            *messages.entry((m.message.clone(), m.code.clone())).or_default() += 1;
+            if let Some(file) = &m.file {
+                let counts = files.entry(file.clone()).or_default();
+                if m.level == "error" { counts.0 += 1 } else { counts.1 += 1 }
+            }
+        }
+
+        // Errors first, then by count; ties sorted by name so the output is stable
+        let mut by_code: Vec<CodeCount> = codes.into_iter()
+            .map(|((code, level), count)| CodeCount { code, level, count })
+            .collect();
+        by_code.sort_by(|a, b| (b.level == "error").cmp(&(a.level == "error"))
+            .then(b.count.cmp(&a.count))
+            .then(a.code.cmp(&b.code)));
+
+        let mut top_messages: Vec<MessageCount> = messages.into_iter()
+            .map(|((message, code), count)| MessageCount { message, code, count })
+            .collect();
+        top_messages.sort_by(|a, b| b.count.cmp(&a.count).then(a.message.cmp(&b.message)));
+        top_messages.truncate(SUMMARY_TOP);
+
+        let mut top_files: Vec<FileCount> = files.into_iter()
+            .map(|(file, (errors, warnings))| FileCount { file, errors, warnings })
+            .collect();
+        top_files.sort_by(|a, b| b.errors.cmp(&a.errors)
+            .then(b.warnings.cmp(&a.warnings))
+            .then(a.file.cmp(&b.file)));
+        top_files.truncate(SUMMARY_TOP);
+
+        CheckSummary { by_code, top_messages, top_files }
+    }
+
+    /// Keeps only messages that belong to `file` (absolute or relative to `project_path`)
+    /// and recomputes the counts. Cargo reports paths relative to the workspace root,
+    /// so matching is done on canonicalized paths with a suffix fallback.
+    pub fn only_file(self, project_path: &Path, file: &str) -> (CheckResult, usize) {
+        let canonical = |p: &Path| p.canonicalize().unwrap_or_else(|_| p.to_path_buf());
+
+        let target = if Path::new(file).is_absolute() {
+            canonical(Path::new(file))
+        } else {
+            canonical(&project_path.join(file))
+        };
+
+        let (matching, others): (Vec<_>, Vec<_>) = self.messages.into_iter().partition(|m| {
+            match &m.file {
+                Some(msg_file) => {
+                    canonical(&project_path.join(msg_file)) == target
+                        || target.ends_with(msg_file)
+                }
+                None => false,
+            }
+        });
+
+        let error_count = matching.iter().filter(|m| m.level == "error").count();
+        let warning_count = matching.iter().filter(|m| m.level == "warning").count();
+
+        let result = CheckResult {
+            success: self.success,
+            cancelled: self.cancelled,
+            has_errors: error_count > 0,
+            error_count,
+            warning_count,
+            messages: matching,
+        };
+
+        (result, others.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(level: &str, text: &str, line: usize) -> CompilerMessage {
+        CompilerMessage {
+            level: level.to_string(),
+            message: text.to_string(),
+            file: Some("src/lib.rs".to_string()),
+            line: Some(line),
+            code: None,
+            suggestions: Vec::new(),
+        }
+    }
+
+    fn result(messages: Vec<CompilerMessage>) -> CheckResult {
+        let error_count = messages.iter().filter(|m| m.level == "error").count();
+        CheckResult {
+            success: error_count == 0,
+            cancelled: false,
+            has_errors: error_count > 0,
+            error_count,
+            warning_count: messages.len() - error_count,
+            messages,
+        }
+    }
+
+    #[test]
+    fn test_diff_with_previous() {
+        let checker = CargoChecker::new();
+        let path = Path::new("/nonexistent/demo");
+        let default = FeatureFlags::default();
+        let no_env = HashMap::new();
+        let diff = |all_targets, features: &FeatureFlags, env: &HashMap<String, String>, result: &CheckResult| {
+            checker.diff_with_previous(path, all_targets, features, env, result)
+        };
+
+        let first = result(vec![message("error", "mismatched types", 3), message("warning", "unused variable", 7), message("warning", "unused variable", 9)]);
+        assert!(diff(false, &default, &no_env, &first).is_none());
+
+        // Shifted lines stay "unchanged"; one of the duplicate warnings is gone
+        let second = result(vec![message("warning", "unused variable", 8), message("error", "cannot find value", 4), message("error", "mismatched types", 5)]);
+        let changes = diff(false, &default, &no_env, &second).unwrap();
+        assert_eq!(changes.unchanged, 2);
+        assert_eq!(changes.new.iter().map(|m| m.message.as_str()).collect::<Vec<_>>(), vec!["cannot find value"]);
+        assert_eq!(changes.fixed.iter().map(|m| m.message.as_str()).collect::<Vec<_>>(), vec!["unused variable"]);
+
+        // --all-targets, a feature set and RUSTFLAGS each keep their own history instead of
+        // diffing against the plain run
+        let gated = result(vec![message("warning", "unused import in tests", 1)]);
+        let serde = FeatureFlags::new(None, None, Some(vec!["serde".into()]));
+        let cfg = HashMap::from([("RUSTFLAGS".to_string(), "--cfg tokio_unstable".to_string())]);
+        assert!(diff(true, &default, &no_env, &gated).is_none());
+        assert!(diff(false, &serde, &no_env, &gated).is_none());
+        assert!(diff(false, &default, &cfg, &gated).is_none());
+        assert!(diff(false, &serde, &cfg, &gated).is_none());
+        let changes = diff(false, &default, &no_env, &second).unwrap();
+        assert!(changes.new.is_empty() && changes.fixed.is_empty());
+        assert_eq!(changes.unchanged, 3);
+
+        // The same mode again diffs against its own previous run
+        let changes = diff(false, &serde, &no_env, &second).unwrap();
+        assert_eq!(changes.new.len(), 3);
+        assert_eq!(changes.fixed.iter().map(|m| m.message.as_str()).collect::<Vec<_>>(), vec!["unused import in tests"]);
+    }
+
+    #[test]
+    fn test_machine_applicable_groups() {
+        let span = |start: u64, replacement: &str, applicability: &str| serde_json::json!({
+            "file_name": "src/lib.rs",
+            "line_start": 1,
+            "byte_start": start,
+            "byte_end": start + 1,
+            "suggested_replacement": replacement,
+            "suggestion_applicability": applicability,
+        });
+        let msg = serde_json::json!({
+            "spans": [span(0, "", "MaybeIncorrect")],
+            "children": [
+                { "spans": [span(10, "use a::B;\n", "MachineApplicable"), span(40, "B", "MachineApplicable")] },
+                { "spans": [span(60, "mut x", "MachineApplicable")] },
+            ],
+        });
+
+        let groups: Vec<(usize, usize)> = CargoChecker::machine_applicable(&msg).iter().map(|s| (s.group, s.byte_start)).collect();
+        assert_eq!(groups, vec![(1, 10), (1, 40), (2, 60)]);
+    }
+}
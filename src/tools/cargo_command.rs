@@ -0,0 +1,213 @@
+// src/tools/cargo_command.rs
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+use anyhow::{Context, Result};
+use rmcp::schemars::JsonSchema;
+use serde::Deserialize;
+use rmcp::schemars;
+use tokio_util::sync::CancellationToken;
+use tracing::Instrument;
+use crate::utils::process::{capped_output, cargo_command, output_with_timeout, validate_env, ProcessOutcome};
+use crate::utils::ToolError;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct CargoCommandRequest {
+    #[schemars(description = "Absolute path to the project root")]
+    pub path: String,
+    #[schemars(description = "Cargo subcommand, e.g. 'tree'. Must be on the server's allowlist.")]
+    pub subcommand: String,
+    #[schemars(description = "Optional: Arguments after the subcommand, e.g. [\"-i\", \"serde\"]. --config, -Z, --manifest-path and -C are refused.")]
+    pub args: Option<Vec<String>>,
+    #[schemars(description = "Optional: Toolchain override without '+', e.g. 'nightly' (runs 'cargo +nightly ...')")]
+    pub toolchain: Option<String>,
+    #[schemars(description = "Optional: Extra environment variables, e.g. {\"RUSTFLAGS\": \"-C target-cpu=native\"} for 'build'. Note: changing RUSTFLAGS invalidates the build cache and triggers a full rebuild. Variables that select programs (RUSTC_WRAPPER, CARGO_*, PATH, ...) are refused.")]
+    pub env: Option<HashMap<String, String>>,
+    #[schemars(description = "Optional: Timeout in seconds; cargo is killed afterwards (default: server's --command-timeout-secs)")]
+    pub timeout_secs: Option<u64>,
+}
+
+/// Subcommands permitted by `cargo_command` unless `--cargo-allowlist` says otherwise
+pub const DEFAULT_ALLOWED_SUBCOMMANDS: [&str; 9] = [
+    "check", "build", "test", "fmt", "clippy", "tree", "metadata", "add", "remove",
+];
+
+/// Subcommands that change files or the manifest (refused in read-only mode)
+pub const MUTATING_SUBCOMMANDS: [&str; 4] = ["add", "remove", "fmt", "fix"];
+
+/// Cargo flags that could run arbitrary programs (`--config build.rustc-wrapper=...`,
+/// `target.*.runner`), enable unstable behavior or point cargo at another project
+const FORBIDDEN_ARGS: [&str; 4] = ["--config", "-Z", "--manifest-path", "-C"];
+
+/// Returns the forbidden flag `arg` spells, covering `--flag=value` and short forms like `-Zflag`
+fn forbidden_arg(arg: &str) -> Option<&'static str> {
+    FORBIDDEN_ARGS.iter().copied().find(|flag| {
+        arg == *flag
+            || arg.strip_prefix(flag).is_some_and(|rest| rest.starts_with('=') || !flag.starts_with("--"))
+    })
+}
+
+/// Whether `cargo <subcommand> <args>` may edit files. Besides `MUTATING_SUBCOMMANDS`
+/// this covers `clippy --fix`, which rewrites sources like `cargo fix`; `fmt --check` only reports.
+pub fn is_mutating(subcommand: &str, args: &[String]) -> bool {
+    let has = |flag: &str| args.iter().any(|a| a == flag);
+    match subcommand {
+        "fmt" => !has("--check"),
+        "clippy" => has("--fix"),
+        other => MUTATING_SUBCOMMANDS.contains(&other),
+    }
+}
+
+/// Escape hatch for cargo subcommands without a dedicated tool. Only allowlisted
+/// subcommands run, and never through a shell.
+pub struct CargoCommandRunner {
+    allowed: Vec<String>,
+}
+
+impl CargoCommandRunner {
+    /// `allowed` of `None` means `DEFAULT_ALLOWED_SUBCOMMANDS`
+    pub fn new(allowed: Option<&[String]>) -> Self {
+        let allowed = match allowed {
+            Some(list) => list.to_vec(),
+            None => DEFAULT_ALLOWED_SUBCOMMANDS.iter().map(|s| s.to_string()).collect(),
+        };
+        Self { allowed }
+    }
+
+    /// Runs `cargo [+toolchain] <subcommand> <args>` in `project_path`, with `env` added to the
+    /// server's environment.
+    pub async fn run(
+        &self,
+        project_path: PathBuf,
+        subcommand: &str,
+        args: &[String],
+        toolchain: Option<&str>,
+        env: &HashMap<String, String>,
+        cancel: &CancellationToken,
+        timeout: Duration,
+    ) -> Result<String> {
+        if !self.allowed.iter().any(|a| a == subcommand) {
+            return Err(ToolError::InvalidInput(format!(
+                "Subcommand '{}' is not allowed. Permitted: {}",
+                subcommand,
+                self.allowed.join(", ")
+            )).into());
+        }
+
+        if let Some(flag) = args.iter().find_map(|a| forbidden_arg(a)) {
+            return Err(ToolError::InvalidInput(format!(
+                "Argument '{}' is not allowed in cargo_command (forbidden: {}).",
+                flag,
+                FORBIDDEN_ARGS.join(", ")
+            )).into());
+        }
+
+        if let Some(toolchain) = toolchain {
+            // Only names like 'nightly', 'stable', '1.80.0' or 'nightly-2024-05-01'
+            if toolchain.is_empty() || !toolchain.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '_')) {
+                return Err(ToolError::InvalidInput(format!("Invalid toolchain '{}'. Use e.g. 'nightly' or '1.80.0'.", toolchain)).into());
+            }
+        }
+
+        validate_env(env)?;
+
+        if !project_path.is_dir() {
+            return Err(ToolError::NotFound(format!("The directory '{}' does not exist.", project_path.display())).into());
+        }
+
+        let mut cmd = cargo_command();
+        cmd.current_dir(&project_path);
+        if let Some(toolchain) = toolchain {
+            cmd.arg(format!("+{}", toolchain));
+        }
+        cmd.arg(subcommand).args(args).envs(env);
+
+        let output = match output_with_timeout(cmd, cancel, timeout)
+            .instrument(tracing::info_span!("cargo_command", path = %project_path.display(), subcommand = %subcommand))
+            .await
+            .with_context(|| format!("Failed to execute 'cargo {}'", subcommand))?
+        {
+            ProcessOutcome::Completed(output) => output,
+            ProcessOutcome::Cancelled => return Ok(format!("'cargo {}' cancelled by client.", subcommand)),
+            ProcessOutcome::TimedOut(limit) => return Err(ToolError::Timeout(format!(
+                "'cargo {}' timed out after {}s and was killed.",
+                subcommand,
+                limit.as_secs()
+            )).into()),
+        };
+
+        let status = match output.status.code() {
+            Some(0) => "succeeded".to_string(),
+            Some(code) => format!("failed (exit code {})", code),
+            None => "was terminated by a signal".to_string(),
+        };
+
+        Ok(format!(
+            "'cargo {}' {}.\n\n=== STDOUT ===\n{}\n=== STDERR ===\n{}",
+            subcommand,
+            status,
+            capped_output(&output.stdout),
+            capped_output(&output.stderr)
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_mutating() {
+        let args = |list: &[&str]| list.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+        assert!(is_mutating("clippy", &args(&["--fix", "--allow-dirty"])));
+        assert!(!is_mutating("clippy", &args(&["--all-targets", "--", "-D", "warnings"])));
+        assert!(is_mutating("fmt", &[]));
+        assert!(!is_mutating("fmt", &args(&["--", "--check"])));
+        assert!(is_mutating("add", &args(&["serde"])));
+        assert!(is_mutating("fix", &[]));
+        assert!(!is_mutating("tree", &args(&["-i", "serde"])));
+    }
+
+    #[test]
+    fn test_forbidden_arg() {
+        assert_eq!(forbidden_arg("--config"), Some("--config"));
+        assert_eq!(forbidden_arg("--config=build.rustc-wrapper='/bin/sh'"), Some("--config"));
+        assert_eq!(forbidden_arg("--manifest-path=/elsewhere/Cargo.toml"), Some("--manifest-path"));
+        assert_eq!(forbidden_arg("-Zbuild-std"), Some("-Z"));
+        assert_eq!(forbidden_arg("-C"), Some("-C"));
+        assert_eq!(forbidden_arg("--configure"), None);
+        assert_eq!(forbidden_arg("--all-targets"), None);
+        assert_eq!(forbidden_arg("-i"), None);
+    }
+
+    #[tokio::test]
+    async fn test_run_rejects_before_spawning() {
+        let runner = CargoCommandRunner::new(None);
+        let args = |list: &[&str]| list.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+        let run = |subcommand: &'static str, args: Vec<String>| {
+            let runner = &runner;
+            async move {
+                runner.run(PathBuf::from("/nonexistent"), subcommand, &args, None, &HashMap::new(), &CancellationToken::new(), Duration::from_secs(5))
+                    .await
+                    .unwrap_err()
+                    .to_string()
+            }
+        };
+
+        let err = run("install", vec![]).await;
+        assert!(err.contains("'install' is not allowed"));
+        assert!(err.contains(&DEFAULT_ALLOWED_SUBCOMMANDS.join(", ")));
+
+        let custom = CargoCommandRunner::new(Some(&args(&["tree"])));
+        let err = custom.run(PathBuf::from("/nonexistent"), "check", &[], None, &HashMap::new(), &CancellationToken::new(), Duration::from_secs(5))
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.ends_with("Permitted: tree"));
+
+        for bad in [&["--config", "build.rustc-wrapper=/bin/sh"][..], &["--config=target.x.runner=sh"], &["-Zunstable-options"], &["--manifest-path", "/elsewhere/Cargo.toml"], &["-C", "/elsewhere"]] {
+            let err = run("build", args(bad)).await;
+            assert!(err.contains("is not allowed in cargo_command"), "{:?}: {}", bad, err);
+        }
+    }
+}
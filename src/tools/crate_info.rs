@@ -1,13 +1,88 @@
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use rmcp::schemars::JsonSchema;
 use rmcp::schemars;
+use crate::utils::text::levenshtein;
+use crate::utils::ToolError;
 
 #[derive(Deserialize, JsonSchema)]
 pub struct GetCrateInfoRequest {
     #[schemars(description = "Name of the crate (e.g., 'serde', 'tokio', 'rmcp')")]
     pub crate_name: String,
+    #[schemars(description = "Optional: Only search registries whose cache directory contains this text (e.g., 'crates.io' or the host of a private registry). Default: all registries.")]
+    pub registry: Option<String>,
+    #[schemars(description = "Optional: Exact version to describe (e.g., the one in Cargo.lock) instead of the newest cached one")]
+    pub version: Option<String>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct SuggestCratesRequest {
+    #[schemars(description = "Partial or misspelled crate name (e.g., 'serd', 'tokoi')")]
+    pub prefix: String,
+    #[schemars(description = "Optional: Maximum number of suggestions (default 10)")]
+    pub limit: Option<usize>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct GetCrateReadmeRequest {
+    #[schemars(description = "Name of the crate (e.g., 'serde', 'tokio', 'rmcp')")]
+    pub crate_name: String,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct GetCrateExampleRequest {
+    #[schemars(description = "Name of the crate (e.g., 'axum', 'rmcp')")]
+    pub crate_name: String,
+    #[schemars(description = "Optional: Example to read (file name like 'hello_world' or relative path like 'hello_world.rs'). Omit to list all examples.")]
+    pub example: Option<String>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct CrateFeatureGraphRequest {
+    #[schemars(description = "Name of the crate (e.g., 'tokio', 'serde')")]
+    pub crate_name: String,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct DiffCrateVersionsRequest {
+    #[schemars(description = "Name of the crate (e.g., 'tokio')")]
+    pub crate_name: String,
+    #[schemars(description = "Older version, exactly as published (e.g., '1.37.0')")]
+    pub from_version: String,
+    #[schemars(description = "Newer version, exactly as published (e.g., '1.38.0')")]
+    pub to_version: String,
+}
+
+/// Unterschiede zweier Versionen laut lokalem Index (Abhängigkeiten und Features)
+#[derive(Debug, Serialize)]
+pub struct VersionDiff {
+    pub crate_name: String,
+    pub from: String,
+    pub to: String,
+    pub added_dependencies: Vec<DependencyInfo>,
+    pub removed_dependencies: Vec<DependencyInfo>,
+    pub changed_dependencies: Vec<DependencyChange>,
+    pub added_features: Vec<String>,
+    pub removed_features: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DependencyChange {
+    pub name: String,
+    pub from: DependencyInfo,
+    pub to: DependencyInfo,
+}
+
+/// Alles, was ein Feature (transitiv) aktiviert
+#[derive(Debug, Serialize, Default)]
+pub struct FeatureClosure {
+    pub features: Vec<String>,                 // Eigene Features inkl. des Features selbst
+    pub dependencies: Vec<String>,             // Aktivierte optionale Abhängigkeiten
+    pub dependency_features: Vec<String>,      // "dep/feature", aktiviert auch die Abhängigkeit
+    pub weak_dependency_features: Vec<String>, // "dep?/feature", nur falls dep anderweitig aktiv ist
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -18,7 +93,18 @@ pub struct CrateInfo {
     pub repository: Option<String>,
     pub documentation: Option<String>,
     pub license: Option<String>,
-    pub dependencies: Vec<String>,
+    pub dependencies: Vec<String>, // Nur Namen der nicht-optionalen Abhängigkeiten (kompatibel)
+    pub dependency_details: Vec<DependencyInfo>, // Alle Abhängigkeiten inkl. Versionsanforderung
+    #[serde(default)]
+    pub features: Vec<String>, // Namen aller Features dieser Version
+    pub yanked: bool, // true, wenn nur gelöschte (yanked) Versionen lokal vorhanden sind
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DependencyInfo {
+    pub name: String,
+    pub req: String, // z.B. "^1.0", "*" wenn nicht angegeben
+    pub optional: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -29,6 +115,9 @@ struct IndexEntry {
     deps: Vec<Dependency>,
     #[serde(default)]
     features: std::collections::HashMap<String, Vec<String>>,
+    // Features mit `dep:`/`?`-Syntax stehen im neueren Index-Format separat
+    #[serde(default)]
+    features2: std::collections::HashMap<String, Vec<String>>,
     yanked: bool,
 }
 
@@ -36,14 +125,58 @@ struct IndexEntry {
 struct Dependency {
     name: String,
     #[serde(default)]
+    req: String,
+    #[serde(default)]
     optional: bool,
 }
 
+/// (major, minor, patch, ist_release, pre-release): Releases sortieren nach ihren Pre-Releases
+type SemVer = (u64, u64, u64, bool, Vec<PreRelease>);
+
+/// Ein Pre-Release-Bezeichner. Nach SemVer werden numerische Bezeichner numerisch verglichen
+/// (`rc.9 < rc.10`) und sortieren vor alphanumerischen; die Variantenreihenfolge bildet das ab.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum PreRelease {
+    Numeric(u64),
+    Alphanumeric(String),
+}
+
+/// Parst "1.0.203", "0.4.0-alpha.1" oder "1.2.3+build" für den Versionsvergleich
+fn parse_semver(version: &str) -> Option<SemVer> {
+    let version = version.split('+').next()?;
+    let (core, pre) = match version.split_once('-') {
+        Some((core, pre)) => (core, Some(pre)),
+        None => (version, None),
+    };
+
+    let mut parts = core.split('.').map(|p| p.parse::<u64>().ok());
+    let (major, minor, patch) = (parts.next()??, parts.next()??, parts.next()??);
+    if parts.next().is_some() {
+        return None;
+    }
+
+    // Bei gleichem Anfang sortiert die kürzere Liste zuerst, wie beim Vec-Vergleich
+    let is_release = pre.is_none();
+    let pre = pre
+        .map(|pre| {
+            pre.split('.')
+                .map(|id| match id.parse::<u64>() {
+                    Ok(n) => PreRelease::Numeric(n),
+                    Err(_) => PreRelease::Alphanumeric(id.to_string()),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    Some((major, minor, patch, is_release, pre))
+}
+
 #[derive(Debug, Deserialize)]
 struct CargoToml {
     package: Package,
     #[serde(default)]
     dependencies: std::collections::HashMap<String, toml::Value>,
+    #[serde(default)]
+    features: std::collections::BTreeMap<String, Vec<String>>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -58,64 +191,227 @@ struct Package {
     documentation: Option<String>,
     #[serde(default)]
     license: Option<String>,
+    #[serde(default)]
+    readme: Option<toml::Value>,
 }
 
+/// So lange bleibt die Namensliste für `suggest` gültig; Cargo ergänzt den Index nur bei Builds
+const INDEX_NAMES_TTL: Duration = Duration::from_secs(300);
+
 pub struct CrateInfoProvider {
     registry_path: PathBuf,
+    // Namensliste für `suggest`, damit nicht jede erfolglose Suche den ganzen Index durchläuft
+    index_names: Mutex<Option<(Instant, Arc<Vec<String>>)>>,
 }
 
 impl CrateInfoProvider {
     pub fn new(registry_path: PathBuf) -> Self {
-        Self { registry_path }
+        Self { registry_path, index_names: Mutex::new(None) }
     }
 
-    /// Holt Crate-Informationen aus dem lokalen Registry
-    pub fn get_crate_info(&self, crate_name: &str) -> anyhow::Result<Option<CrateInfo>> {
-        // Versuche zuerst aus dem Index zu lesen
-        if let Some(info) = self.get_from_index(crate_name)? {
-            return Ok(Some(info));
+    /// Holt Crate-Informationen aus dem lokalen Registry.
+    /// `registry` schränkt die Suche auf Registries ein, deren Verzeichnisname den Text enthält.
+    /// Mit `version` wird genau diese Version beschrieben statt der neuesten; ist sie lokal
+    /// nicht vorhanden, die Crate aber schon, gibt es einen Fehler mit den vorhandenen Versionen.
+    pub fn get_crate_info(&self, crate_name: &str, registry: Option<&str>, version: Option<&str>) -> anyhow::Result<Option<CrateInfo>> {
+        if let Some(version) = version {
+            return self.get_version_info(crate_name, registry, version.trim());
+        }
+
+        // Versuche zuerst aus dem Index zu lesen (alle Registries, erste mit Treffer gewinnt)
+        for index_root in self.index_roots(registry)? {
+            if let Some(info) = self.get_from_index(&index_root, crate_name)? {
+                return Ok(Some(info));
+            }
         }
 
         // Fallback: Suche in src/ nach entpackten Crates
-        self.get_from_src(crate_name)
+        self.get_from_src(crate_name, registry)
     }
 
-    /// Liest aus dem crates.io-v3 Index (neueres Format)
-    fn get_from_index(&self, crate_name: &str) -> anyhow::Result<Option<CrateInfo>> {
+    /// Eine bestimmte Version aus dem Index, sonst aus src/ (`<crate>-<version>/Cargo.toml`)
+    fn get_version_info(&self, crate_name: &str, registry: Option<&str>, version: &str) -> anyhow::Result<Option<CrateInfo>> {
+        let mut entries = Vec::new();
+        for index_root in self.index_roots(registry)? {
+            entries.extend(self.index_entries(&index_root, crate_name)?);
+        }
+
+        if let Some(position) = entries.iter().position(|e| e.vers == version) {
+            let entry = entries.swap_remove(position);
+            let yanked = entry.yanked;
+            return Ok(Some(Self::info_from_entry(entry, crate_name, yanked)));
+        }
+
+        let mut src_versions = Vec::new();
+        let src_path = self.registry_path.join("src");
+        if src_path.is_dir() {
+            for dir in Self::registry_dirs(&src_path, registry)? {
+                let crate_path = dir.join(format!("{}-{}", crate_name, version));
+                if crate_path.join("Cargo.toml").is_file() {
+                    return self.parse_cargo_toml(&crate_path.join("Cargo.toml"));
+                }
+                src_versions.extend(fs::read_dir(&dir)?
+                    .flatten()
+                    .filter_map(|e| e.file_name().to_str()?.strip_prefix(crate_name)?.strip_prefix('-').map(String::from))
+                    .filter(|v| parse_semver(v).is_some()));
+            }
+        }
+
+        if entries.is_empty() && src_versions.is_empty() {
+            return Ok(None);
+        }
+
+        let cached: Vec<&str> = entries.iter().map(|e| e.vers.as_str())
+            .chain(src_versions.iter().map(String::as_str))
+            .collect();
+        Err(ToolError::NotFound(format!(
+            "Version {} of '{}' not found locally. {}",
+            version,
+            crate_name,
+            Self::cached_versions_hint(cached)
+        )).into())
+    }
+
+    /// "Cached versions (newest last): ..." mit den 20 neuesten Versionen
+    fn cached_versions_hint(mut versions: Vec<&str>) -> String {
+        versions.sort_by_key(|v| parse_semver(v));
+        versions.dedup();
+        format!("Cached versions (newest last): {}", versions[versions.len().saturating_sub(20)..].join(", "))
+    }
+
+    /// Alle Verzeichnisse, unter denen die Shard-Struktur (1/, 2/, 3/x/, ab/cd/) liegt:
+    /// - `index/` selbst (flaches Layout)
+    /// - pro Registry `index/<host>-<hash>/` (Git-Index) und `.../.cache/` (Sparse-Index)
+    fn index_roots(&self, registry: Option<&str>) -> anyhow::Result<Vec<PathBuf>> {
         let index_path = self.registry_path.join("index");
+        let mut roots = Vec::new();
 
-        // Index-Pfad-Logik für crates.io
-        let index_file = if crate_name.len() == 1 {
-            index_path.join("1").join(crate_name)
-        } else if crate_name.len() == 2 {
-            index_path.join("2").join(crate_name)
-        } else if crate_name.len() == 3 {
-            index_path.join("3").join(&crate_name[..1]).join(crate_name)
-        } else {
-            index_path
-                .join(&crate_name[..2])
-                .join(&crate_name[2..4])
-                .join(crate_name)
-        };
+        if !index_path.is_dir() {
+            return Ok(roots);
+        }
 
-        if !index_file.exists() {
+        if registry.is_none() {
+            roots.push(index_path.clone());
+        }
+
+        for dir in Self::registry_dirs(&index_path, registry)? {
+            roots.push(dir.join(".cache"));
+            roots.push(dir);
+        }
+
+        Ok(roots)
+    }
+
+    /// Registry-Verzeichnisse unter `parent` (index/ oder src/), optional gefiltert.
+    /// Shard-Verzeichnisse haben höchstens 2 Zeichen, Registry-Verzeichnisse mehr.
+    fn registry_dirs(parent: &Path, registry: Option<&str>) -> anyhow::Result<Vec<PathBuf>> {
+        let mut dirs: Vec<PathBuf> = fs::read_dir(parent)?
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.is_dir())
+            .filter(|p| {
+                let name = p.file_name().and_then(|s| s.to_str()).unwrap_or("");
+                name.len() > 2
+                    && !name.starts_with('.')
+                    && registry.is_none_or(|r| name.contains(r))
+            })
+            .collect();
+        dirs.sort();
+        Ok(dirs)
+    }
+
+    /// Relativer Pfad einer Crate in der Shard-Struktur des Index (wie cargo: alles kleingeschrieben,
+    /// `1/a`, `2/ab`, `3/a/abc`, sonst `ab/cd/abcd...`). Zählt Zeichen statt Bytes, damit
+    /// Namen mit Mehrbyte-Zeichen nicht mitten im Zeichen geschnitten werden.
+    fn index_file_path(crate_name: &str) -> PathBuf {
+        let name = crate_name.to_lowercase();
+        let chars: Vec<char> = name.chars().collect();
+        let prefix = |range: std::ops::Range<usize>| chars[range].iter().collect::<String>();
+
+        match chars.len() {
+            0 => PathBuf::new(),
+            1 => PathBuf::from("1").join(&name),
+            2 => PathBuf::from("2").join(&name),
+            3 => PathBuf::from("3").join(prefix(0..1)).join(&name),
+            _ => PathBuf::from(prefix(0..2)).join(prefix(2..4)).join(&name),
+        }
+    }
+
+    /// Liest aus dem crates.io-v3 Index (neueres Format) unterhalb von `index_root`
+    fn get_from_index(&self, index_root: &Path, crate_name: &str) -> anyhow::Result<Option<CrateInfo>> {
+        let Some((entry, is_yanked)) = self.latest_index_entry(index_root, crate_name)? else {
             return Ok(None);
+        };
+
+        Ok(Some(Self::info_from_entry(entry, crate_name, is_yanked)))
+    }
+
+    fn info_from_entry(entry: IndexEntry, crate_name: &str, yanked: bool) -> CrateInfo {
+        let dependencies = entry
+            .deps
+            .iter()
+            .filter(|d| !d.optional)
+            .map(|d| d.name.clone())
+            .collect();
+
+        let dependency_details = entry
+            .deps
+            .iter()
+            .map(|d| DependencyInfo {
+                name: d.name.clone(),
+                req: d.req.clone(),
+                optional: d.optional,
+            })
+            .collect();
+
+        let mut features: Vec<String> = entry.features.keys().chain(entry.features2.keys()).cloned().collect();
+        features.sort();
+        features.dedup();
+
+        CrateInfo {
+            name: entry.name,
+            version: entry.vers,
+            description: None, // Index hat keine description
+            repository: None,
+            documentation: Some(format!("https://docs.rs/{}", crate_name)),
+            license: None,
+            dependencies,
+            dependency_details,
+            features,
+            yanked,
         }
+    }
 
-        let content = fs::read_to_string(index_file)?;
+    /// Alle Index-Einträge (eine pro Version) einer Crate unterhalb von `index_root`
+    fn index_entries(&self, index_root: &Path, crate_name: &str) -> anyhow::Result<Vec<IndexEntry>> {
+        let index_file = index_root.join(Self::index_file_path(crate_name));
 
-        // Jede Zeile ist ein JSON-Eintrag für eine Version
-        let mut latest_entry: Option<IndexEntry> = None;
+        if !index_file.exists() {
+            return Ok(Vec::new());
+        }
 
-        for line in content.lines() {
-            if line.trim().is_empty() {
-                continue;
-            }
+        let bytes = fs::read(index_file)?;
+        let content = String::from_utf8_lossy(&bytes);
+
+        // Jede Zeile ist ein JSON-Eintrag für eine Version.
+        // Sparse-Cache-Dateien trennen die Einträge stattdessen mit NUL und haben einen Header.
+        Ok(content.split(['\n', '\0'])
+            .filter(|line| line.trim_start().starts_with('{'))
+            .filter_map(|line| serde_json::from_str::<IndexEntry>(line).ok())
+            .collect())
+    }
 
-            let entry: IndexEntry = serde_json::from_str(line)?;
+    /// Neuester Index-Eintrag einer Crate; yanked Versionen nur als Fallback (dann `true`)
+    fn latest_index_entry(&self, index_root: &Path, crate_name: &str) -> anyhow::Result<Option<(IndexEntry, bool)>> {
+        let mut latest_entry: Option<IndexEntry> = None;
+        let mut latest_yanked: Option<IndexEntry> = None;
 
-            // Überspringe "yanked" Versionen
+        for entry in self.index_entries(index_root, crate_name)? {
+            // "yanked" Versionen nur als Fallback merken
             if entry.yanked {
+                if latest_yanked.is_none() || self.is_newer_version(&entry.vers, &latest_yanked.as_ref().unwrap().vers) {
+                    latest_yanked = Some(entry);
+                }
                 continue;
             }
 
@@ -125,64 +421,246 @@ impl CrateInfoProvider {
             }
         }
 
-        if let Some(entry) = latest_entry {
-            let dependencies = entry
-                .deps
-                .iter()
-                .filter(|d| !d.optional)
-                .map(|d| d.name.clone())
-                .collect();
+        // Fallback: Nur yanked Versionen im Cache -> neueste davon, markiert
+        let is_yanked = latest_entry.is_none() && latest_yanked.is_some();
+        Ok(latest_entry.or(latest_yanked).map(|entry| (entry, is_yanked)))
+    }
+
+    /// Vergleicht zwei lokal im Index vorhandene Versionen: hinzugekommene, entfernte und
+    /// geänderte Abhängigkeiten (Versionsanforderung/optional) sowie Features.
+    pub fn diff_versions(&self, crate_name: &str, from: &str, to: &str) -> anyhow::Result<VersionDiff> {
+        let mut entries = Vec::new();
+        for index_root in self.index_roots(None)? {
+            entries.extend(self.index_entries(&index_root, crate_name)?);
+        }
 
-            Ok(Some(CrateInfo {
-                name: entry.name,
-                version: entry.vers,
-                description: None, // Index hat keine description
-                repository: None,
-                documentation: Some(format!("https://docs.rs/{}", crate_name)),
-                license: None,
-                dependencies,
-            }))
-        } else {
-            Ok(None)
+        if entries.is_empty() {
+            return Err(ToolError::NotFound(format!("Crate '{}' not found in the local registry index", crate_name)).into());
+        }
+
+        let find = |version: &str| entries.iter().find(|e| e.vers == version.trim());
+        let (Some(old), Some(new)) = (find(from), find(to)) else {
+            let missing: Vec<&str> = [from, to].into_iter().filter(|v| find(v).is_none()).collect();
+            return Err(ToolError::NotFound(format!(
+                "Version(s) {} of '{}' not cached locally. {}",
+                missing.join(", "),
+                crate_name,
+                Self::cached_versions_hint(entries.iter().map(|e| e.vers.as_str()).collect())
+            )).into());
+        };
+
+        // Dieselbe Abhängigkeit kann mehrfach vorkommen (normal/dev/target); eine pro Name genügt
+        let deps = |entry: &IndexEntry| -> std::collections::BTreeMap<String, DependencyInfo> {
+            entry.deps.iter()
+                .map(|d| (d.name.clone(), DependencyInfo { name: d.name.clone(), req: d.req.clone(), optional: d.optional }))
+                .collect()
+        };
+        let features = |entry: &IndexEntry| -> std::collections::BTreeSet<String> {
+            entry.features.keys().chain(entry.features2.keys()).cloned().collect()
+        };
+
+        let (old_deps, new_deps) = (deps(old), deps(new));
+        let (old_features, new_features) = (features(old), features(new));
+
+        let changed_dependencies = new_deps.iter()
+            .filter_map(|(name, new_dep)| {
+                let old_dep = old_deps.get(name)?;
+                (old_dep.req != new_dep.req || old_dep.optional != new_dep.optional).then(|| DependencyChange {
+                    name: name.clone(),
+                    from: old_dep.clone(),
+                    to: new_dep.clone(),
+                })
+            })
+            .collect();
+
+        Ok(VersionDiff {
+            crate_name: crate_name.to_string(),
+            from: old.vers.clone(),
+            to: new.vers.clone(),
+            added_dependencies: new_deps.iter().filter(|(n, _)| !old_deps.contains_key(*n)).map(|(_, d)| d.clone()).collect(),
+            removed_dependencies: old_deps.iter().filter(|(n, _)| !new_deps.contains_key(*n)).map(|(_, d)| d.clone()).collect(),
+            changed_dependencies,
+            added_features: new_features.difference(&old_features).cloned().collect(),
+            removed_features: old_features.difference(&new_features).cloned().collect(),
+        })
+    }
+
+    /// Löst für jedes Feature der neuesten Version auf, was es transitiv aktiviert.
+    /// Versteht `dep:name`, `name/feature`, schwaches `name?/feature` und implizite
+    /// Features optionaler Abhängigkeiten.
+    pub fn feature_graph(&self, crate_name: &str) -> anyhow::Result<Option<std::collections::BTreeMap<String, FeatureClosure>>> {
+        let mut found = None;
+        for index_root in self.index_roots(None)? {
+            if let Some((entry, _)) = self.latest_index_entry(&index_root, crate_name)? {
+                found = Some(entry);
+                break;
+            }
+        }
+        let Some(entry) = found else { return Ok(None) };
+
+        let mut features = entry.features.clone();
+        features.extend(entry.features2.clone());
+
+        let optional_deps: Vec<&str> = entry.deps.iter()
+            .filter(|d| d.optional)
+            .map(|d| d.name.as_str())
+            .collect();
+
+        Ok(Some(features.keys()
+            .map(|name| (name.clone(), Self::resolve_feature(name, &features, &optional_deps)))
+            .collect()))
+    }
+
+    fn resolve_feature(
+        feature: &str,
+        features: &std::collections::HashMap<String, Vec<String>>,
+        optional_deps: &[&str],
+    ) -> FeatureClosure {
+        use std::collections::BTreeSet;
+
+        let mut seen = BTreeSet::new();
+        let (mut deps, mut dep_features, mut weak) = (BTreeSet::new(), BTreeSet::new(), BTreeSet::new());
+        let mut stack = vec![feature.to_string()];
+
+        while let Some(current) = stack.pop() {
+            if !seen.insert(current.clone()) {
+                continue;
+            }
+            for value in features.get(&current).into_iter().flatten() {
+                if let Some(dep) = value.strip_prefix("dep:") {
+                    deps.insert(dep.to_string());
+                } else if value.contains("?/") {
+                    weak.insert(value.clone());
+                } else if let Some((dep, _)) = value.split_once('/') {
+                    dep_features.insert(value.clone());
+                    if optional_deps.contains(&dep) {
+                        deps.insert(dep.to_string());
+                    }
+                    // Altes Format: "dep/feat" aktiviert auch das implizite Feature "dep"
+                    if features.contains_key(dep) {
+                        stack.push(dep.to_string());
+                    }
+                } else if features.contains_key(value.as_str()) {
+                    stack.push(value.clone());
+                } else if optional_deps.contains(&value.as_str()) {
+                    // Implizites Feature einer optionalen Abhängigkeit
+                    deps.insert(value.clone());
+                }
+            }
+        }
+
+        FeatureClosure {
+            features: seen.into_iter().collect(),
+            dependencies: deps.into_iter().collect(),
+            dependency_features: dep_features.into_iter().collect(),
+            weak_dependency_features: weak.into_iter().collect(),
         }
     }
 
     /// Liest aus entpackten Crates in src/
-    fn get_from_src(&self, crate_name: &str) -> anyhow::Result<Option<CrateInfo>> {
+    fn get_from_src(&self, crate_name: &str, registry: Option<&str>) -> anyhow::Result<Option<CrateInfo>> {
+        match self.find_src_dir(crate_name, registry)? {
+            Some(crate_path) => self.parse_cargo_toml(&crate_path.join("Cargo.toml")),
+            None => Ok(None),
+        }
+    }
+
+    /// Findet das entpackte Crate-Verzeichnis in src/ (mit Cargo.toml).
+    /// Sind mehrere Versionen entpackt, gewinnt die höchste (über alle Registries).
+    fn find_src_dir(&self, crate_name: &str, registry: Option<&str>) -> anyhow::Result<Option<PathBuf>> {
         let src_path = self.registry_path.join("src");
 
         if !src_path.exists() {
             return Ok(None);
         }
 
-        // Durchsuche src/ nach passenden Crates
-        for entry in fs::read_dir(src_path)? {
-            let entry = entry?;
-            let path = entry.path();
-
-            if !path.is_dir() {
-                continue;
-            }
+        let mut best: Option<(SemVer, PathBuf)> = None;
 
-            // Crate-Verzeichnisse haben Format: github.com-xxx
+        // Durchsuche src/ nach passenden Crates
+        for path in Self::registry_dirs(&src_path, registry)? {
+            // Registry-Verzeichnisse haben Format: github.com-xxx
             for crate_dir in fs::read_dir(path)? {
                 let crate_dir = crate_dir?;
                 let crate_path = crate_dir.path();
 
-                // Format: crate_name-version
-                if let Some(dir_name) = crate_path.file_name().and_then(|s| s.to_str()) {
-                    if dir_name.starts_with(crate_name) {
-                        let cargo_toml = crate_path.join("Cargo.toml");
+                // Format: crate_name-version. Exakter Vergleich, sonst passt "serde" auch auf "serde_json-1.0.0"
+                let Some(version) = crate_path.file_name()
+                    .and_then(|s| s.to_str())
+                    .and_then(|dir_name| dir_name.strip_prefix(crate_name))
+                    .and_then(|rest| rest.strip_prefix('-'))
+                    .and_then(parse_semver)
+                else { continue };
 
-                        if cargo_toml.exists() {
-                            return self.parse_cargo_toml(&cargo_toml);
-                        }
-                    }
+                if !crate_path.join("Cargo.toml").exists() {
+                    continue;
+                }
+                if best.as_ref().is_none_or(|(current, _)| version > *current) {
+                    best = Some((version, crate_path));
                 }
             }
         }
 
-        Ok(None)
+        Ok(best.map(|(_, path)| path))
+    }
+
+    /// Verzeichnis der entpackten Quellen eines Crates (höchste Version), z.B. für die Symbolsuche
+    pub fn source_dir(&self, crate_name: &str) -> anyhow::Result<PathBuf> {
+        self.find_src_dir(crate_name, None)?
+            .ok_or_else(|| ToolError::NotFound(format!(
+                "Crate '{}' is not unpacked locally (no sources in registry/src). Building a project that depends on it downloads them.",
+                crate_name
+            )).into())
+    }
+
+    /// Liest die README eines lokal entpackten Crates (`package.readme` oder README.md)
+    pub fn get_readme(&self, crate_name: &str) -> anyhow::Result<String> {
+        // Lange READMEs kürzen, um das Kontextfenster zu schonen
+        const MAX_README_CHARS: usize = 30_000;
+
+        let crate_path = match self.find_src_dir(crate_name, None)? {
+            Some(path) => path,
+            None => {
+                let in_index = self.index_roots(None)?
+                    .iter()
+                    .any(|root| matches!(self.latest_index_entry(root, crate_name), Ok(Some(_))));
+                if in_index {
+                    return Ok(format!("README not available for '{}' (crate not unpacked locally). Build a project depending on it to unpack the sources.", crate_name));
+                }
+                return Err(ToolError::NotFound(format!("Crate '{}' not found in local cache", crate_name)).into());
+            }
+        };
+
+        // `readme` kann ein Pfad oder `false` sein
+        let content = fs::read_to_string(crate_path.join("Cargo.toml"))?;
+        let cargo_toml: CargoToml = toml::from_str(&content)?;
+        let mut candidates = Vec::new();
+        match &cargo_toml.package.readme {
+            Some(toml::Value::String(file)) => candidates.push(file.clone()),
+            Some(toml::Value::Boolean(false)) => {
+                return Ok(format!("Crate '{}' declares no README (`readme = false`).", crate_name));
+            }
+            _ => {}
+        }
+        candidates.extend(["README.md", "README", "readme.md", "README.txt"].map(String::from));
+
+        for candidate in candidates {
+            let readme_path = crate_path.join(&candidate);
+            if !readme_path.is_file() {
+                continue;
+            }
+
+            let readme = fs::read_to_string(&readme_path)?;
+            if readme.chars().count() > MAX_README_CHARS {
+                let truncated: String = readme.chars().take(MAX_README_CHARS).collect();
+                return Ok(format!(
+                    "{}\n\n[... README truncated after {} characters. Full file: {} ...]",
+                    truncated, MAX_README_CHARS, readme_path.display()
+                ));
+            }
+            return Ok(readme);
+        }
+
+        Ok(format!("No README file found in {}", crate_path.display()))
     }
 
     /// Parst eine Cargo.toml Datei
@@ -190,10 +668,16 @@ impl CrateInfoProvider {
         let content = fs::read_to_string(path)?;
         let cargo_toml: CargoToml = toml::from_str(&content)?;
 
-        let dependencies = cargo_toml
+        let dependency_details: Vec<DependencyInfo> = cargo_toml
             .dependencies
-            .keys()
-            .cloned()
+            .iter()
+            .map(|(name, value)| Self::dependency_from_toml(name, value))
+            .collect();
+
+        let dependencies = dependency_details
+            .iter()
+            .filter(|d| !d.optional)
+            .map(|d| d.name.clone())
             .collect();
 
         Ok(Some(CrateInfo {
@@ -204,36 +688,196 @@ impl CrateInfoProvider {
             documentation: cargo_toml.package.documentation,
             license: cargo_toml.package.license,
             dependencies,
+            dependency_details,
+            features: cargo_toml.features.into_keys().collect(),
+            yanked: false,
         }))
     }
 
+    /// Liest eine Abhängigkeit aus Cargo.toml (`serde = "1"` oder `serde = { version = "1", optional = true }`)
+    fn dependency_from_toml(name: &str, value: &toml::Value) -> DependencyInfo {
+        let (req, optional) = match value {
+            toml::Value::String(version) => (version.clone(), false),
+            toml::Value::Table(table) => (
+                table.get("version").and_then(|v| v.as_str()).unwrap_or("*").to_string(),
+                table.get("optional").and_then(|v| v.as_bool()).unwrap_or(false),
+            ),
+            _ => ("*".to_string(), false),
+        };
+
+        DependencyInfo {
+            name: name.to_string(),
+            req,
+            optional,
+        }
+    }
+
+    /// Listet die Dateien unter `examples/` eines lokal entpackten Crates (relative Pfade)
+    pub fn list_examples(&self, crate_name: &str) -> anyhow::Result<Vec<String>> {
+        let crate_path = self.find_src_dir(crate_name, None)?
+            .ok_or_else(|| ToolError::NotFound(format!("Crate '{}' is not unpacked locally (no sources in registry/src)", crate_name)))?;
+
+        let examples_dir = crate_path.join("examples");
+        if !examples_dir.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        // Beispiele können auch Unterordner sein (examples/foo/main.rs)
+        let mut examples: Vec<String> = walkdir::WalkDir::new(&examples_dir)
+            .into_iter()
+            .flatten()
+            .filter(|e| e.file_type().is_file())
+            .filter_map(|e| e.path().strip_prefix(&examples_dir).ok().map(|p| p.display().to_string()))
+            .collect();
+        examples.sort();
+
+        Ok(examples)
+    }
+
+    /// Liest ein einzelnes Beispiel (`name`, `name.rs` oder relativer Pfad)
+    pub fn read_example(&self, crate_name: &str, example: &str) -> anyhow::Result<String> {
+        let examples = self.list_examples(crate_name)?;
+
+        let wanted = example.trim_end_matches(".rs");
+        let found = examples.iter().find(|e| {
+            e.as_str() == example
+                || e.trim_end_matches(".rs") == wanted
+                || *e == &format!("{}/main.rs", wanted)
+        });
+
+        match found {
+            Some(relative) => {
+                let crate_path = self.find_src_dir(crate_name, None)?
+                    .ok_or_else(|| ToolError::NotFound(format!("Crate '{}' is not unpacked locally", crate_name)))?;
+                Ok(fs::read_to_string(crate_path.join("examples").join(relative))?)
+            }
+            None => Err(ToolError::NotFound(format!(
+                "Example '{}' not found in '{}'. Available: {}",
+                example,
+                crate_name,
+                if examples.is_empty() { "(none)".to_string() } else { examples.join(", ") }
+            )).into()),
+        }
+    }
+
     /// Vergleicht Versionsnummern (simpel)
     fn is_newer_version(&self, v1: &str, v2: &str) -> bool {
         v1 > v2 // Lexikographischer Vergleich funktioniert für semver meist
     }
 
-    /// Liste verfügbare Crates (limitiert)
-    pub fn list_available_crates(&self, limit: usize) -> anyhow::Result<Vec<String>> {
-        let index_path = self.registry_path.join("index");
-        let mut crates = Vec::new();
+    /// Schlägt Crate-Namen vor, die mit `prefix` beginnen oder ihm ähnlich sind
+    pub fn suggest(&self, prefix: &str, limit: usize) -> anyhow::Result<Vec<String>> {
+        let prefix = prefix.trim().to_lowercase().replace('_', "-");
+        if prefix.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let names = self.cached_index_names()?;
+
+        // Exakte Präfix-Treffer zuerst (kürzeste zuerst), danach unscharfe Treffer
+        let normalize = |name: &str| name.to_lowercase().replace('_', "-");
+        let mut prefix_matches: Vec<&String> = names.iter()
+            .filter(|n| normalize(n).starts_with(&prefix))
+            .collect();
+        prefix_matches.sort_by(|a, b| a.len().cmp(&b.len()).then(a.cmp(b)));
+
+        let max_distance = if prefix.len() > 4 { 2 } else { 1 };
+        let mut fuzzy_matches: Vec<(usize, &String)> = names.iter()
+            .filter(|n| !normalize(n).starts_with(&prefix))
+            .filter_map(|n| {
+                let normalized = normalize(n);
+                // Vergleiche mit dem gleich langen Anfang des Namens
+                let head: String = normalized.chars().take(prefix.chars().count()).collect();
+                let distance = levenshtein(&head, &prefix).min(levenshtein(&normalized, &prefix));
+                (distance <= max_distance).then_some((distance, n))
+            })
+            .collect();
+        fuzzy_matches.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.len().cmp(&b.1.len())));
+
+        Ok(prefix_matches.into_iter()
+            .chain(fuzzy_matches.into_iter().map(|(_, n)| n))
+            .take(limit)
+            .cloned()
+            .collect())
+    }
 
-        if !index_path.exists() {
-            return Ok(crates);
+    /// Alle Crate-Namen, für `INDEX_NAMES_TTL` zwischengespeichert.
+    /// Der Lock bleibt während des Durchlaufs gehalten, parallele Aufrufe warten statt selbst zu suchen.
+    fn cached_index_names(&self) -> anyhow::Result<Arc<Vec<String>>> {
+        let mut cache = self.index_names.lock().unwrap();
+        if let Some((collected, names)) = cache.as_ref() {
+            if collected.elapsed() < INDEX_NAMES_TTL {
+                return Ok(names.clone());
+            }
         }
 
-        // Durchsuche Index-Verzeichnisse
-        for entry in fs::read_dir(&index_path)?.take(limit) {
-            if let Ok(entry) = entry {
-                let path = entry.path();
-                if path.is_file() {
-                    if let Some(name) = path.file_name().and_then(|s| s.to_str()) {
-                        crates.push(name.to_string());
-                    }
-                }
+        let names = Arc::new(self.collect_index_names(usize::MAX)?);
+        *cache = Some((Instant::now(), names.clone()));
+        Ok(names)
+    }
+
+    /// Sammelt Crate-Namen aus allen Registries (ohne Duplikate)
+    fn collect_index_names(&self, limit: usize) -> anyhow::Result<Vec<String>> {
+        let mut names = Vec::new();
+        for root in self.index_roots(None)? {
+            Self::walk_index(&root, 0, &mut names, limit)?;
+            names.sort();
+            names.dedup();
+            if names.len() >= limit {
+                names.truncate(limit);
+                break;
             }
         }
+        Ok(names)
+    }
+
+    /// Durchläuft die Shard-Verzeichnisse des Index (1/, 2/, 3/x/, ab/cd/) und sammelt Crate-Namen
+    fn walk_index(dir: &Path, depth: usize, names: &mut Vec<String>, limit: usize) -> anyhow::Result<()> {
+        // Tiefste Ebene ist ab/cd/<name>
+        if depth > 2 || names.len() >= limit || !dir.is_dir() {
+            return Ok(());
+        }
+
+        let mut entries: Vec<PathBuf> = fs::read_dir(dir)?
+            .flatten()
+            .map(|e| e.path())
+            .collect();
+        entries.sort();
+
+        for path in entries {
+            if names.len() >= limit {
+                break;
+            }
+
+            let name = match path.file_name().and_then(|s| s.to_str()) {
+                Some(n) => n.to_string(),
+                None => continue,
+            };
+
+            // .git, .cache und config.json sind keine Crates
+            if name.starts_with('.') || name == "config.json" {
+                continue;
+            }
+
+            // Auf oberster Ebene sind längere Namen Registry-Verzeichnisse (eigene Wurzel)
+            if path.is_dir() && depth == 0 && name.len() > 2 {
+                continue;
+            }
 
-        Ok(crates)
+            if path.is_dir() {
+                Self::walk_index(&path, depth + 1, names, limit)?;
+            } else if depth > 0 {
+                names.push(name);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Liste verfügbare Crates (limitiert)
+    /// Steigt in die Shard-Verzeichnisse ab und liefert Namen, keine Pfade.
+    pub fn list_available_crates(&self, limit: usize) -> anyhow::Result<Vec<String>> {
+        self.collect_index_names(limit)
     }
 }
 
@@ -241,6 +885,7 @@ impl CrateInfoProvider {
 mod tests {
     use super::*;
     use crate::utils::paths::RustPaths;
+    use crate::utils::test_support::TempDir;
 
     #[test]
     fn test_get_crate_info() {
@@ -250,7 +895,7 @@ mod tests {
             let provider = CrateInfoProvider::new(registry_path);
 
             // Test: Suche nach "serde"
-            if let Ok(Some(info)) = provider.get_crate_info("serde") {
+            if let Ok(Some(info)) = provider.get_crate_info("serde", None, None) {
                 println!("Found serde: v{}", info.version);
                 println!("  Description: {:?}", info.description);
                 println!("  Dependencies: {:?}", info.dependencies);
@@ -259,4 +904,168 @@ mod tests {
             }
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_parse_semver_precedence() {
+        // Reihenfolge aus der SemVer-Spezifikation, ergänzt um zweistellige Bezeichner
+        let ordered = [
+            "1.0.0-alpha", "1.0.0-alpha.1", "1.0.0-alpha.beta", "1.0.0-beta", "1.0.0-beta.2",
+            "1.0.0-beta.11", "1.0.0-rc.1", "1.0.0-rc.9", "1.0.0-rc.10", "1.0.0", "1.0.1+build.5", "1.10.0",
+        ];
+        for pair in ordered.windows(2) {
+            assert!(parse_semver(pair[0]) < parse_semver(pair[1]), "{} < {}", pair[0], pair[1]);
+        }
+        assert_eq!(parse_semver("1.0.0+abc"), parse_semver("1.0.0"));
+        assert!(parse_semver("1.0").is_none());
+    }
+
+    #[test]
+    fn test_index_file_path() {
+        assert_eq!(CrateInfoProvider::index_file_path("a"), PathBuf::from("1/a"));
+        assert_eq!(CrateInfoProvider::index_file_path("ab"), PathBuf::from("2/ab"));
+        assert_eq!(CrateInfoProvider::index_file_path("syn"), PathBuf::from("3/s/syn"));
+        assert_eq!(CrateInfoProvider::index_file_path("rand"), PathBuf::from("ra/nd/rand"));
+        assert_eq!(CrateInfoProvider::index_file_path("Serde_JSON"), PathBuf::from("se/rd/serde_json"));
+        // Mehrbyte-Zeichen: kein Panic, Shards nach Zeichen
+        assert_eq!(CrateInfoProvider::index_file_path("äöü"), PathBuf::from("3/ä/äöü"));
+        assert_eq!(CrateInfoProvider::index_file_path("äbcdé"), PathBuf::from("äb/cd/äbcdé"));
+        assert_eq!(CrateInfoProvider::index_file_path(""), PathBuf::new());
+    }
+
+    #[test]
+    fn test_list_available_crates_sharded() {
+        let registry = TempDir::new("index");
+        let index = registry.join("index");
+        for (dir, name) in [("1", "a"), ("2", "ab"), ("3/s", "syn"), ("se/rd", "serde")] {
+            fs::create_dir_all(index.join(dir)).unwrap();
+            fs::write(index.join(dir).join(name), "").unwrap();
+        }
+        fs::write(index.join("config.json"), "{}").unwrap();
+        fs::create_dir_all(index.join(".cache/se/rd")).unwrap();
+        fs::write(index.join(".cache/se/rd/serde"), "").unwrap();
+
+        let provider = CrateInfoProvider::new(registry.path().to_path_buf());
+        let mut crates = provider.list_available_crates(100).unwrap();
+        crates.sort();
+        assert_eq!(crates, vec!["a", "ab", "serde", "syn"]);
+        assert_eq!(provider.list_available_crates(2).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_suggest_reuses_index_names() {
+        let registry = TempDir::new("suggest");
+        let shard = registry.join("index/index.crates.io-6f17d22bba15001f/.cache/se/rd");
+        fs::create_dir_all(&shard).unwrap();
+        fs::write(shard.join("serde"), "").unwrap();
+        fs::write(shard.join("serde_json"), "").unwrap();
+
+        let provider = CrateInfoProvider::new(registry.path().to_path_buf());
+        assert_eq!(provider.suggest("serd", 5).unwrap(), vec!["serde", "serde_json"]);
+        assert_eq!(provider.suggest("sedre", 1).unwrap(), vec!["serde"]);
+
+        // Innerhalb der TTL wird der Index nicht erneut durchlaufen
+        fs::write(shard.join("serde_yaml"), "").unwrap();
+        assert_eq!(provider.suggest("serde_", 1).unwrap(), vec!["serde_json"]);
+        assert!(!provider.suggest("serde_y", 5).unwrap().contains(&"serde_yaml".to_string()));
+    }
+
+    #[test]
+    fn test_resolve_feature() {
+        let features: std::collections::HashMap<String, Vec<String>> = [
+            ("default", vec!["std", "derive"]),
+            ("std", vec!["serde?/std"]),
+            ("derive", vec!["dep:serde_derive", "serde/derive"]),
+            ("full", vec!["default", "rayon"]),
+        ].into_iter().map(|(k, v)| (k.to_string(), v.into_iter().map(String::from).collect())).collect();
+        let optional = ["serde", "serde_derive", "rayon"];
+
+        let closure = CrateInfoProvider::resolve_feature("full", &features, &optional);
+        assert_eq!(closure.features, vec!["default", "derive", "full", "std"]);
+        assert_eq!(closure.dependencies, vec!["rayon", "serde", "serde_derive"]);
+        assert_eq!(closure.dependency_features, vec!["serde/derive"]);
+        assert_eq!(closure.weak_dependency_features, vec!["serde?/std"]);
+    }
+
+    #[test]
+    fn test_get_crate_info_multiple_registries() {
+        let registry = TempDir::new("registries");
+        let entry = |name: &str, vers: &str| format!(r#"{{"name":"{}","vers":"{}","deps":[],"yanked":false}}"#, name, vers);
+
+        // Sparse-Cache: Header + NUL-getrennte Einträge
+        let sparse = registry.join("index/index.crates.io-6f17d22bba15001f/.cache/3/s");
+        fs::create_dir_all(&sparse).unwrap();
+        fs::write(sparse.join("syn"), format!("\u{3}\0\0\0etag\0{}\0{}\0", "2.0.0", entry("syn", "2.0.0"))).unwrap();
+
+        // Privates Registry mit Git-Index-Layout
+        let private = registry.join("index/git.example.com-0123456789abcdef/2");
+        fs::create_dir_all(&private).unwrap();
+        fs::write(private.join("ab"), entry("ab", "0.1.0") + "\n").unwrap();
+
+        let provider = CrateInfoProvider::new(registry.path().to_path_buf());
+        assert_eq!(provider.get_crate_info("syn", None, None).unwrap().unwrap().version, "2.0.0");
+        assert_eq!(provider.get_crate_info("ab", None, None).unwrap().unwrap().version, "0.1.0");
+        assert!(provider.get_crate_info("ab", Some("crates.io"), None).unwrap().is_none());
+
+        let mut crates = provider.list_available_crates(100).unwrap();
+        crates.sort();
+        assert_eq!(crates, vec!["ab", "syn"]);
+    }
+
+    #[test]
+    fn test_get_from_src_prefers_highest_version() {
+        let registry = TempDir::new("src_versions");
+        let src = registry.join("src/index.crates.io-6f17d22bba15001f");
+        for (dir, name, version) in [
+            ("serde-1.0.9", "serde", "1.0.9"),
+            ("serde-1.0.10", "serde", "1.0.10"),
+            ("serde-1.0.10-rc.1", "serde", "1.0.10-rc.1"),
+            ("serde_json-1.0.99", "serde_json", "1.0.99"),
+        ] {
+            fs::create_dir_all(src.join(dir)).unwrap();
+            fs::write(
+                src.join(dir).join("Cargo.toml"),
+                format!("[package]\nname = \"{}\"\nversion = \"{}\"\n", name, version),
+            ).unwrap();
+        }
+
+        let provider = CrateInfoProvider::new(registry.path().to_path_buf());
+        let serde = provider.get_crate_info("serde", None, None).unwrap().unwrap();
+        assert_eq!((serde.name.as_str(), serde.version.as_str()), ("serde", "1.0.10"));
+        assert_eq!(provider.get_crate_info("serde_json", None, None).unwrap().unwrap().version, "1.0.99");
+        assert!(provider.get_crate_info("serd", None, None).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_diff_versions() {
+        let registry = TempDir::new("diff_versions");
+        let shard = registry.join("index/index.crates.io-6f17d22bba15001f/.cache/de/mo");
+        fs::create_dir_all(&shard).unwrap();
+        let old = r#"{"name":"demo","vers":"1.0.0","deps":[{"name":"log","req":"^0.4"},{"name":"libc","req":"^0.2"}],"features":{"std":[]},"yanked":false}"#;
+        let new = r#"{"name":"demo","vers":"1.1.0","deps":[{"name":"log","req":"^0.4.20"},{"name":"tracing","req":"^0.1","optional":true}],"features":{"std":[]},"features2":{"tracing":["dep:tracing"]},"yanked":false}"#;
+        fs::write(shard.join("demo"), format!("\u{3}\0\0\0etag\0{}\0{}\0", old, new)).unwrap();
+
+        let provider = CrateInfoProvider::new(registry.path().to_path_buf());
+        let diff = provider.diff_versions("demo", "1.0.0", "1.1.0").unwrap();
+        let names = |deps: &[DependencyInfo]| deps.iter().map(|d| d.name.clone()).collect::<Vec<_>>();
+        assert_eq!(names(&diff.added_dependencies), vec!["tracing"]);
+        assert_eq!(names(&diff.removed_dependencies), vec!["libc"]);
+        assert_eq!(diff.changed_dependencies.len(), 1);
+        assert_eq!(diff.changed_dependencies[0].to.req, "^0.4.20");
+        assert_eq!(diff.added_features, vec!["tracing"]);
+        assert!(diff.removed_features.is_empty());
+
+        let missing = provider.diff_versions("demo", "1.0.0", "2.0.0").unwrap_err().to_string();
+        assert!(missing.contains("2.0.0") && missing.contains("1.0.0, 1.1.0"));
+
+        // Bestimmte Version statt der neuesten
+        let pinned = provider.get_crate_info("demo", None, Some("1.0.0")).unwrap().unwrap();
+        assert_eq!(pinned.version, "1.0.0");
+        assert_eq!(pinned.dependencies, vec!["log", "libc"]);
+        assert_eq!(pinned.features, vec!["std"]);
+        assert_eq!(provider.get_crate_info("demo", None, None).unwrap().unwrap().features, vec!["std", "tracing"]);
+
+        let missing = provider.get_crate_info("demo", None, Some("0.9.0")).unwrap_err().to_string();
+        assert!(missing.contains("not found locally") && missing.contains("1.0.0, 1.1.0"));
+        assert!(provider.get_crate_info("nope", None, Some("1.0.0")).unwrap().is_none());
+    }
+}
@@ -1,10 +1,14 @@
 // src/tools/dependencies.rs
 use std::path::PathBuf;
+use std::time::Duration;
 use anyhow::{Context, Result};
 use rmcp::schemars::JsonSchema;
 use serde::Deserialize;
-use tokio::process::Command;
+use tracing::Instrument;
 use rmcp::schemars;
+use tokio_util::sync::CancellationToken;
+use crate::utils::process::{capped_output, cargo_command, output_with_retry, ProcessOutcome};
+use crate::utils::ToolError;
 
 #[derive(Deserialize, JsonSchema)]
 pub struct AddDepRequest {
@@ -14,6 +18,10 @@ pub struct AddDepRequest {
     pub crate_name: String,
     #[schemars(description = "Optional features (e.g., ['macros', 'rt-multi-thread'])")]
     pub features: Option<Vec<String>>,
+    #[schemars(description = "Optional: Only show what 'cargo add' would do (resolved version, features) without changing Cargo.toml (default false)")]
+    pub dry_run: Option<bool>,
+    #[schemars(description = "Optional: Timeout in seconds per attempt (default: server's --command-timeout-secs)")]
+    pub timeout_secs: Option<u64>,
 }
 
 pub struct DependencyManager;
@@ -28,42 +36,67 @@ impl DependencyManager {
         project_path: PathBuf,
         crate_name: &str,
         features: Option<Vec<String>>,
+        dry_run: bool,
+        cancel: &CancellationToken,
+        timeout: Duration,
     ) -> Result<String> {
         // 1. Validation
         if !project_path.exists() {
-            anyhow::bail!("Project path '{}' does not exist", project_path.display());
+            return Err(ToolError::NotFound(format!("Project path '{}' does not exist", project_path.display())).into());
         }
 
         let cargo_toml = project_path.join("Cargo.toml");
         if !cargo_toml.exists() {
-            anyhow::bail!("No Cargo.toml found at '{}'", project_path.display());
+            return Err(ToolError::NotFound(format!("No Cargo.toml found at '{}'", project_path.display())).into());
         }
 
         // 2. Construct Command
-        let mut cmd = Command::new("cargo");
-        cmd.current_dir(&project_path) // Execute inside the project folder
-            .arg("add")
-            .arg(crate_name);
+        let build = || {
+            let mut cmd = cargo_command();
+            cmd.current_dir(&project_path) // Execute inside the project folder
+                .arg("add")
+                .arg(crate_name);
 
-        if let Some(feats) = features {
-            if !feats.is_empty() {
-                cmd.arg("--features");
-                cmd.arg(feats.join(","));
+            if let Some(feats) = &features {
+                if !feats.is_empty() {
+                    cmd.arg("--features");
+                    cmd.arg(feats.join(","));
+                }
             }
-        }
 
-        // 3. Execute Async
-        let output = cmd.output().await.context("Failed to execute 'cargo add'")?;
+            if dry_run {
+                cmd.arg("--dry-run");
+            }
+            cmd
+        };
+
+        // 3. Execute Async (registry blips are retried with backoff)
+        let output = match output_with_retry(build, cancel, timeout)
+            .instrument(tracing::info_span!("cargo_add", crate_name))
+            .await
+            .context("Failed to execute 'cargo add'")?
+        {
+            ProcessOutcome::Completed(output) => output,
+            ProcessOutcome::TimedOut(limit) => return Err(ToolError::Timeout(format!("'cargo add' timed out after {}s", limit.as_secs())).into()),
+            ProcessOutcome::Cancelled => return Ok(format!("Adding '{}' cancelled by client.", crate_name)),
+        };
 
-        if output.status.success() {
+        if output.status.success() && dry_run {
+            // cargo describes the resolution on stderr; pass it through verbatim
+            Ok(format!(
+                "Dry run for '{}' (Cargo.toml was NOT modified):\n{}",
+                crate_name,
+                capped_output(&output.stderr)
+            ))
+        } else if output.status.success() {
             Ok(format!(
                 "Successfully added '{}'.\n{}",
                 crate_name,
-                String::from_utf8_lossy(&output.stderr) // cargo add prints to stderr usually
+                capped_output(&output.stderr) // cargo add prints to stderr usually
             ))
         } else {
-            let error_msg = String::from_utf8_lossy(&output.stderr);
-            anyhow::bail!("Cargo failed: {}", error_msg);
+            let error_msg = capped_output(&output.stderr);
+            Err(ToolError::CommandFailed(format!("Cargo failed: {}", error_msg)).into())
         }
     }
 }
\ No newline at end of file
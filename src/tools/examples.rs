@@ -0,0 +1,141 @@
+// src/tools/examples.rs
+use std::path::PathBuf;
+use std::time::Duration;
+use anyhow::{Context, Result};
+use rmcp::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use rmcp::schemars;
+use tokio_util::sync::CancellationToken;
+use tracing::Instrument;
+use crate::utils::process::{capped_output, cargo_command, output_with_timeout, ProcessOutcome};
+use crate::utils::metadata::cargo_metadata;
+use crate::utils::ToolError;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ListExamplesRequest {
+    #[schemars(description = "Absolute path to the project root")]
+    pub path: String,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct RunExampleRequest {
+    #[schemars(description = "Absolute path to the project root")]
+    pub path: String,
+    #[schemars(description = "Name of the example target (see 'list_examples')")]
+    pub name: String,
+    #[schemars(description = "Optional: Arguments passed to the example (after '--')")]
+    pub args: Option<Vec<String>>,
+    #[schemars(description = "Optional: Timeout in seconds (default 120)")]
+    pub timeout_secs: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExampleTarget {
+    pub name: String,
+    pub package: String,
+    pub src_path: String,
+    /// `required-features` of the target; the example is skipped by cargo without them
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub required_features: Vec<String>,
+}
+
+pub struct ExampleRunner;
+
+impl ExampleRunner {
+    /// Examples are usually short demos; long-running servers get killed.
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(120);
+
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Lists the example targets of the project (all workspace members) via `cargo metadata`.
+    pub async fn list(&self, project_path: PathBuf, cancel: &CancellationToken, timeout: Duration) -> Result<Vec<ExampleTarget>> {
+        if !project_path.join("Cargo.toml").exists() {
+            return Err(ToolError::NotFound(format!("No Cargo.toml found at '{}'.", project_path.display())).into());
+        }
+
+        let metadata = cargo_metadata(&project_path, cancel, timeout).await?;
+
+        let mut examples: Vec<ExampleTarget> = metadata.packages.into_iter()
+            .flat_map(|package| {
+                let package_name = package.name;
+                package.targets.into_iter()
+                    .filter(|t| t.kind.iter().any(|k| k == "example"))
+                    .map(move |t| ExampleTarget {
+                        name: t.name,
+                        package: package_name.clone(),
+                        src_path: t.src_path,
+                        required_features: t.required_features,
+                    })
+            })
+            .collect();
+        examples.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(examples)
+    }
+
+    /// Runs `cargo run --example <name> -- <args>`.
+    /// The process is killed when `timeout` elapses or `cancel` fires.
+    pub async fn run(
+        &self,
+        project_path: PathBuf,
+        name: &str,
+        args: &[String],
+        timeout: Duration,
+        cancel: &CancellationToken,
+    ) -> Result<String> {
+        let examples = self.list(project_path.clone()).await?;
+        let Some(example) = examples.iter().find(|e| e.name == name) else {
+            let available: Vec<&str> = examples.iter().map(|e| e.name.as_str()).collect();
+            return Err(ToolError::NotFound(format!(
+                "Example '{}' not found. Available: {}",
+                name,
+                if available.is_empty() { "(none)".to_string() } else { available.join(", ") }
+            )).into());
+        };
+
+        let mut cmd = cargo_command();
+        cmd.current_dir(&project_path)
+            .arg("run")
+            .arg("--color").arg("never")
+            .arg("--package").arg(&example.package)
+            .arg("--example").arg(&example.name);
+
+        if !example.required_features.is_empty() {
+            cmd.arg("--features").arg(example.required_features.join(","));
+        }
+
+        if !args.is_empty() {
+            cmd.arg("--").args(args);
+        }
+
+        let output = match output_with_timeout(cmd, cancel, timeout)
+            .instrument(tracing::info_span!("cargo_run_example", path = %project_path.display(), example = %name))
+            .await
+            .context("Failed to execute 'cargo run --example'")?
+        {
+            ProcessOutcome::Completed(output) => output,
+            ProcessOutcome::Cancelled => return Ok("Example cancelled by client.".to_string()),
+            ProcessOutcome::TimedOut(limit) => return Err(ToolError::Timeout(format!(
+                "Example '{}' timed out after {}s and was killed. Use a larger 'timeout_secs' if it is expected to run longer.",
+                name,
+                limit.as_secs()
+            )).into()),
+        };
+
+        let stdout = capped_output(&output.stdout);
+        let stderr = capped_output(&output.stderr);
+
+        let status_msg = match output.status.code() {
+            Some(0) => "Example finished successfully.".to_string(),
+            Some(code) => format!("Example failed (exit code {}).", code),
+            None => "Example was terminated by a signal.".to_string(),
+        };
+
+        Ok(format!(
+            "{}\n\n=== STDOUT ===\n{}\n=== STDERR ===\n{}",
+            status_msg, stdout, stderr
+        ))
+    }
+}
@@ -1,9 +1,18 @@
-use std::process::Command;
-use anyhow::Result;
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+use anyhow::{Context, Result};
 use regex::Regex;
+use tokio::process::Command;
+use tokio_util::sync::CancellationToken;
 use rmcp::schemars::JsonSchema;
 use rmcp::schemars;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use crate::utils::process::{output_with_timeout, ProcessOutcome};
+use crate::utils::ToolError;
+
+static CODE_FORMAT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^E\d{4}$").unwrap());
+static CODE_IN_TEXT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\bE\d{4}\b").unwrap());
 
 #[derive(Deserialize, JsonSchema)]
 pub struct ExplainRequest {
@@ -11,32 +20,159 @@ pub struct ExplainRequest {
     pub error_code: String,
 }
 
-pub struct ErrorExplainer;
+#[derive(Deserialize, JsonSchema)]
+pub struct ExplainOutputRequest {
+    #[schemars(description = "Raw compiler/build output, e.g. a pasted CI log")]
+    pub output: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CodeExplanation {
+    pub code: String,
+    /// Lines of the input that mention the code
+    pub lines: Vec<String>,
+    pub explanation: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OutputExplanation {
+    pub codes: Vec<CodeExplanation>,
+    /// Distinct codes found beyond `MAX_EXPLAINED_CODES` (not explained)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub unexplained_codes: Vec<String>,
+    /// `error:`/`warning:` lines without a code
+    pub other_diagnostics: Vec<String>,
+}
+
+pub struct ErrorExplainer {
+    // Explanations never change for a given toolchain, so cache them
+    cache: Mutex<HashMap<String, String>>,
+}
 
 impl ErrorExplainer {
     pub fn new() -> Self {
-        Self
+        Self {
+            cache: Mutex::new(HashMap::new()),
+        }
     }
 
-    pub fn explain(&self, error_code: &str) -> Result<String> {
+    pub async fn explain(&self, error_code: &str, cancel: &CancellationToken, timeout: Duration) -> Result<String> {
+        if let Some(cached) = self.cache.lock().unwrap().get(error_code) {
+            return Ok(cached.clone());
+        }
         // 1. Validate input to prevent command injection (must look like E0123)
-        let re = Regex::new(r"^E\d{4}$").unwrap();
-        if !re.is_match(error_code) {
+        if !CODE_FORMAT.is_match(error_code) {
             return Ok(format!("Invalid error code format: '{}'. Expected format like 'E0308'.", error_code));
         }
 
         // 2. Run rustc --explain
-        let output = Command::new("rustc")
-            .arg("--explain")
-            .arg(error_code)
-            .output()?;
+        let mut cmd = Command::new("rustc");
+        cmd.arg("--explain").arg(error_code);
+
+        let output = match output_with_timeout(cmd, cancel, timeout)
+            .await
+            .context("Failed to execute 'rustc --explain'")?
+        {
+            ProcessOutcome::Completed(output) => output,
+            ProcessOutcome::TimedOut(limit) => return Err(ToolError::Timeout(format!("'rustc --explain {}' timed out after {}s", error_code, limit.as_secs())).into()),
+            ProcessOutcome::Cancelled => return Ok(format!("Explaining {} cancelled by client.", error_code)),
+        };
 
         if output.status.success() {
             let text = String::from_utf8_lossy(&output.stdout).to_string();
             // Optional: Truncate if too long, but usually explanations are fine
+            self.cache.lock().unwrap().insert(error_code.to_string(), text.clone());
             Ok(text)
         } else {
             Ok(format!("No explanation found for {}. It might not be a standard rustc error code.", error_code))
         }
     }
-}
\ No newline at end of file
+
+    /// Explanations are long; more codes than this would flood the context.
+    pub const MAX_EXPLAINED_CODES: usize = 5;
+    const MAX_DIAGNOSTIC_LINES: usize = 50;
+
+    /// Scans unstructured build output for error codes (in order of first appearance)
+    /// and explains each one; `error:`/`warning:` lines without a code are listed as is.
+    pub async fn explain_output(&self, output: &str, cancel: &CancellationToken, timeout: Duration) -> OutputExplanation {
+        let mut found: Vec<(String, Vec<String>)> = Vec::new();
+        let mut other_diagnostics = Vec::new();
+
+        for line in output.lines() {
+            let trimmed = line.trim();
+            let mut has_code = false;
+
+            for m in CODE_IN_TEXT.find_iter(trimmed) {
+                has_code = true;
+                match found.iter_mut().find(|(code, _)| code == m.as_str()) {
+                    Some((_, lines)) => {
+                        if !lines.iter().any(|l| l == trimmed) {
+                            lines.push(trimmed.to_string());
+                        }
+                    }
+                    None => found.push((m.as_str().to_string(), vec![trimmed.to_string()])),
+                }
+            }
+
+            let is_diagnostic = ["error:", "warning:", "error[", "warning["].iter().any(|p| trimmed.starts_with(p));
+            if !has_code && is_diagnostic && other_diagnostics.len() < Self::MAX_DIAGNOSTIC_LINES {
+                other_diagnostics.push(trimmed.to_string());
+            }
+        }
+
+        let unexplained_codes = found.iter()
+            .skip(Self::MAX_EXPLAINED_CODES)
+            .map(|(code, _)| code.clone())
+            .collect();
+
+        let mut codes = Vec::new();
+        for (code, lines) in found.into_iter().take(Self::MAX_EXPLAINED_CODES) {
+            let explanation = self.explain(&code, cancel, timeout)
+                .await
+                .unwrap_or_else(|e| format!("Could not explain {}: {}", code, e));
+            codes.push(CodeExplanation { code, lines, explanation });
+        }
+
+        OutputExplanation { codes, unexplained_codes, other_diagnostics }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_explain_output() {
+        let explainer = ErrorExplainer::new();
+        // Pre-filled cache, so no rustc is needed
+        for code in ["E0308", "E0425", "E0599", "E0277", "E0061"] {
+            explainer.cache.lock().unwrap().insert(code.to_string(), format!("about {}", code));
+        }
+
+        let log = [
+            "error[E0308]: mismatched types",
+            " --> src/main.rs:4:18",
+            "error[E0308]: mismatched types",
+            "error[E0425]: cannot find value `x` in this scope",
+            "warning: unused variable: `y`",
+            "error: could not compile `demo` (bin \"demo\") due to 3 previous errors",
+            "For more information about an error, try `rustc --explain E0308`.",
+            "  = note: see E0599, E0277, E0061 and E0382",
+        ].join("\n");
+        let result = explainer.explain_output(&log, &CancellationToken::new(), Duration::from_secs(30)).await;
+
+        let codes: Vec<&str> = result.codes.iter().map(|c| c.code.as_str()).collect();
+        assert_eq!(codes, vec!["E0308", "E0425", "E0599", "E0277", "E0061"]);
+        assert_eq!(result.unexplained_codes, vec!["E0382"]);
+        // Repeated lines are listed once
+        assert_eq!(result.codes[0].lines, vec![
+            "error[E0308]: mismatched types",
+            "For more information about an error, try `rustc --explain E0308`.",
+        ]);
+        assert_eq!(result.codes[1].explanation, "about E0425");
+        assert_eq!(result.other_diagnostics, vec![
+            "warning: unused variable: `y`",
+            "error: could not compile `demo` (bin \"demo\") due to 3 previous errors",
+        ]);
+    }
+}
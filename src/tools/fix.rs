@@ -0,0 +1,118 @@
+// src/tools/fix.rs
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use anyhow::{Context, Result};
+use rmcp::schemars::JsonSchema;
+use serde::Deserialize;
+use rmcp::schemars;
+use similar::TextDiff;
+use tokio_util::sync::CancellationToken;
+use tracing::Instrument;
+use crate::utils::process::{capped_output, cargo_command, output_with_timeout, ProcessOutcome};
+use crate::utils::walk::rust_files;
+use crate::utils::{IgnoreList, ToolError};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct CargoFixRequest {
+    #[schemars(description = "Absolute path to the project root")]
+    pub path: String,
+    #[schemars(description = "Optional: Apply the migration lints for the next edition (cargo fix --edition). Default false.")]
+    pub edition_migration: Option<bool>,
+    #[schemars(description = "Optional: Allow fixing a repository with uncommitted changes (or no VCS at all). Default false: commit first so the fixes can be reviewed and undone.")]
+    pub allow_dirty: Option<bool>,
+    #[schemars(description = "Optional: Timeout in seconds (default: server's --command-timeout-secs)")]
+    pub timeout_secs: Option<u64>,
+}
+
+pub struct CargoFixer {
+    ignore: IgnoreList,
+}
+
+impl CargoFixer {
+    pub fn new(ignore: IgnoreList) -> Self {
+        Self { ignore }
+    }
+
+    /// Runs `cargo fix` (rustc's machine-applicable suggestions, not clippy's) and returns
+    /// a unified diff of every `.rs` file it changed.
+    /// Without `allow_dirty`, cargo refuses to touch a repository with uncommitted changes.
+    pub async fn fix(
+        &self,
+        project_path: PathBuf,
+        edition_migration: bool,
+        allow_dirty: bool,
+        cancel: &CancellationToken,
+        timeout: Duration,
+    ) -> Result<String> {
+        if !project_path.join("Cargo.toml").exists() {
+            return Err(ToolError::NotFound(format!("No Cargo.toml found at '{}'.", project_path.display())).into());
+        }
+
+        let before = self.snapshot(&project_path);
+
+        let mut cmd = cargo_command();
+        cmd.current_dir(&project_path)
+            .arg("fix")
+            .arg("--color").arg("never");
+        if edition_migration {
+            cmd.arg("--edition");
+        }
+        if allow_dirty {
+            cmd.arg("--allow-dirty").arg("--allow-staged").arg("--allow-no-vcs");
+        }
+
+        let output = match output_with_timeout(cmd, cancel, timeout)
+            .instrument(tracing::info_span!("cargo_fix", path = %project_path.display(), edition_migration))
+            .await
+            .context("Failed to execute 'cargo fix'")?
+        {
+            ProcessOutcome::Completed(output) => output,
+            // cargo may have rewritten some files already
+            ProcessOutcome::Cancelled => return Ok("cargo fix cancelled by client. Files may be partially fixed; check 'git_operations(diff)'.".to_string()),
+            ProcessOutcome::TimedOut(limit) => return Err(ToolError::Timeout(format!("'cargo fix' timed out after {}s", limit.as_secs())).into()),
+        };
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if !output.status.success() {
+            if stderr.contains("--allow-dirty") || stderr.contains("--allow-no-vcs") {
+                return Err(ToolError::InvalidInput(format!(
+                    "cargo fix refused to run: the working tree has uncommitted changes (or no VCS).\n\
+                    Action: Commit first with 'git_operations(commit)', or retry with 'allow_dirty=true'.\n\n{}",
+                    capped_output(&output.stderr)
+                )).into());
+            }
+            return Err(ToolError::CommandFailed(format!("cargo fix failed:\n{}", capped_output(&output.stderr))).into());
+        }
+
+        let after = self.snapshot(&project_path);
+        let mut diff = String::new();
+        let mut changed = 0;
+        for (file, new) in &after {
+            let old = before.get(file).map(String::as_str).unwrap_or("");
+            if old == new {
+                continue;
+            }
+            changed += 1;
+            let name = file.strip_prefix(&project_path).unwrap_or(file).display().to_string();
+            diff.push_str(&TextDiff::from_lines(old, new).unified_diff().context_radius(3).header(&name, &name).to_string());
+        }
+
+        if changed == 0 {
+            return Ok(format!("cargo fix finished. No changes were needed.\n{}", capped_output(&output.stderr)));
+        }
+        Ok(format!(
+            "cargo fix changed {} file(s):\n\n{}",
+            changed,
+            capped_output(diff.as_bytes())
+        ))
+    }
+
+    /// Contents of all `.rs` files of the project, keyed by path
+    fn snapshot(&self, root: &Path) -> BTreeMap<PathBuf, String> {
+        rust_files(root, &self.ignore)
+            .into_iter()
+            .filter_map(|file| std::fs::read_to_string(&file).ok().map(|content| (file, content)))
+            .collect()
+    }
+}
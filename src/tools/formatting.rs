@@ -1,9 +1,14 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tokio::process::Command;
 use anyhow::{Context, Result};
 use rmcp::schemars::JsonSchema;
 use serde::Deserialize;
+use tokio_util::sync::CancellationToken;
+use tracing::Instrument;
+use crate::utils::process::{capped_output, cargo_command, output_with_timeout, ProcessOutcome};
 use rmcp::schemars;
+use crate::utils::{FeatureFlags, ToolError};
 
 #[derive(Deserialize, JsonSchema)]
 pub struct PolishRequest {
@@ -11,6 +16,18 @@ pub struct PolishRequest {
     pub path: String,
     #[schemars(description = "Mode: 'fmt' (Format code) or 'clippy' (Check for lint errors). Note: Clippy does NOT auto-fix.")]
     pub mode: String,
+    #[schemars(description = "Optional (fmt only): Format just this file (absolute or relative to the project root) instead of the whole project")]
+    pub file: Option<String>,
+    #[schemars(description = "Optional (clippy only): Also lint tests, examples and benches (--all-targets). Slower, default false.")]
+    pub all_targets: Option<bool>,
+    #[schemars(description = "Optional (clippy only): Activate all features (--all-features). Default false.")]
+    pub all_features: Option<bool>,
+    #[schemars(description = "Optional (clippy only): Disable the default features (--no-default-features). Default false.")]
+    pub no_default_features: Option<bool>,
+    #[schemars(description = "Optional (clippy only): Features to activate, e.g. [\"serde\", \"tokio/full\"] (--features)")]
+    pub features: Option<Vec<String>>,
+    #[schemars(description = "Optional: Timeout in seconds (default: server's --command-timeout-secs)")]
+    pub timeout_secs: Option<u64>,
 }
 
 pub struct CodePolisher;
@@ -18,36 +35,138 @@ pub struct CodePolisher;
 impl CodePolisher {
     pub fn new() -> Self { Self }
 
-    pub async fn run_fmt(&self, path: PathBuf) -> Result<String> {
+    pub async fn run_fmt(&self, path: PathBuf, cancel: &CancellationToken, timeout: Duration) -> Result<String> {
         // cargo fmt is safe: it only affects style (indentation, spacing)
-        let output = Command::new("cargo")
-            .current_dir(&path)
-            .arg("fmt")
-            .output()
-            .await?;
+        let mut cmd = cargo_command();
+        // No `--edition` here: cargo fmt already passes the manifest's edition to rustfmt,
+        // and a second one makes rustfmt fail with "given more than once"
+        cmd.current_dir(&path)
+            .arg("fmt");
+
+        let output = match output_with_timeout(cmd, cancel, timeout)
+            .instrument(tracing::info_span!("cargo_fmt", path = %path.display()))
+            .await?
+        {
+            ProcessOutcome::Completed(output) => output,
+            ProcessOutcome::Cancelled => return Ok("Formatting cancelled by client.".to_string()),
+            ProcessOutcome::TimedOut(limit) => return Err(ToolError::Timeout(format!("'cargo fmt' timed out after {}s", limit.as_secs())).into()),
+        };
 
         if output.status.success() {
             Ok("Code formatted successfully.".to_string())
         } else {
-            Ok(format!("❌ Format failed: {}", String::from_utf8_lossy(&output.stderr)))
+            Ok(format!("❌ Format failed:\n{}", failure_output(&output)))
         }
     }
 
-    pub async fn run_clippy(&self, path: PathBuf) -> Result<String> {
+    /// Runs `cargo fmt -- --check` and returns the files that would be reformatted
+    /// (empty if everything is formatted). Nothing is changed on disk.
+    pub async fn unformatted_files(&self, path: PathBuf, cancel: &CancellationToken, timeout: Duration) -> Result<Vec<String>> {
+        let mut cmd = cargo_command();
+        cmd.current_dir(&path)
+            .arg("fmt")
+            .arg("--")
+            .arg("--check");
+
+        let output = match output_with_timeout(cmd, cancel, timeout)
+            .instrument(tracing::info_span!("cargo_fmt_check", path = %path.display()))
+            .await?
+        {
+            ProcessOutcome::Completed(output) => output,
+            ProcessOutcome::Cancelled => return Err(ToolError::CommandFailed("Format check cancelled by client.".to_string()).into()),
+            ProcessOutcome::TimedOut(limit) => return Err(ToolError::Timeout(format!("'cargo fmt --check' timed out after {}s", limit.as_secs())).into()),
+        };
+
+        if output.status.success() {
+            return Ok(Vec::new());
+        }
+
+        // The diff starts every hunk with "Diff in /abs/path/file.rs at line 12:" (older: "...file.rs:12:")
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let mut files: Vec<String> = stdout.lines()
+            .filter_map(|line| line.strip_prefix("Diff in "))
+            .filter_map(|rest| rest.find(".rs").map(|end| &rest[..end + 3]))
+            .map(|file| Path::new(file).strip_prefix(&path).map(|p| p.display().to_string()).unwrap_or_else(|_| file.to_string()))
+            .collect();
+        files.sort();
+        files.dedup();
+
+        // A failure without a diff means rustfmt itself failed (e.g. a syntax error)
+        if files.is_empty() {
+            return Err(ToolError::CommandFailed(format!("'cargo fmt --check' failed:\n{}", failure_output(&output))).into());
+        }
+        Ok(files)
+    }
+
+    /// Formats a single file with `rustfmt`, leaving the rest of the project untouched.
+    /// Keeps the diff scoped to what was actually edited.
+    pub async fn fmt_file(&self, path: PathBuf, file: &str, cancel: &CancellationToken, timeout: Duration) -> Result<String> {
+        let file_path = if Path::new(file).is_absolute() {
+            PathBuf::from(file)
+        } else {
+            path.join(file)
+        };
+
+        let before = std::fs::read_to_string(&file_path)
+            .with_context(|| format!("Could not read file '{}'", file_path.display()))?;
+
+        // Running from the project root lets rustfmt pick up rustfmt.toml
+        let mut cmd = Command::new("rustfmt");
+        cmd.current_dir(&path)
+            .arg(&file_path);
+        if let Some(edition) = project_edition(&path) {
+            cmd.arg("--edition").arg(edition);
+        }
+
+        let output = match output_with_timeout(cmd, cancel, timeout)
+            .instrument(tracing::info_span!("rustfmt", file = %file_path.display()))
+            .await?
+        {
+            ProcessOutcome::Completed(output) => output,
+            ProcessOutcome::Cancelled => return Ok("Formatting cancelled by client.".to_string()),
+            ProcessOutcome::TimedOut(limit) => return Err(ToolError::Timeout(format!("'rustfmt' timed out after {}s", limit.as_secs())).into()),
+        };
+
+        if !output.status.success() {
+            return Ok(format!("❌ Format failed:\n{}", failure_output(&output)));
+        }
+
+        let after = std::fs::read_to_string(&file_path).unwrap_or_default();
+        if before == after {
+            Ok(format!("{} is already formatted. No changes.", file))
+        } else {
+            Ok(format!("{} formatted successfully.", file))
+        }
+    }
+
+    /// Default is the fast pass (default targets and features); `all_targets` and `features`
+    /// widen it to test code and feature-gated code for a thorough review.
+    pub async fn run_clippy(&self, path: PathBuf, all_targets: bool, features: &FeatureFlags, cancel: &CancellationToken, timeout: Duration) -> Result<String> {
         // SAFETY: We do NOT use `--fix`. This is purely diagnostic.
         // We use `-D warnings` to treat warnings as errors so the AI takes them seriously.
-        let output = Command::new("cargo")
-            .current_dir(&path)
+        let mut cmd = cargo_command();
+        cmd.current_dir(&path)
             .arg("clippy")
             .arg("--no-deps") // Only check this project, not dependencies (speed)
-            .arg("--message-format=short")
-            .arg("--")
+            .arg("--message-format=short");
+        if all_targets {
+            cmd.arg("--all-targets");
+        }
+        features.apply(&mut cmd);
+        cmd.arg("--")
             .arg("-D")
-            .arg("warnings")
-            .output()
-            .await?;
+            .arg("warnings");
+
+        let output = match output_with_timeout(cmd, cancel, timeout)
+            .instrument(tracing::info_span!("cargo_clippy", path = %path.display()))
+            .await?
+        {
+            ProcessOutcome::Completed(output) => output,
+            ProcessOutcome::Cancelled => return Ok("Clippy cancelled by client.".to_string()),
+            ProcessOutcome::TimedOut(limit) => return Err(ToolError::Timeout(format!("'cargo clippy' timed out after {}s", limit.as_secs())).into()),
+        };
 
-        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stderr = capped_output(&output.stderr);
 
         if output.status.success() {
             Ok("Clippy is happy. No issues found.".to_string())
@@ -56,4 +175,53 @@ impl CodePolisher {
             Ok(format!("Clippy Suggestions:\n{}", stderr))
         }
     }
-}
\ No newline at end of file
+}
+
+/// Reads the edition from the project's Cargo.toml (`package.edition`, or
+/// `workspace.package.edition` when the package inherits it).
+/// Without it rustfmt falls back to edition 2015 when run on single files.
+fn project_edition(path: &Path) -> Option<String> {
+    let content = std::fs::read_to_string(path.join("Cargo.toml")).ok()?;
+    let manifest: toml::Value = toml::from_str(&content).ok()?;
+
+    manifest.get("package")
+        .and_then(|p| p.get("edition"))
+        .and_then(|e| e.as_str())
+        .or_else(|| manifest.get("workspace")
+            .and_then(|w| w.get("package"))
+            .and_then(|p| p.get("edition"))
+            .and_then(|e| e.as_str()))
+        .map(|e| e.to_string())
+}
+
+/// rustfmt reports parse errors on stderr; fall back to stdout if stderr is empty.
+fn failure_output(output: &std::process::Output) -> String {
+    let stderr = capped_output(&output.stderr).trim().to_string();
+    if stderr.is_empty() {
+        capped_output(&output.stdout).trim().to_string()
+    } else {
+        stderr
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_support::TempDir;
+
+    #[tokio::test]
+    async fn test_unformatted_files_with_edition() {
+        let project = TempDir::new("fmt_check");
+        std::fs::create_dir_all(project.join("src")).unwrap();
+        std::fs::write(project.join("Cargo.toml"), "[package]\nname = \"demo\"\nversion = \"0.1.0\"\nedition = \"2021\"\n").unwrap();
+        std::fs::write(project.join("src/lib.rs"), "mod tidy;\n\nfn  messy( ) {}\n").unwrap();
+        std::fs::write(project.join("src/tidy.rs"), "pub fn tidy() {}\n").unwrap();
+
+        let polisher = CodePolisher::new();
+        let check = || polisher.unformatted_files(project.path().to_path_buf(), &CancellationToken::new(), Duration::from_secs(60));
+        assert_eq!(check().await.unwrap(), vec!["src/lib.rs"]);
+
+        std::fs::write(project.join("src/lib.rs"), "mod tidy;\n\nfn messy() {}\n").unwrap();
+        assert!(check().await.unwrap().is_empty());
+    }
+}
@@ -1,18 +1,33 @@
 use std::path::PathBuf;
+use std::time::Duration;
 use tokio::process::Command;
+use tokio_util::sync::CancellationToken;
 use anyhow::{Context, Result};
+use regex::Regex;
 use rmcp::schemars::JsonSchema;
 use serde::Deserialize;
 use rmcp::schemars;
+use crate::utils::process::{output_with_timeout, ProcessOutcome};
+use crate::utils::ToolError;
 
 #[derive(Deserialize, JsonSchema)]
 pub struct GitRequest {
     #[schemars(description = "Project root path")]
     pub path: String,
-    #[schemars(description = "Operation: 'status', 'diff', 'commit', 'undo'")]
+    #[schemars(description = "Operation: 'status', 'diff', 'change_summary', 'commit', 'undo', 'reset_to'")]
     pub operation: String,
     #[schemars(description = "Commit message (required for 'commit')")]
     pub message: Option<String>,
+    #[schemars(description = "Commit hash returned by a previous 'commit' (required for 'reset_to')")]
+    pub hash: Option<String>,
+    #[schemars(description = "Optional: Author name for 'commit' (defaults to the repo/global git config)")]
+    pub author_name: Option<String>,
+    #[schemars(description = "Optional: Author email for 'commit' (defaults to the repo/global git config)")]
+    pub author_email: Option<String>,
+    #[schemars(description = "Optional: For 'commit', run 'cargo fmt -- --check' first and refuse to commit unformatted code (default false)")]
+    pub require_fmt: Option<bool>,
+    #[schemars(description = "Optional: Timeout in seconds for each git command (default: server's --command-timeout-secs)")]
+    pub timeout_secs: Option<u64>,
 }
 
 pub struct GitController;
@@ -21,46 +36,247 @@ impl GitController {
     pub fn new() -> Self { Self }
 
     /// Helper to run git commands
-    async fn run_git(&self, path: &PathBuf, args: &[&str]) -> Result<String> {
+    async fn run_git(&self, path: &PathBuf, args: &[&str], cancel: &CancellationToken, timeout: Duration) -> Result<String> {
         // 1. Check if it's a git repo
         if !path.join(".git").exists() {
             // Optional: Auto-init if missing? For now, just fail safely.
             return Ok("Not a git repository. Run 'git init' manually first.".to_string());
         }
 
-        let output = Command::new("git")
-            .current_dir(path)
-            .args(args)
-            .output()
-            .await
-            .context("Failed to execute git command")?;
+        let output = self.git_output(path, args, cancel, timeout).await?;
 
         if output.status.success() {
             Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
         } else {
             let err = String::from_utf8_lossy(&output.stderr).trim().to_string();
-            Ok(format!("Git Error: {}", err))
+            Err(ToolError::CommandFailed(format!("'git {}' failed: {}", args.first().unwrap_or(&""), err)).into())
+        }
+    }
+
+    /// Runs git and returns the raw output, so callers can inspect the exit status.
+    /// git is killed after `timeout` (e.g. waiting on a credential prompt or a lock) or when
+    /// the client cancels the request.
+    async fn git_output(&self, path: &PathBuf, args: &[&str], cancel: &CancellationToken, timeout: Duration) -> Result<std::process::Output> {
+        let mut cmd = Command::new("git");
+        cmd.current_dir(path)
+            .args(args);
+
+        match output_with_timeout(cmd, cancel, timeout)
+            .await
+            .context("Failed to execute git command")?
+        {
+            ProcessOutcome::Completed(output) => Ok(output),
+            ProcessOutcome::TimedOut(limit) => Err(ToolError::Timeout(format!(
+                "'git {}' timed out after {}s and was killed",
+                args.first().unwrap_or(&""),
+                limit.as_secs()
+            )).into()),
+            ProcessOutcome::Cancelled => Err(ToolError::CommandFailed(format!(
+                "'git {}' cancelled by client",
+                args.first().unwrap_or(&"")
+            )).into()),
         }
     }
 
-    pub async fn status(&self, path: PathBuf) -> Result<String> {
-        self.run_git(&path, &["status", "--short"]).await
+    pub async fn status(&self, path: PathBuf, cancel: &CancellationToken, timeout: Duration) -> Result<String> {
+        self.run_git(&path, &["status", "--short"], cancel, timeout).await
+    }
+
+    pub async fn diff(&self, path: PathBuf, cancel: &CancellationToken, timeout: Duration) -> Result<String> {
+        self.run_git(&path, &["diff"], cancel, timeout).await
     }
 
-    pub async fn diff(&self, path: PathBuf) -> Result<String> {
-        self.run_git(&path, &["diff"]).await
+    /// Object id of git's empty tree, used as diff base before the first commit
+    const EMPTY_TREE: &'static str = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
+
+    /// Structured overview of everything that differs from the last commit
+    /// (staged, unstaged and untracked): per-file line counts plus totals.
+    pub async fn change_summary(&self, path: PathBuf, cancel: &CancellationToken, timeout: Duration) -> Result<String> {
+        if !path.join(".git").exists() {
+            return Ok("Not a git repository. Run 'git init' manually first.".to_string());
+        }
+
+        let has_commits = self.git_output(&path, &["rev-parse", "--verify", "--quiet", "HEAD"], cancel, timeout)
+            .await?
+            .status
+            .success();
+        let base = if has_commits { "HEAD" } else { Self::EMPTY_TREE };
+
+        let status = self.git_output(&path, &["status", "--porcelain", "--no-renames"], cancel, timeout).await?;
+        if !status.status.success() {
+            return Err(ToolError::CommandFailed(format!("'git status' failed: {}", String::from_utf8_lossy(&status.stderr).trim())).into());
+        }
+        let numstat = self.git_output(&path, &["diff", "--numstat", "--no-renames", base], cancel, timeout).await?;
+
+        // path -> (insertions, deletions); binary files report "-"
+        let mut counts = std::collections::HashMap::new();
+        for line in String::from_utf8_lossy(&numstat.stdout).lines() {
+            let mut parts = line.splitn(3, '\t');
+            let (Some(ins), Some(del), Some(file)) = (parts.next(), parts.next(), parts.next()) else { continue };
+            counts.insert(file.to_string(), (ins.parse::<usize>().ok(), del.parse::<usize>().ok()));
+        }
+
+        let mut files = Vec::new();
+        let (mut insertions, mut deletions) = (0, 0);
+        for line in String::from_utf8_lossy(&status.stdout).lines() {
+            if line.len() < 4 { continue; }
+            let (code, file) = (&line[..2], line[3..].trim_matches('"'));
+
+            let state = match code {
+                "??" => "untracked",
+                c if c.contains('A') => "added",
+                c if c.contains('D') => "deleted",
+                _ => "modified",
+            };
+            let (ins, del) = counts.get(file).copied().unwrap_or((None, None));
+            insertions += ins.unwrap_or(0);
+            deletions += del.unwrap_or(0);
+
+            files.push(serde_json::json!({
+                "path": file,
+                "status": state,
+                "insertions": ins,
+                "deletions": del,
+            }));
+        }
+
+        let response = serde_json::json!({
+            "status": if files.is_empty() { "clean" } else { "dirty" },
+            "has_commits": has_commits,
+            "files_changed": files.len(),
+            "insertions": insertions,
+            "deletions": deletions,
+            "files": files,
+        });
+        Ok(serde_json::to_string_pretty(&response)?)
     }
 
-    pub async fn commit(&self, path: PathBuf, message: String) -> Result<String> {
+    /// Stages everything and commits. Returns JSON with the full and short commit hash
+    /// so the agent can later roll back to exactly this point with `reset_to`.
+    /// If `author` (name, email) is given, it overrides the git identity for this commit only.
+    pub async fn commit(&self, path: PathBuf, message: String, author: Option<(String, String)>, cancel: &CancellationToken, timeout: Duration) -> Result<String> {
+        if !path.join(".git").exists() {
+            return Ok("Not a git repository. Run 'git init' manually first.".to_string());
+        }
+
         // Stage all changes
-        self.run_git(&path, &["add", "."]).await?;
-        // Commit
-        self.run_git(&path, &["commit", "-m", &message]).await
+        self.run_git(&path, &["add", "."], cancel, timeout).await?;
+        self.commit_staged(&path, message, author, cancel, timeout).await
     }
 
-    pub async fn undo(&self, path: PathBuf) -> Result<String> {
+    /// Like `commit`, but stages only changes to tracked files plus `include`, so untracked
+    /// files (build output, local secrets) never end up in the commit.
+    pub async fn commit_tracked(&self, path: PathBuf, message: String, include: &[PathBuf], cancel: &CancellationToken, timeout: Duration) -> Result<String> {
+        if !path.join(".git").exists() {
+            return Ok("Not a git repository. Run 'git init' manually first.".to_string());
+        }
+
+        self.run_git(&path, &["add", "--update"], cancel, timeout).await?;
+        for file in include {
+            self.run_git(&path, &["add", "--", &file.to_string_lossy()], cancel, timeout).await?;
+        }
+        self.commit_staged(&path, message, None, cancel, timeout).await
+    }
+
+    /// Commits the index; shared tail of `commit` and `commit_tracked`.
+    async fn commit_staged(&self, path: &PathBuf, message: String, author: Option<(String, String)>, cancel: &CancellationToken, timeout: Duration) -> Result<String> {
+        // Commit (identity via `-c` so no config is written)
+        let mut args: Vec<String> = Vec::new();
+        if let Some((name, email)) = author {
+            args.push("-c".to_string());
+            args.push(format!("user.name={}", name));
+            args.push("-c".to_string());
+            args.push(format!("user.email={}", email));
+        }
+        args.extend(["commit".to_string(), "-m".to_string(), message]);
+
+        let arg_refs: Vec<&str> = args.iter().map(String::as_str).collect();
+        let output = self.git_output(path, &arg_refs, cancel, timeout).await?;
+        let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+        if !output.status.success() {
+            // git exits with 1 on a clean tree (or when only untracked files changed with
+            // `commit_tracked`); that's a status, not an error
+            if stdout.contains("nothing to commit") || stdout.contains("nothing added to commit") {
+                let response = serde_json::json!({
+                    "status": "nothing_to_commit",
+                    "message": stdout,
+                });
+                return Ok(serde_json::to_string_pretty(&response)?);
+            }
+
+            let err = String::from_utf8_lossy(&output.stderr).trim().to_string();
+
+            // Fresh machines / CI often have no git identity configured
+            if err.contains("Please tell me who you are") || err.contains("unable to auto-detect email address") {
+                return Err(ToolError::InvalidInput(format!(
+                    "No git identity configured on this machine.\n\
+                    Action: Retry 'commit' with 'author_name' and 'author_email' set.\n\nDetails: {}",
+                    err
+                )).into());
+            }
+
+            return Err(ToolError::CommandFailed(format!("'git commit' failed: {}", err)).into());
+        }
+
+        let hash = self.run_git(path, &["rev-parse", "HEAD"], cancel, timeout).await?;
+        let short_hash = self.run_git(path, &["rev-parse", "--short", "HEAD"], cancel, timeout).await?;
+
+        let response = serde_json::json!({
+            "status": "committed",
+            "hash": hash,
+            "short_hash": short_hash,
+            "output": stdout,
+        });
+        Ok(serde_json::to_string_pretty(&response)?)
+    }
+
+    pub async fn undo(&self, path: PathBuf, cancel: &CancellationToken, timeout: Duration) -> Result<String> {
         // Hard reset to HEAD (Dangerous but effective for "Undo")
         // Or just `checkout .` to discard local changes. `checkout .` is safer.
-        self.run_git(&path, &["checkout", "."]).await
+        self.run_git(&path, &["checkout", "."], cancel, timeout).await
     }
-}
\ No newline at end of file
+
+    /// Full hash of `HEAD`, or `None` if the repository has no commits yet.
+    pub async fn head(&self, path: &PathBuf, cancel: &CancellationToken, timeout: Duration) -> Result<Option<String>> {
+        let output = self.git_output(path, &["rev-parse", "--verify", "--quiet", "HEAD"], cancel, timeout).await?;
+        Ok(output.status.success().then(|| String::from_utf8_lossy(&output.stdout).trim().to_string()))
+    }
+
+    /// Rolls back to a commit created earlier (e.g. before a risky experiment).
+    /// Uses `git reset --hard`, so all changes after that commit are discarded.
+    pub async fn reset_to(&self, path: PathBuf, hash: &str, cancel: &CancellationToken, timeout: Duration) -> Result<String> {
+        // Validate input to prevent passing options/refs to git (must look like an object id)
+        let re = Regex::new(r"^[0-9a-fA-F]{7,40}$").unwrap();
+        if !re.is_match(hash) {
+            return Err(ToolError::InvalidInput(format!("Invalid commit hash '{}'. Expected 7-40 hex characters as returned by 'commit'.", hash)).into());
+        }
+
+        self.run_git(&path, &["reset", "--hard", hash], cancel, timeout).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_support::TempDir;
+
+    #[tokio::test]
+    async fn test_git_failure_is_an_error() {
+        let dir = TempDir::new("git_errors");
+        let git = GitController::new();
+        let cancel = CancellationToken::new();
+        let timeout = Duration::from_secs(30);
+        std::process::Command::new("git").current_dir(dir.path()).args(["init", "-q"]).status().unwrap();
+
+        // A clean status is a success with empty output
+        assert_eq!(git.status(dir.path().to_path_buf(), &cancel, timeout).await.unwrap(), "");
+
+        let err = git.reset_to(dir.path().to_path_buf(), "deadbeef", &cancel, timeout).await.unwrap_err();
+        assert!(matches!(err.downcast_ref::<ToolError>(), Some(ToolError::CommandFailed(_))));
+        assert!(err.to_string().starts_with("'git reset' failed:"));
+
+        let err = git.reset_to(dir.path().to_path_buf(), "--hard", &cancel, timeout).await.unwrap_err();
+        assert!(matches!(err.downcast_ref::<ToolError>(), Some(ToolError::InvalidInput(_))));
+    }
+}
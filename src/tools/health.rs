@@ -0,0 +1,169 @@
+// src/tools/health.rs
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+use rmcp::schemars;
+use rmcp::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Availability of a single external binary the tools depend on.
+#[derive(Debug, Serialize, Clone)]
+pub struct BinaryStatus {
+    pub name: String,
+    pub available: bool,
+    pub version: Option<String>,
+    pub used_by: String,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct BuildEnvironmentRequest {
+    #[schemars(description = "Optional: Project root. rustc is queried there (honoring rust-toolchain.toml) and .cargo/config.toml files above it are listed.")]
+    pub path: Option<String>,
+}
+
+/// What cargo will build with: host triple, compiler and the environment overrides in effect.
+#[derive(Debug, Serialize)]
+pub struct BuildEnvironment {
+    /// `host:` line of `rustc -vV`, e.g. `x86_64-unknown-linux-gnu`
+    pub host: Option<String>,
+    /// `release:` line of `rustc -vV`, e.g. `1.82.0`
+    pub rustc_release: Option<String>,
+    pub cargo_path: Option<String>,
+    /// `$RUSTC` if set, otherwise the rustc found on PATH
+    pub rustc_path: Option<String>,
+    /// Build-relevant variables that are set (`CARGO_BUILD_TARGET`, `RUSTFLAGS`, ...)
+    pub env: BTreeMap<String, String>,
+    /// `.cargo/config.toml` files that apply to the project; they can set `build.target` and rustflags too
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub config_files: Vec<String>,
+}
+
+/// Environment variables that change what or how cargo compiles
+const BUILD_ENV_VARS: [&str; 11] = [
+    "CARGO_BUILD_TARGET",
+    "RUSTFLAGS",
+    "CARGO_ENCODED_RUSTFLAGS",
+    "CARGO_BUILD_RUSTFLAGS",
+    "RUSTDOCFLAGS",
+    "RUSTC",
+    "RUSTC_WRAPPER",
+    "CARGO_TARGET_DIR",
+    "CARGO_HOME",
+    "RUSTUP_TOOLCHAIN",
+    "CARGO_NET_OFFLINE",
+];
+
+pub struct HealthChecker;
+
+impl HealthChecker {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Probes every external binary via `--version`.
+    pub async fn probe_all(&self) -> Vec<BinaryStatus> {
+        // (display name, program, args, tools that need it)
+        let probes: [(&str, &str, &[&str], &str); 7] = [
+            ("cargo", "cargo", &["--version"], "check_code, run_tests, add_dependency, polish_code"),
+            ("rustc", "rustc", &["--version"], "explain_error"),
+            ("git", "git", &["--version"], "git_operations"),
+            ("rustfmt", "rustfmt", &["--version"], "polish_code (fmt)"),
+            ("cargo-clippy", "cargo", &["clippy", "--version"], "polish_code (clippy)"),
+            ("cargo-expand", "cargo", &["expand", "--version"], "optional"),
+            ("cargo-audit", "cargo", &["audit", "--version"], "optional"),
+        ];
+
+        let mut results = Vec::new();
+        for (name, program, args, used_by) in probes {
+            let version = Self::probe(program, args).await;
+            results.push(BinaryStatus {
+                name: name.to_string(),
+                available: version.is_some(),
+                version,
+                used_by: used_by.to_string(),
+            });
+        }
+
+        results
+    }
+
+    /// Reads the host triple from `rustc -vV` and collects the build overrides. Read-only;
+    /// runs a single rustc process.
+    pub async fn build_environment(&self, cargo_bin: Option<&Path>, project: Option<&Path>) -> BuildEnvironment {
+        let rustc = std::env::var_os("RUSTC")
+            .map(PathBuf::from)
+            .or_else(|| Self::find_on_path("rustc"));
+
+        let mut cmd = Command::new(rustc.as_deref().unwrap_or(Path::new("rustc")));
+        cmd.arg("-vV");
+        if let Some(dir) = project.filter(|dir| dir.is_dir()) {
+            cmd.current_dir(dir);
+        }
+        let verbose = match cmd.output().await {
+            Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout).to_string(),
+            _ => String::new(),
+        };
+        let field = |name: &str| verbose.lines()
+            .find_map(|line| line.strip_prefix(name))
+            .map(|value| value.trim().to_string());
+
+        let env = BUILD_ENV_VARS.iter()
+            .filter_map(|name| Some((name.to_string(), std::env::var(name).ok()?)))
+            .collect();
+
+        // Cargo merges .cargo/config.toml (or legacy .cargo/config) from the project up to the root
+        let config_files = project.map(|dir| dir.ancestors()
+            .flat_map(|dir| ["config.toml", "config"].map(|name| dir.join(".cargo").join(name)))
+            .filter(|file| file.is_file())
+            .map(|file| file.display().to_string())
+            .collect())
+            .unwrap_or_default();
+
+        BuildEnvironment {
+            host: field("host:"),
+            rustc_release: field("release:"),
+            cargo_path: cargo_bin.map(|p| p.display().to_string()),
+            rustc_path: rustc.map(|p| p.display().to_string()),
+            env,
+            config_files,
+        }
+    }
+
+    fn find_on_path(program: &str) -> Option<PathBuf> {
+        let exe = format!("{}{}", program, std::env::consts::EXE_SUFFIX);
+        let path = std::env::var_os("PATH")?;
+        std::env::split_paths(&path)
+            .map(|dir| dir.join(&exe))
+            .find(|bin| bin.is_file())
+    }
+
+    /// Formats the probe results in the same style as `RustPaths::status_report`.
+    pub fn format_report(binaries: &[BinaryStatus]) -> String {
+        let mut report = String::new();
+
+        report.push_str("Tool Binaries:\n");
+        for bin in binaries {
+            report.push_str(&format!("  {}: {}\n",
+                                     bin.name,
+                                     bin.version.clone().unwrap_or_else(|| "NOT FOUND".to_string())
+            ));
+        }
+
+        report
+    }
+
+    async fn probe(program: &str, args: &[&str]) -> Option<String> {
+        let output = Command::new(program)
+            .args(args)
+            .output()
+            .await
+            .ok()?;
+
+        if !output.status.success() {
+            return None;
+        }
+
+        let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        Some(version.lines().next().unwrap_or_default().to_string())
+    }
+}
@@ -0,0 +1,678 @@
+// src/tools/manifest.rs
+use std::path::{Path, PathBuf};
+use anyhow::{Context, Result};
+use rmcp::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use toml_edit::{DocumentMut, InlineTable, Item, Table};
+use rmcp::schemars;
+use crate::utils::ToolError;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ReadManifestRequest {
+    #[schemars(description = "Absolute path to the project root (or directly to a Cargo.toml)")]
+    pub path: String,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct SetManifestFieldRequest {
+    #[schemars(description = "Absolute path to the project root (or directly to a Cargo.toml)")]
+    pub path: String,
+    #[schemars(description = "Dotted path of the field, e.g. 'package.edition' or 'dependencies.serde.features'")]
+    pub field: String,
+    #[schemars(description = "New value as a TOML literal, e.g. '\"2021\"', 'true' or '[\"derive\"]'")]
+    pub value: String,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct DependencyConfigRequest {
+    #[schemars(description = "Absolute path to the project root (or directly to a Cargo.toml)")]
+    pub path: String,
+    #[schemars(description = "Dependency name as used in Cargo.toml (e.g. 'serde'); renamed dependencies also match their 'package'")]
+    pub name: String,
+}
+
+/// How one manifest section declares a dependency.
+#[derive(Debug, Serialize)]
+pub struct DependencyConfig {
+    /// e.g. `dependencies` or `target.'cfg(unix)'.dependencies`
+    pub section: String,
+    pub name: String,
+    /// Real crate name if the dependency is renamed (`package = "..."`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub package: Option<String>,
+    /// Version requirement; `None` for path/git-only dependencies
+    pub version: Option<String>,
+    pub features: Vec<String>,
+    pub default_features: bool,
+    pub optional: bool,
+    /// `workspace = true`: version and features come (partly) from `[workspace.dependencies]`
+    pub workspace: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub git: Option<String>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ResolvedVersionsRequest {
+    #[schemars(description = "Absolute path to the project root (a workspace member finds the workspace's Cargo.lock)")]
+    pub path: String,
+    #[schemars(description = "Optional: Only this package (e.g. 'syn'); all its locked versions are returned")]
+    pub name: Option<String>,
+}
+
+/// One `[[package]]` entry of Cargo.lock
+#[derive(Debug, Serialize)]
+pub struct ResolvedPackage {
+    pub name: String,
+    pub version: String,
+    /// `registry+...` or `git+...`; `None` for path/workspace packages
+    pub source: Option<String>,
+    pub dependencies: Vec<String>,
+    /// Locked packages that depend on this one (only filled when filtering by name)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub dependents: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ResolvedVersions {
+    /// Path of the Cargo.lock that was read, `None` if there is none yet
+    pub lockfile: Option<String>,
+    pub packages: Vec<ResolvedPackage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ProjectKindRequest {
+    #[schemars(description = "Absolute path to the project root (or directly to a Cargo.toml)")]
+    pub path: String,
+}
+
+/// What a manifest builds, following cargo's target auto-discovery.
+#[derive(Debug, Serialize)]
+pub struct ProjectKind {
+    /// `binary`, `library`, `both`, `workspace` (virtual root without `[package]`) or `no_targets`
+    pub kind: String,
+    pub name: Option<String>,
+    /// Resolved from `[workspace.package]` when the package inherits it
+    pub edition: Option<String>,
+    pub has_lib: bool,
+    pub bins: Vec<String>,
+    pub examples: Vec<String>,
+    /// Also declares `[workspace]` (a virtual root or a root package)
+    pub is_workspace_root: bool,
+    /// `workspace.members` as written (globs are not expanded, see 'list_workspace_members')
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub workspace_members: Vec<String>,
+}
+
+// Minimal view of Cargo.lock
+#[derive(Deserialize)]
+struct Lockfile {
+    #[serde(default, rename = "package")]
+    packages: Vec<LockedPackage>,
+}
+
+#[derive(Deserialize)]
+struct LockedPackage {
+    name: String,
+    version: String,
+    source: Option<String>,
+    #[serde(default)]
+    dependencies: Vec<String>,
+}
+
+pub struct ManifestManager;
+
+impl ManifestManager {
+    /// Dependency tables that are normalized (short `"1.0"` form becomes `{ "version": "1.0" }`)
+    const DEPENDENCY_SECTIONS: [&'static str; 3] = ["dependencies", "dev-dependencies", "build-dependencies"];
+
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Accepts a project directory or the manifest itself.
+    pub fn manifest_path(path: PathBuf) -> PathBuf {
+        if path.is_dir() { path.join("Cargo.toml") } else { path }
+    }
+
+    /// Parses Cargo.toml into a normalized JSON object. Sections that are missing
+    /// (e.g. `package` in a virtual workspace root) are `null` / empty, never absent.
+    pub async fn read(&self, path: PathBuf) -> Result<Value> {
+        let manifest_path = Self::manifest_path(path);
+        let content = tokio::fs::read_to_string(&manifest_path)
+            .await
+            .with_context(|| format!("No Cargo.toml found at '{}'", manifest_path.display()))?;
+
+        let manifest: toml::Value = toml::from_str(&content)
+            .with_context(|| format!("'{}' is not valid TOML", manifest_path.display()))?;
+
+        let section = |name: &str| -> Value {
+            manifest.get(name)
+                .and_then(|v| serde_json::to_value(v).ok())
+                .unwrap_or(Value::Null)
+        };
+
+        let mut result = Map::new();
+        result.insert("manifest_path".to_string(), json!(manifest_path.display().to_string()));
+        result.insert("package".to_string(), section("package"));
+        for name in Self::DEPENDENCY_SECTIONS {
+            result.insert(name.to_string(), Self::normalize_dependencies(manifest.get(name)));
+        }
+        result.insert("features".to_string(), match section("features") {
+            Value::Null => json!({}),
+            features => features,
+        });
+        result.insert("workspace".to_string(), section("workspace"));
+        result.insert("is_virtual_workspace".to_string(), json!(manifest.get("package").is_none() && manifest.get("workspace").is_some()));
+
+        Ok(Value::Object(result))
+    }
+
+    /// Returns every declaration of dependency `name` (regular, dev, build and
+    /// target-specific sections), in both the `"1.0"` short form and the table form.
+    pub async fn dependency_config(&self, path: PathBuf, name: &str) -> Result<Vec<DependencyConfig>> {
+        let manifest_path = Self::manifest_path(path);
+        let content = tokio::fs::read_to_string(&manifest_path)
+            .await
+            .with_context(|| format!("No Cargo.toml found at '{}'", manifest_path.display()))?;
+
+        let manifest: toml::Value = toml::from_str(&content)
+            .with_context(|| format!("'{}' is not valid TOML", manifest_path.display()))?;
+
+        let mut sections: Vec<(String, &toml::Value)> = Self::DEPENDENCY_SECTIONS.iter()
+            .filter_map(|section| manifest.get(*section).map(|deps| (section.to_string(), deps)))
+            .collect();
+        if let Some(toml::Value::Table(targets)) = manifest.get("target") {
+            for (cfg, table) in targets {
+                for section in Self::DEPENDENCY_SECTIONS {
+                    if let Some(deps) = table.get(section) {
+                        sections.push((format!("target.'{}'.{}", cfg, section), deps));
+                    }
+                }
+            }
+        }
+
+        let mut found = Vec::new();
+        for (section, deps) in sections {
+            let Some(deps) = deps.as_table() else { continue };
+            for (key, value) in deps {
+                let package = value.get("package").and_then(|v| v.as_str());
+                if key != name && package != Some(name) {
+                    continue;
+                }
+
+                let text = |field: &str| value.get(field).and_then(|v| v.as_str()).map(str::to_string);
+                let flag = |field: &str| value.get(field).and_then(|v| v.as_bool());
+
+                found.push(DependencyConfig {
+                    section: section.clone(),
+                    name: key.clone(),
+                    package: package.map(str::to_string),
+                    version: value.as_str().map(str::to_string).or_else(|| text("version")),
+                    features: value.get("features")
+                        .and_then(|f| f.as_array())
+                        .map(|f| f.iter().filter_map(|v| v.as_str().map(str::to_string)).collect())
+                        .unwrap_or_default(),
+                    // `default_features` is the deprecated spelling
+                    default_features: flag("default-features").or_else(|| flag("default_features")).unwrap_or(true),
+                    optional: flag("optional").unwrap_or(false),
+                    workspace: flag("workspace").unwrap_or(false),
+                    path: text("path"),
+                    git: text("git"),
+                });
+            }
+        }
+
+        if found.is_empty() {
+            return Err(ToolError::NotFound(format!(
+                "'{}' is not a dependency in {}",
+                name,
+                manifest_path.display()
+            )).into());
+        }
+        Ok(found)
+    }
+
+    /// Reads the resolved versions from the Cargo.lock of the project (or of the enclosing
+    /// workspace). A missing lockfile is not an error: libraries often don't commit one.
+    pub async fn resolved_versions(&self, path: PathBuf, name: Option<&str>) -> Result<ResolvedVersions> {
+        let start = if path.is_dir() { path.clone() } else { path.parent().map(PathBuf::from).unwrap_or_default() };
+        if !start.is_dir() {
+            return Err(ToolError::NotFound(format!("The directory '{}' was not found.", path.display())).into());
+        }
+
+        let Some(lockfile) = start.ancestors().map(|dir| dir.join("Cargo.lock")).find(|lock| lock.is_file()) else {
+            return Ok(ResolvedVersions {
+                lockfile: None,
+                packages: Vec::new(),
+                message: Some(
+                    "No Cargo.lock found (libraries often don't commit one). Any cargo build, e.g. 'check_code', creates it.".to_string()
+                ),
+            });
+        };
+
+        let content = tokio::fs::read_to_string(&lockfile)
+            .await
+            .with_context(|| format!("Failed to read '{}'", lockfile.display()))?;
+        let parsed: Lockfile = toml::from_str(&content)
+            .with_context(|| format!("'{}' is not a valid Cargo.lock", lockfile.display()))?;
+
+        // Cargo treats `-` and `_` in package names as equivalent
+        let normalize = |s: &str| s.replace('-', "_");
+        let wanted = name.map(normalize);
+
+        let mut packages: Vec<ResolvedPackage> = parsed.packages.iter()
+            .filter(|p| wanted.as_ref().is_none_or(|w| normalize(&p.name) == *w))
+            .map(|p| ResolvedPackage {
+                name: p.name.clone(),
+                version: p.version.clone(),
+                source: p.source.clone(),
+                dependencies: p.dependencies.clone(),
+                dependents: Vec::new(),
+            })
+            .collect();
+
+        if wanted.is_some() {
+            // A dependency is listed as "name", "name version" or "name version (source)"
+            for package in &mut packages {
+                package.dependents = parsed.packages.iter()
+                    .filter(|p| p.dependencies.iter().any(|dep| {
+                        let mut parts = dep.split_whitespace();
+                        parts.next() == Some(package.name.as_str())
+                            && parts.next().is_none_or(|version| version == package.version)
+                    }))
+                    .map(|p| format!("{} {}", p.name, p.version))
+                    .collect();
+            }
+        }
+
+        let message = match (name, packages.is_empty()) {
+            (Some(name), true) => Some(format!("'{}' is not in {}", name, lockfile.display())),
+            _ => None,
+        };
+
+        Ok(ResolvedVersions {
+            lockfile: Some(lockfile.display().to_string()),
+            packages,
+            message,
+        })
+    }
+
+    /// Classifies the project from its Cargo.toml plus the files cargo discovers on its own
+    /// (`src/lib.rs`, `src/main.rs`, `src/bin/*`, `examples/*`), honoring `autobins`/`autoexamples`.
+    pub async fn project_kind(&self, path: PathBuf) -> Result<ProjectKind> {
+        let manifest_path = Self::manifest_path(path);
+        let content = tokio::fs::read_to_string(&manifest_path)
+            .await
+            .with_context(|| format!("No Cargo.toml found at '{}'", manifest_path.display()))?;
+
+        let manifest: toml::Value = toml::from_str(&content)
+            .with_context(|| format!("'{}' is not valid TOML", manifest_path.display()))?;
+        let root = manifest_path.parent().map(Path::to_path_buf).unwrap_or_default();
+
+        let workspace = manifest.get("workspace");
+        let workspace_members = workspace
+            .and_then(|w| w.get("members"))
+            .and_then(|m| m.as_array())
+            .map(|members| members.iter().filter_map(|m| m.as_str()).map(String::from).collect())
+            .unwrap_or_default();
+
+        let Some(package) = manifest.get("package") else {
+            return Ok(ProjectKind {
+                kind: if workspace.is_some() { "workspace" } else { "no_targets" }.to_string(),
+                name: None,
+                edition: None,
+                has_lib: false,
+                bins: Vec::new(),
+                examples: Vec::new(),
+                is_workspace_root: workspace.is_some(),
+                workspace_members,
+            });
+        };
+
+        let name = package.get("name").and_then(|n| n.as_str()).map(String::from);
+        let edition = match package.get("edition") {
+            Some(toml::Value::String(edition)) => Some(edition.clone()),
+            // `edition.workspace = true`
+            Some(_) => Self::workspace_edition(&root),
+            None => None,
+        };
+        let auto = |key: &str| package.get(key).and_then(|v| v.as_bool()).unwrap_or(true);
+
+        let has_lib = manifest.get("lib").is_some() || root.join("src/lib.rs").is_file();
+
+        let mut bins = Self::declared_targets(&manifest, "bin");
+        if auto("autobins") {
+            if let Some(name) = name.as_ref().filter(|_| root.join("src/main.rs").is_file()) {
+                bins.push(name.clone());
+            }
+            bins.extend(Self::discovered_targets(&root.join("src/bin")));
+        }
+        bins.sort();
+        bins.dedup();
+
+        let mut examples = Self::declared_targets(&manifest, "example");
+        if auto("autoexamples") {
+            examples.extend(Self::discovered_targets(&root.join("examples")));
+        }
+        examples.sort();
+        examples.dedup();
+
+        let kind = match (has_lib, bins.is_empty()) {
+            (true, false) => "both",
+            (true, true) => "library",
+            (false, false) => "binary",
+            (false, true) => "no_targets",
+        };
+
+        Ok(ProjectKind {
+            kind: kind.to_string(),
+            name,
+            edition,
+            has_lib,
+            bins,
+            examples,
+            is_workspace_root: workspace.is_some(),
+            workspace_members,
+        })
+    }
+
+    /// Names of the `[[bin]]` / `[[example]]` entries
+    fn declared_targets(manifest: &toml::Value, section: &str) -> Vec<String> {
+        manifest.get(section)
+            .and_then(|v| v.as_array())
+            .map(|targets| targets.iter()
+                .filter_map(|t| t.get("name").and_then(|n| n.as_str()))
+                .map(String::from)
+                .collect())
+            .unwrap_or_default()
+    }
+
+    /// `dir/<name>.rs` and `dir/<name>/main.rs`, the layouts cargo picks up automatically
+    fn discovered_targets(dir: &Path) -> Vec<String> {
+        let Ok(entries) = std::fs::read_dir(dir) else { return Vec::new() };
+
+        entries.filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter_map(|path| {
+                if path.is_dir() {
+                    if !path.join("main.rs").is_file() {
+                        return None;
+                    }
+                    Some(path.file_name()?.to_string_lossy().to_string())
+                } else if path.extension().is_some_and(|ext| ext == "rs") {
+                    Some(path.file_stem()?.to_string_lossy().to_string())
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// `workspace.package.edition` of the nearest enclosing manifest that declares it
+    fn workspace_edition(root: &Path) -> Option<String> {
+        root.ancestors().find_map(|dir| {
+            let content = std::fs::read_to_string(dir.join("Cargo.toml")).ok()?;
+            let manifest: toml::Value = toml::from_str(&content).ok()?;
+            manifest.get("workspace")?
+                .get("package")?
+                .get("edition")?
+                .as_str()
+                .map(String::from)
+        })
+    }
+
+    /// Sets `field` (dotted path) to the TOML literal `value`, keeping the formatting
+    /// and comments of the rest of the file. Missing tables are created; a dependency in
+    /// short form (`serde = "1.0"`) is expanded to an inline table when a sub-key is set.
+    pub async fn set_field(&self, path: PathBuf, field: &str, value: &str) -> Result<String> {
+        let manifest_path = Self::manifest_path(path);
+        let content = tokio::fs::read_to_string(&manifest_path)
+            .await
+            .with_context(|| format!("No Cargo.toml found at '{}'", manifest_path.display()))?;
+
+        let mut doc: DocumentMut = content.parse()
+            .with_context(|| format!("'{}' is not valid TOML", manifest_path.display()))?;
+
+        let segments: Vec<&str> = field.split('.').map(str::trim).collect();
+        if segments.iter().any(|s| s.is_empty()) {
+            return Err(ToolError::InvalidInput(format!("Invalid field path '{}'. Use dotted keys like 'package.edition'.", field)).into());
+        }
+
+        let new_value: toml_edit::Value = value.trim().parse()
+            .map_err(|e| ToolError::InvalidInput(format!("'{}' is not a valid TOML value (strings need quotes): {}", value, e)))?;
+        Self::validate(&segments, &new_value)?;
+
+        let previous = Self::set_path(doc.as_item_mut(), &segments, new_value)?;
+
+        tokio::fs::write(&manifest_path, doc.to_string())
+            .await
+            .context("Failed to write Cargo.toml")?;
+
+        Ok(match previous {
+            Some(old) => format!("Updated '{}' in {}: {} -> {}", field, manifest_path.display(), old.trim(), value.trim()),
+            None => format!("Set '{}' in {} to {}", field, manifest_path.display(), value.trim()),
+        })
+    }
+
+    /// Walks/creates the tables along `segments` and sets the last key. Returns the old value.
+    fn set_path(item: &mut Item, segments: &[&str], value: toml_edit::Value) -> Result<Option<String>> {
+        let table = item.as_table_like_mut()
+            .ok_or_else(|| ToolError::InvalidInput("Cannot set a key below a non-table value".to_string()))?;
+
+        let (key, rest) = segments.split_first().expect("non-empty path");
+        if rest.is_empty() {
+            let previous = table.get(key).map(|old| old.to_string());
+            table.insert(key, Item::Value(value));
+            return Ok(previous);
+        }
+
+        if !table.contains_key(key) {
+            table.insert(key, Item::Table(Table::new()));
+        }
+        let child = table.get_mut(key).expect("inserted above");
+
+        // `serde = "1.0"` -> `serde = { version = "1.0" }`, so `features` etc. can be added
+        if let Some(version) = child.as_str().map(str::to_string) {
+            let mut expanded = InlineTable::new();
+            expanded.insert("version", version.into());
+            *child = Item::Value(toml_edit::Value::InlineTable(expanded));
+        }
+
+        Self::set_path(child, rest, value)
+            .with_context(|| format!("'{}' is not a table", key))
+    }
+
+    /// Type checks for the fields agents edit most often.
+    fn validate(segments: &[&str], value: &toml_edit::Value) -> Result<()> {
+        let last = *segments.last().unwrap_or(&"");
+        let in_dependencies = segments.iter().any(|s| Self::DEPENDENCY_SECTIONS.contains(s));
+
+        let ok = match (segments, last) {
+            (["package", "edition"], _) => value.as_str()
+                .is_some_and(|e| ["2015", "2018", "2021", "2024"].contains(&e)),
+            (["package", "version" | "name" | "description" | "license" | "rust-version"], _) => value.is_str(),
+            (_, "features") => value.as_array()
+                .is_some_and(|a| a.iter().all(|v| v.is_str())),
+            (_, "optional" | "default-features" | "workspace") if in_dependencies => value.is_bool(),
+            (_, "version" | "path" | "git" | "branch" | "tag" | "rev") if in_dependencies => value.is_str(),
+            _ => true,
+        };
+
+        if !ok {
+            return Err(ToolError::InvalidInput(format!("Value '{}' has the wrong type for '{}'", value.to_string().trim(), segments.join("."))).into());
+        }
+        Ok(())
+    }
+
+    fn normalize_dependencies(table: Option<&toml::Value>) -> Value {
+        let mut deps = Map::new();
+        let Some(toml::Value::Table(table)) = table else { return Value::Object(deps) };
+
+        for (name, value) in table {
+            let normalized = match value {
+                toml::Value::String(version) => json!({ "version": version }),
+                other => serde_json::to_value(other).unwrap_or(Value::Null),
+            };
+            deps.insert(name.clone(), normalized);
+        }
+
+        Value::Object(deps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_support::TempDir;
+
+    #[tokio::test]
+    async fn test_dependency_config() {
+        let dir = TempDir::new("dependency_config");
+        std::fs::write(dir.join("Cargo.toml"), r#"
+            [package]
+            name = "demo"
+            version = "0.1.0"
+
+            [dependencies]
+            serde = "1.0"
+            json = { package = "serde_json", version = "1", optional = true }
+            tokio = { workspace = true, features = ["rt"], default-features = false }
+
+            [dev-dependencies]
+            serde = { version = "1.0.200", features = ["derive"] }
+
+            [target.'cfg(unix)'.dependencies]
+            serde = { path = "../serde", default_features = false }
+        "#).unwrap();
+
+        let manager = ManifestManager::new();
+        let serde = manager.dependency_config(dir.path().to_path_buf(), "serde").await.unwrap();
+        let sections: Vec<&str> = serde.iter().map(|d| d.section.as_str()).collect();
+        assert_eq!(sections, vec!["dependencies", "dev-dependencies", "target.'cfg(unix)'.dependencies"]);
+        assert_eq!(serde[0].version.as_deref(), Some("1.0"));
+        assert!(serde[0].default_features && serde[0].features.is_empty());
+        assert_eq!(serde[1].features, vec!["derive"]);
+        // Deprecated `default_features` spelling
+        assert!(!serde[2].default_features);
+        assert_eq!((serde[2].version.as_deref(), serde[2].path.as_deref()), (None, Some("../serde")));
+
+        // Renamed dependencies match by key and by package
+        let renamed = manager.dependency_config(dir.join("Cargo.toml"), "serde_json").await.unwrap();
+        assert_eq!((renamed[0].name.as_str(), renamed[0].package.as_deref()), ("json", Some("serde_json")));
+        assert!(renamed[0].optional);
+
+        let tokio = manager.dependency_config(dir.path().to_path_buf(), "tokio").await.unwrap();
+        assert!(tokio[0].workspace && !tokio[0].default_features);
+        assert_eq!(tokio[0].version, None);
+
+        assert!(manager.dependency_config(dir.path().to_path_buf(), "rand").await.is_err());
+    }
+
+    const LOCKFILE: &str = r#"
+version = 3
+
+[[package]]
+name = "demo"
+version = "0.1.0"
+dependencies = ["serde_derive", "syn 2.0.60"]
+
+[[package]]
+name = "serde_derive"
+version = "1.0.200"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+dependencies = ["syn 1.0.109 (registry+https://github.com/rust-lang/crates.io-index)"]
+
+[[package]]
+name = "syn"
+version = "1.0.109"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "syn"
+version = "2.0.60"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+"#;
+
+    #[tokio::test]
+    async fn test_resolved_versions() {
+        let dir = TempDir::new("resolved_versions");
+        std::fs::create_dir_all(dir.join("member/src")).unwrap();
+        let manager = ManifestManager::new();
+
+        let missing = manager.resolved_versions(dir.join("member"), None).await.unwrap();
+        assert!(missing.lockfile.is_none() && missing.packages.is_empty());
+        assert!(missing.message.unwrap().starts_with("No Cargo.lock found"));
+
+        // A member finds the workspace's lockfile further up
+        std::fs::write(dir.join("Cargo.lock"), LOCKFILE).unwrap();
+        let all = manager.resolved_versions(dir.join("member/src"), None).await.unwrap();
+        assert_eq!(all.lockfile, Some(dir.join("Cargo.lock").display().to_string()));
+        assert_eq!(all.packages.len(), 4);
+        assert!(all.packages.iter().all(|p| p.dependents.is_empty()));
+        assert_eq!(all.packages[0].source, None);
+
+        // Both locked versions, each with the packages depending on exactly that version
+        let syn = manager.resolved_versions(dir.path().to_path_buf(), Some("syn")).await.unwrap();
+        let found: Vec<(&str, Vec<String>)> = syn.packages.iter().map(|p| (p.version.as_str(), p.dependents.clone())).collect();
+        assert_eq!(found, vec![
+            ("1.0.109", vec!["serde_derive 1.0.200".to_string()]),
+            ("2.0.60", vec!["demo 0.1.0".to_string()]),
+        ]);
+
+        // `-` and `_` are interchangeable; a bare dependency name matches any version
+        let derive = manager.resolved_versions(dir.path().to_path_buf(), Some("serde-derive")).await.unwrap();
+        assert_eq!(derive.packages[0].name, "serde_derive");
+        assert_eq!(derive.packages[0].dependents, vec!["demo 0.1.0"]);
+
+        let unknown = manager.resolved_versions(dir.path().to_path_buf(), Some("rand")).await.unwrap();
+        assert!(unknown.packages.is_empty());
+        assert!(unknown.message.unwrap().starts_with("'rand' is not in"));
+    }
+
+    #[tokio::test]
+    async fn test_project_kind() {
+        let dir = TempDir::new("project_kind");
+        let write = |relative: &str, content: &str| {
+            let file = dir.join(relative);
+            std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+            std::fs::write(file, content).unwrap();
+        };
+        write("Cargo.toml", "[workspace]\nmembers = [\"app\", \"tool\"]\n\n[workspace.package]\nedition = \"2021\"\n");
+        write("app/Cargo.toml", "[package]\nname = \"app\"\nversion = \"0.1.0\"\nedition.workspace = true\n");
+        for file in ["app/src/lib.rs", "app/src/main.rs", "app/src/bin/extra.rs", "app/src/bin/multi/main.rs", "app/examples/demo.rs"] {
+            write(file, "");
+        }
+        write("app/src/bin/notes.txt", "");
+        write("tool/Cargo.toml", "[package]\nname = \"tool\"\nedition = \"2018\"\nautobins = false\n\n[[bin]]\nname = \"declared\"\npath = \"src/cli.rs\"\n");
+        write("tool/src/main.rs", "");
+        write("tool/src/bin/hidden.rs", "");
+
+        let manager = ManifestManager::new();
+
+        let root = manager.project_kind(dir.path().to_path_buf()).await.unwrap();
+        assert_eq!(root.kind, "workspace");
+        assert!(root.is_workspace_root && root.name.is_none());
+        assert_eq!(root.workspace_members, vec!["app", "tool"]);
+
+        // lib + main.rs + src/bin/* (file and directory layouts), edition inherited from the workspace
+        let app = manager.project_kind(dir.join("app/Cargo.toml")).await.unwrap();
+        assert_eq!(app.kind, "both");
+        assert!(app.has_lib && !app.is_workspace_root);
+        assert_eq!(app.bins, vec!["app", "extra", "multi"]);
+        assert_eq!(app.examples, vec!["demo"]);
+        assert_eq!(app.edition.as_deref(), Some("2021"));
+
+        // autobins = false: only the declared [[bin]] counts
+        let tool = manager.project_kind(dir.join("tool")).await.unwrap();
+        assert_eq!(tool.kind, "binary");
+        assert_eq!(tool.bins, vec!["declared"]);
+        assert_eq!(tool.edition.as_deref(), Some("2018"));
+
+        assert!(manager.project_kind(dir.join("missing")).await.is_err());
+    }
+}
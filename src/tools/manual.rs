@@ -25,7 +25,7 @@ You are operating in the following project structure. Adhere to this organizatio
 *   **Actions:**
     1.  `get_project_structure(path=".")` -> Verify file locations.
     2.  `read_file_with_lines(path="Cargo.toml")` -> Check enabled features (e.g., does `serde` have `derive`?).
-    3.  `git_operations(operation="status")` -> **CRITICAL:** Ensure working directory is clean.
+    3.  `git_operations(operation="status")` -> **CRITICAL:** Ensure working directory is clean. (`operation="change_summary"` shows which files changed and by how many lines).
 
 ### Phase 2: 🛡️ Safeguard (The Time Machine)
 *   **Protocol:** Never edit code without a save point.
@@ -33,6 +33,7 @@ You are operating in the following project structure. Adhere to this organizatio
     *   If clean: `git_operations(operation="commit", message="Save: Before implementing [Task]")`.
     *   If dirty: `git_operations(operation="commit", message="WIP: Saving state")`.
     *   *Emergency:* If you break the build and can't fix it in 2 tries: `git_operations(operation="undo")`.
+    *   *Experiments:* Note the hash returned by `commit`. If a risky change fails its tests: `git_operations(operation="reset_to", hash="<hash>")`.
 
 ### Phase 3: 🏗️ Construction (Hands)
 *   **Scenario A: Creating a NEW Tool**
@@ -45,11 +46,14 @@ You are operating in the following project structure. Adhere to this organizatio
         *   Add field to struct: `my_tool: Arc<MyTool>,`
         *   Init in `new()`: `my_tool: Arc::new(MyTool::new()),`
         *   Add `#[tool]` function: Delegate to `self.my_tool.run(...)`.
+        *   Verify: `verify_tool_registered(tool_name="my_tool")` names any missing step.
 
 *   **Scenario B: Modifying Logic**
     *   **Surgeon Rule:** **NEVER** overwrite whole files.
     *   Use `patch_file`.
     *   *Tip:* Copy the `original_snippet` **exactly** (including whitespace) from `read_file_with_lines`.
+    *   Rewriting a whole function? Prefer `replace_function(name="Type::method", new_source="{ ... }")`.
+    *   Single risky patch? `run_checked_edit(path=".", patch={...})` commits, patches, formats, checks and reverts on errors in one call.
 
 *   **Scenario C: Dependencies**
     *   Check availability: `get_crate_info`.
@@ -64,6 +68,7 @@ You are operating in the following project structure. Adhere to this organizatio
 ### Phase 5: ✅ Verification (QA)
 *   **Compile:** `check_code(path=".")`.
 *   **Test:** `run_tests(path=".")`.
+*   **Examples:** `list_examples` -> `run_example(name="...")` to verify that examples still run.
     *   *Mandate:* If creating a new tool, ensure it has at least a basic test or verification step.
 *   **Failure Loop:**
     *   If error -> `explain_error` -> Read file at line -> Patch -> Retry.
@@ -80,6 +85,7 @@ You are operating in the following project structure. Adhere to this organizatio
 | | `add_dependency` | "I need `serde`." |
 | **Brain** | `get_mcp_template` | "Give me the `#[tool]` boilerplate." |
 | | `explain_error` | "What does E0308 mean?" |
+| | `list_tools` | "Which tools exist for X?" |
 | **Safety** | `git_operations` | "Save point" or "Undo". |
 | | `polish_code` | "Make it pretty." |
 
@@ -12,6 +12,14 @@ pub mod git;
 pub mod formatting;
 pub mod manual;
 pub mod analyzer;
+pub mod health;
+pub mod bench;
+pub mod symbols;
+pub mod manifest;
+pub mod examples;
+pub mod fix;
+pub mod cargo_command;
+pub mod workflow;
 
 pub use search_docs::RustDocsSearcher;
 pub use crate_info::CrateInfoProvider;
@@ -27,3 +35,11 @@ pub use git::GitController;
 pub use formatting::CodePolisher;
 pub use manual::SYSTEM_INSTRUCTIONS;
 pub use analyzer::SymbolAnalyzer;
+pub use health::HealthChecker;
+pub use bench::BenchRunner;
+pub use symbols::SymbolIndex;
+pub use manifest::ManifestManager;
+pub use examples::ExampleRunner;
+pub use fix::CargoFixer;
+pub use cargo_command::CargoCommandRunner;
+pub use workflow::CheckedEditor;
\ No newline at end of file
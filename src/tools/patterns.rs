@@ -6,7 +6,7 @@ use rmcp::schemars;
 
 #[derive(Deserialize, JsonSchema)]
 pub struct GetPatternRequest {
-    #[schemars(description = "The topic to get a template for: 'tool', 'prompt', 'resource', or 'server_setup'")]
+    #[schemars(description = "The topic to get a template for: 'tool', 'prompt', 'resource', or 'server_setup'. Server setup variants: 'server_setup:stdio', 'server_setup:with_prompts', 'server_setup:sse'")]
     pub topic: String,
 }
 
@@ -17,7 +17,14 @@ impl McpPatterns {
         Self
     }
 
+    /// Variants of the `server_setup` topic, requested as `server_setup:<variant>`
+    pub const SERVER_SETUP_VARIANTS: [&'static str; 3] = ["stdio", "with_prompts", "sse"];
+
     pub fn get_template(&self, topic: &str) -> Result<String> {
+        if let Some(variant) = topic.strip_prefix("server_setup:") {
+            return self.get_server_setup_variant(variant);
+        }
+
         let template = match topic {
             "tool" => r#"
 // PATTERN: Defining an MCP Tool
@@ -164,7 +171,197 @@ async fn main() -> anyhow::Result<()> {
     Ok(())
 }
 "#,
-            _ => "Topic not found. Available: 'tool', 'prompt', 'resource', 'server_setup'",
+            _ => "Topic not found. Available: 'tool', 'prompt', 'resource', 'server_setup' (variants: 'server_setup:stdio', 'server_setup:with_prompts', 'server_setup:sse')",
+        };
+        Ok(template.to_string())
+    }
+
+    /// Complete, compilable files for a fresh rmcp server crate: (relative path, content).
+    /// Same layout as this crate: logic in `src/tools/`, routing in `main.rs`.
+    pub fn starter_files(&self) -> [(&'static str, &'static str); 3] {
+        [
+            ("src/main.rs", r#"mod tools;
+
+use rmcp::{
+    handler::server::tool::ToolRouter,
+    handler::server::wrapper::Parameters,
+    model::*,
+    schemars::{self, JsonSchema},
+    tool, tool_handler, tool_router,
+    ErrorData as McpError, ServerHandler, ServiceExt,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use tokio::io::{stdin, stdout};
+
+use crate::tools::Greeter;
+
+#[derive(Deserialize, JsonSchema)]
+struct GreetRequest {
+    #[schemars(description = "Name of the person to greet")]
+    name: String,
+}
+
+#[derive(Clone)]
+struct MyServer {
+    greeter: Arc<Greeter>,
+    tool_router: ToolRouter<Self>,
+}
+
+#[tool_router]
+impl MyServer {
+    fn new() -> Self {
+        Self {
+            greeter: Arc::new(Greeter::new()),
+            tool_router: Self::tool_router(),
+        }
+    }
+
+    #[tool(description = "Returns a greeting. Replace this with your first real tool.")]
+    async fn greet(&self, params: Parameters<GreetRequest>) -> Result<CallToolResult, McpError> {
+        let text = self.greeter.greet(&params.0.name);
+        Ok(CallToolResult::success(vec![Content::text(text)]))
+    }
+}
+
+#[tool_handler]
+impl ServerHandler for MyServer {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            server_info: Implementation::from_build_env(),
+            instructions: Some("My MCP Server".to_string()),
+            ..Default::default()
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    // stdout is reserved for the MCP protocol, log to stderr only
+    eprintln!("MCP Server starting...");
+    MyServer::new().serve((stdin(), stdout())).await?.waiting().await?;
+    Ok(())
+}
+"#),
+            ("src/tools/mod.rs", r#"pub mod greeter;
+
+pub use greeter::Greeter;
+"#),
+            ("src/tools/greeter.rs", r#"pub struct Greeter;
+
+impl Greeter {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn greet(&self, name: &str) -> String {
+        format!("Hello, {}!", name)
+    }
+}
+"#),
+        ]
+    }
+
+    fn get_server_setup_variant(&self, variant: &str) -> Result<String> {
+        let template = match variant {
+            "stdio" => return self.get_template("server_setup"),
+            "with_prompts" => r#"
+// PATTERN: Server with Tools AND Prompts (like mcp-rust-builder itself)
+// Both routers live in the server struct; the handler macros are stacked.
+
+use rmcp::{
+    handler::server::tool::ToolRouter,
+    handler::server::router::prompt::PromptRouter,
+    model::*,
+    tool_router, tool_handler, prompt_router, prompt_handler,
+    ServerHandler, ServiceExt,
+};
+use tokio::io::{stdin, stdout};
+
+#[derive(Clone)]
+struct MyServer {
+    tool_router: ToolRouter<Self>,
+    prompt_router: PromptRouter<Self>,
+}
+
+#[prompt_router]
+impl MyServer {
+    // #[prompt(...)] functions go here
+}
+
+#[tool_router]
+impl MyServer {
+    fn new() -> Self {
+        Self {
+            tool_router: Self::tool_router(),
+            prompt_router: Self::prompt_router(),
+        }
+    }
+
+    // #[tool(...)] functions go here
+}
+
+#[tool_handler]
+#[prompt_handler]
+impl ServerHandler for MyServer {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            capabilities: ServerCapabilities::builder()
+                .enable_tools()
+                .enable_prompts() // <--- Required, otherwise clients never ask for prompts
+                .build(),
+            server_info: Implementation::from_build_env(),
+            instructions: Some("My Server".to_string()),
+            ..Default::default()
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let server = MyServer::new();
+    eprintln!("MCP Server starting...");
+    server.serve((stdin(), stdout())).await?.waiting().await?;
+    Ok(())
+}
+"#,
+            "sse" => r#"
+// PATTERN: Server over HTTP/SSE instead of Stdio
+// Dependency: rmcp with features = ["transport-sse-server"]
+// Each connecting client gets its own server instance from the factory closure.
+
+use rmcp::transport::sse_server::SseServer;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let bind_address = "127.0.0.1:8000";
+
+    // Serve and create a fresh MyServer per session
+    let ct = SseServer::serve(bind_address.parse()?)
+        .await?
+        .with_service(MyServer::new);
+
+    eprintln!("MCP SSE Server listening on http://{}/sse", bind_address);
+
+    // No `waiting()` here: shut down on Ctrl+C via the cancellation token
+    tokio::signal::ctrl_c().await?;
+    ct.cancel();
+
+    Ok(())
+}
+"#,
+            _ => {
+                return Ok(format!(
+                    "Unknown server_setup variant '{}'. Available: {}",
+                    variant,
+                    Self::SERVER_SETUP_VARIANTS
+                        .iter()
+                        .map(|v| format!("'server_setup:{}'", v))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ));
+            }
         };
         Ok(template.to_string())
     }
@@ -1,30 +1,199 @@
 use walkdir::WalkDir;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use anyhow::Result;
+use regex::Regex;
 use rmcp::schemars::JsonSchema;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use rmcp::schemars;
+use crate::utils::{IgnoreList, ToolError};
+use crate::utils::text::{format_age, format_utc};
+use crate::utils::walk::rust_files;
+use crate::utils::metadata::cargo_metadata;
+use tokio_util::sync::CancellationToken;
+
+/// Markers for `list_todos`, compiled once
+static TODO_COMMENT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"//.*\b(TODO|FIXME)\b").unwrap());
+static PANIC_MACRO: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b(todo|unimplemented)!\s*[(\[{]").unwrap());
 
 #[derive(Deserialize, JsonSchema)]
 pub struct StructureRequest {
     #[schemars(description = "Absolute path to the project root")]
     pub path: String,
+    #[schemars(description = "Optional: Only list files with these extensions, e.g. [\"rs\", \"toml\"]. Directories are always shown.")]
+    pub extensions: Option<Vec<String>>,
+    #[schemars(description = "Optional: Workspace member (package name or directory, see 'list_workspace_members') whose directory is shown instead of the root")]
+    pub member: Option<String>,
+    #[schemars(description = "Optional: Number of tree entries to skip (use the 'next_offset' of the previous page)")]
+    pub offset: Option<usize>,
+    #[schemars(description = "Optional: Max. entries to return (default and upper bound: 500)")]
+    pub limit: Option<usize>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct GrepRequest {
+    #[schemars(description = "Absolute path to the project root (or a single file)")]
+    pub path: String,
+    #[schemars(description = "Text to search for (literal unless 'regex' is true)")]
+    pub pattern: String,
+    #[schemars(description = "Optional: Treat 'pattern' as a regular expression (default false)")]
+    pub regex: Option<bool>,
+    #[schemars(description = "Optional: Only return match counts per file and a total instead of the matching lines (default false). Use it to gauge how widespread a pattern is.")]
+    pub count_only: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GrepMatch {
+    pub file: String,
+    pub line: usize,
+    pub text: String,
 }
 
-pub struct ProjectManager;
+#[derive(Debug, Serialize)]
+pub struct FileMatchCount {
+    pub file: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum GrepOutput {
+    Matches { matches: Vec<GrepMatch>, total: usize, truncated: bool },
+    Counts { files: Vec<FileMatchCount>, total: usize },
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ListTodosRequest {
+    #[schemars(description = "Absolute path to the project root (or a single .rs file)")]
+    pub path: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TodoMarker {
+    pub file: String,
+    pub line: usize,
+    /// `TODO`, `FIXME`, `todo!` or `unimplemented!`
+    pub kind: String,
+    pub text: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TodoList {
+    pub todos: Vec<TodoMarker>,
+    pub total: usize,
+    pub truncated: bool,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct RecentlyModifiedRequest {
+    #[schemars(description = "Absolute path to the project root")]
+    pub path: String,
+    #[schemars(description = "Optional: Number of files to return (default 20, max 200)")]
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecentFile {
+    pub file: String,
+    /// Modification time, `YYYY-MM-DD HH:MM:SS UTC`
+    pub modified: String,
+    pub modified_ago: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecentFiles {
+    pub files: Vec<RecentFile>,
+    /// Number of .rs files in the project
+    pub total: usize,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ListWorkspaceMembersRequest {
+    #[schemars(description = "Absolute path to the workspace root (or any directory inside it)")]
+    pub path: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WorkspaceMember {
+    pub name: String,
+    pub version: String,
+    pub manifest_path: String,
+    /// Member directory relative to the workspace root ("" for a root package)
+    pub dir: String,
+}
+
+pub struct ProjectManager {
+    ignore: IgnoreList,
+}
 
 impl ProjectManager {
-    pub fn new() -> Self {
-        Self
+    pub fn new(ignore: IgnoreList) -> Self {
+        Self { ignore }
+    }
+
+    /// Lists the workspace members (or the single package) via `cargo metadata`.
+    pub async fn workspace_members(&self, root: &Path, cancel: &CancellationToken, timeout: Duration) -> Result<Vec<WorkspaceMember>> {
+        if !root.is_dir() {
+            return Err(ToolError::NotFound(format!("The directory '{}' was not found.", root.display())).into());
+        }
+
+        let metadata = cargo_metadata(root, cancel, timeout).await?;
+
+        let mut members: Vec<WorkspaceMember> = metadata.packages.into_iter()
+            .map(|package| {
+                let dir = package.manifest_path.parent()
+                    .and_then(|d| d.strip_prefix(&metadata.workspace_root).ok())
+                    .map(|d| d.display().to_string())
+                    .unwrap_or_default();
+                WorkspaceMember {
+                    name: package.name,
+                    version: package.version,
+                    manifest_path: package.manifest_path.display().to_string(),
+                    dir,
+                }
+            })
+            .collect();
+        members.sort_by(|a, b| a.dir.cmp(&b.dir).then_with(|| a.name.cmp(&b.name)));
+
+        Ok(members)
+    }
+
+    /// Directory of workspace member `member` (package name or member directory);
+    /// without a member, `root` itself.
+    pub async fn resolve_member(&self, root: PathBuf, member: Option<&str>, cancel: &CancellationToken, timeout: Duration) -> Result<PathBuf> {
+        let Some(member) = member else { return Ok(root) };
+        let member = member.trim().trim_end_matches('/');
+
+        let members = self.workspace_members(&root, cancel, timeout).await?;
+        let found = members.iter()
+            .find(|m| m.name == member)
+            .or_else(|| members.iter().find(|m| m.dir == member));
+
+        match found {
+            Some(m) => Ok(Path::new(&m.manifest_path).parent().map(Path::to_path_buf).unwrap_or(root)),
+            None => Err(ToolError::NotFound(format!(
+                "'{}' is not a workspace member. Members: {}",
+                member,
+                members.iter().map(|m| m.name.as_str()).collect::<Vec<_>>().join(", ")
+            )).into()),
+        }
     }
 
-    pub fn get_structure(&self, root_path: PathBuf) -> Result<String> {
-        let mut structure = String::new();
+    /// Max. tree entries per `get_structure` call; larger trees are paginated.
+    pub const MAX_STRUCTURE_ENTRIES: usize = 500;
 
-        // Common folders to ignore to keep the context window small
-        let ignore_dirs = vec!["target", ".git", "node_modules", ".idea", ".vscode"];
+    /// Renders the tree below `root_path`. With `extensions`, only matching files
+    /// are listed (a leading dot is ignored, comparison is case-insensitive).
+    pub fn get_structure(&self, root_path: PathBuf, extensions: Option<&[String]>, offset: usize, limit: Option<usize>) -> Result<String> {
+        let extensions: Option<Vec<String>> = extensions.map(|exts| {
+            exts.iter().map(|e| e.trim_start_matches('.').to_lowercase()).collect()
+        });
 
-        for entry in WalkDir::new(&root_path).max_depth(5).sort_by_file_name() {
+        let mut entries = Vec::new();
+
+        // Symlinks are listed but never descended into, so link cycles can't blow up the walk
+        for entry in WalkDir::new(&root_path).max_depth(5).follow_links(false).sort_by_file_name() {
             let entry = match entry {
                 Ok(e) => e,
                 Err(_) => continue,
@@ -45,32 +214,243 @@ impl ProjectManager {
             let file_name = entry.file_name().to_string_lossy();
 
             // Check for ignored directories
-            if ignore_dirs.contains(&file_name.as_ref()) {
+            // Ignored folders keep the context window small
+            if self.ignore.is_ignored(&file_name) {
                 if entry.file_type().is_dir() {
                     // Add the folder but indicate it's skipped
-                    structure.push_str(&format!("{}|-- {}/ (skipped)\n", "    ".repeat(depth - 1), file_name));
+                    entries.push(format!("{}|-- {}/ (skipped)\n", "    ".repeat(depth - 1), file_name));
                     continue;
                 }
             }
 
-            // Simple check: if any parent is in ignore_dirs, skip printing
-            let path_str = relative_path.to_string_lossy();
-            if ignore_dirs.iter().any(|d| path_str.contains(d)) {
+            // Skip anything below an ignored folder
+            if relative_path.components().any(|c| self.ignore.is_ignored(&c.as_os_str().to_string_lossy())) {
                 continue;
             }
 
             let prefix = "    ".repeat(depth - 1);
             if entry.file_type().is_dir() {
-                structure.push_str(&format!("{}|-- {}/\n", prefix, file_name));
+                entries.push(format!("{}|-- {}/\n", prefix, file_name));
             } else {
-                structure.push_str(&format!("{}|-- {}\n", prefix, file_name));
+                if let Some(exts) = &extensions {
+                    let ext = path.extension().map(|e| e.to_string_lossy().to_lowercase());
+                    if !ext.is_some_and(|ext| exts.contains(&ext)) {
+                        continue;
+                    }
+                }
+                entries.push(format!("{}|-- {}\n", prefix, file_name));
             }
         }
 
-        if structure.is_empty() {
-            Ok("Directory is empty or path is invalid.".to_string())
+        if entries.is_empty() {
+            return Ok("Directory is empty or path is invalid.".to_string());
+        }
+
+        let total = entries.len();
+        if offset >= total {
+            return Err(ToolError::InvalidInput(format!("'offset' {} is past the end ({} entries).", offset, total)).into());
+        }
+        let limit = limit.unwrap_or(Self::MAX_STRUCTURE_ENTRIES).clamp(1, Self::MAX_STRUCTURE_ENTRIES);
+        let end = offset.saturating_add(limit).min(total);
+
+        let mut structure = entries[offset..end].concat();
+        if end < total {
+            structure.push_str(&format!(
+                "... {} more entries (showing {}-{} of {}). next_offset: {}\n",
+                total - end, offset + 1, end, total, end
+            ));
+        }
+        Ok(structure)
+    }
+
+    /// Max. matching lines returned by `grep`; counts are always complete.
+    pub const MAX_GREP_MATCHES: usize = 300;
+
+    /// Searches all text files below `root` line by line (ignored folders and symlinks are skipped).
+    /// With `count_only`, only per-file counts and the total are returned.
+    pub fn grep(&self, root: &Path, pattern: &str, regex: bool, count_only: bool) -> Result<GrepOutput> {
+        if !root.exists() {
+            return Err(ToolError::NotFound(format!("The path '{}' was not found.", root.display())).into());
+        }
+        if pattern.is_empty() {
+            return Err(ToolError::InvalidInput("'pattern' must not be empty.".to_string()).into());
+        }
+
+        let matcher = if regex {
+            Some(Regex::new(pattern).map_err(|e| ToolError::InvalidInput(format!("Invalid regex: {}", e)))?)
+        } else {
+            None
+        };
+        let is_match = |line: &str| match &matcher {
+            Some(re) => re.is_match(line),
+            None => line.contains(pattern),
+        };
+
+        let mut matches = Vec::new();
+        let mut counts = Vec::new();
+        let mut total = 0;
+
+        let walker = WalkDir::new(root).follow_links(false).sort_by_file_name().into_iter()
+            .filter_entry(|e| e.depth() == 0 || !self.ignore.is_ignored(&e.file_name().to_string_lossy()));
+
+        for entry in walker.flatten().filter(|e| e.file_type().is_file()) {
+            // Binary or non-UTF-8 files are skipped
+            let Ok(content) = std::fs::read_to_string(entry.path()) else { continue };
+            let relative = entry.path().strip_prefix(root).unwrap_or(entry.path());
+            let file = if relative.as_os_str().is_empty() { entry.path() } else { relative }.display().to_string();
+
+            let mut count = 0;
+            for (i, line) in content.lines().enumerate() {
+                if !is_match(line) {
+                    continue;
+                }
+                count += 1;
+                if !count_only && matches.len() < Self::MAX_GREP_MATCHES {
+                    matches.push(GrepMatch { file: file.clone(), line: i + 1, text: line.trim_end().to_string() });
+                }
+            }
+
+            if count > 0 {
+                total += count;
+                counts.push(FileMatchCount { file, count });
+            }
+        }
+
+        Ok(if count_only {
+            counts.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.file.cmp(&b.file)));
+            GrepOutput::Counts { files: counts, total }
         } else {
-            Ok(structure)
+            let truncated = total > matches.len();
+            GrepOutput::Matches { matches, total, truncated }
+        })
+    }
+
+    /// Collects `// TODO`, `// FIXME`, `todo!()` and `unimplemented!()` from all .rs files below `root`.
+    /// The macros are included because they panic at runtime.
+    pub fn list_todos(&self, root: &Path) -> Result<TodoList> {
+        if !root.exists() {
+            return Err(ToolError::NotFound(format!("The path '{}' was not found.", root.display())).into());
+        }
+
+        let mut todos = Vec::new();
+        let mut total = 0;
+
+        for path in rust_files(root, &self.ignore) {
+            let Ok(content) = std::fs::read_to_string(&path) else { continue };
+            let relative = path.strip_prefix(root).unwrap_or(&path);
+            let file = if relative.as_os_str().is_empty() { path.as_path() } else { relative }.display().to_string();
+
+            for (i, line) in content.lines().enumerate() {
+                let kind = if let Some(c) = TODO_COMMENT.captures(line) {
+                    c[1].to_string()
+                } else if let Some(c) = PANIC_MACRO.captures(line) {
+                    format!("{}!", &c[1])
+                } else {
+                    continue;
+                };
+
+                total += 1;
+                if todos.len() < Self::MAX_GREP_MATCHES {
+                    todos.push(TodoMarker { file: file.clone(), line: i + 1, kind, text: line.trim().to_string() });
+                }
+            }
+        }
+
+        let truncated = total > todos.len();
+        Ok(TodoList { todos, total, truncated })
+    }
+
+    pub const DEFAULT_RECENT_FILES: usize = 20;
+    pub const MAX_RECENT_FILES: usize = 200;
+
+    /// The project's .rs files, most recently modified first.
+    pub fn recently_modified(&self, root: &Path, limit: Option<usize>) -> Result<RecentFiles> {
+        if !root.is_dir() {
+            return Err(ToolError::NotFound(format!("The directory '{}' was not found.", root.display())).into());
+        }
+
+        let mut files: Vec<(PathBuf, SystemTime)> = rust_files(root, &self.ignore)
+            .into_iter()
+            .filter_map(|path| {
+                let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+                Some((path, modified))
+            })
+            .collect();
+        let total = files.len();
+        files.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        files.truncate(limit.unwrap_or(Self::DEFAULT_RECENT_FILES).clamp(1, Self::MAX_RECENT_FILES));
+
+        let now = SystemTime::now();
+        let files = files.into_iter()
+            .map(|(path, modified)| {
+                let since_epoch = modified.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+                // Files from the future (clock skew) count as just modified
+                let age = now.duration_since(modified).unwrap_or_default().as_secs();
+                RecentFile {
+                    file: path.strip_prefix(root).unwrap_or(&path).display().to_string(),
+                    modified: format_utc(since_epoch),
+                    modified_ago: format_age(age),
+                }
+            })
+            .collect();
+
+        Ok(RecentFiles { files, total })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_support::TempDir;
+
+    #[test]
+    fn test_list_todos() {
+        let root = TempDir::new("list_todos");
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::create_dir_all(root.join("target/debug")).unwrap();
+        std::fs::write(root.join("src/lib.rs"), [
+            "// TODO: split this module",
+            "fn a() { todo!() }",
+            "fn b() -> u32 { unimplemented!(\"later\") }",
+            "fn todos() -> &'static str { \"TODO outside a comment\" }",
+            "/* TODO in a block comment */",
+            "    // FIXME(parser): handle errors",
+        ].join("\n")).unwrap();
+        std::fs::write(root.join("target/debug/gen.rs"), "// TODO: generated\n").unwrap();
+
+        let list = ProjectManager::new(IgnoreList::new(None)).list_todos(root.path()).unwrap();
+        let found: Vec<(&str, usize, &str)> = list.todos.iter().map(|t| (t.file.as_str(), t.line, t.kind.as_str())).collect();
+        assert_eq!(found, vec![
+            ("src/lib.rs", 1, "TODO"),
+            ("src/lib.rs", 2, "todo!"),
+            ("src/lib.rs", 3, "unimplemented!"),
+            ("src/lib.rs", 6, "FIXME"),
+        ]);
+        assert_eq!(list.todos[3].text, "// FIXME(parser): handle errors");
+        assert_eq!(list.total, 4);
+        assert!(!list.truncated);
+    }
+
+    #[test]
+    fn test_get_structure_pagination() {
+        let root = TempDir::new("structure_pages");
+        for name in ["a.rs", "b.rs", "c.rs", "d.rs", "e.rs"] {
+            std::fs::write(root.join(name), "").unwrap();
         }
+        let manager = ProjectManager::new(IgnoreList::new(None));
+        let page = |offset, limit| manager.get_structure(root.path().to_path_buf(), None, offset, limit);
+
+        let first = page(0, Some(2)).unwrap();
+        assert!(first.starts_with("|-- a.rs\n|-- b.rs\n"));
+        assert!(first.ends_with("... 3 more entries (showing 1-2 of 5). next_offset: 2\n"));
+
+        let last = page(4, Some(2)).unwrap();
+        assert_eq!(last, "|-- e.rs\n");
+
+        // Without a limit everything fits, so there is no trailer
+        assert!(!page(0, None).unwrap().contains("more entries"));
+
+        let err = page(5, None).unwrap_err().to_string();
+        assert!(err.contains("past the end (5 entries)"));
     }
-}
\ No newline at end of file
+}
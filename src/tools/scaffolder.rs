@@ -1,10 +1,16 @@
 use std::path::PathBuf;
+use std::time::Duration;
 use anyhow::{Context, Result};
+use tracing::Instrument;
+use crate::tools::McpPatterns;
 use rmcp::schemars::JsonSchema;
 use serde::Deserialize;
 use tokio::fs;
 use tokio::io::AsyncWriteExt;
+use tokio_util::sync::CancellationToken;
 use rmcp::schemars;
+use crate::utils::process::{cargo_command, output_with_retry, output_with_timeout, ProcessOutcome};
+use crate::utils::ToolError;
 
 #[derive(Deserialize, JsonSchema)]
 pub struct ScaffoldToolRequest {
@@ -18,6 +24,16 @@ pub struct ScaffoldToolRequest {
     pub description: String,
 }
 
+#[derive(Deserialize, JsonSchema)]
+pub struct InitMcpServerRequest {
+    #[schemars(description = "Absolute path of the directory in which the new crate is created")]
+    pub parent_path: String,
+    #[schemars(description = "Name of the new crate (e.g., 'weather-mcp')")]
+    pub name: String,
+    #[schemars(description = "Optional: Timeout in seconds for each cargo step (default: server's --command-timeout-secs)")]
+    pub timeout_secs: Option<u64>,
+}
+
 pub struct McpToolScaffolder;
 
 impl McpToolScaffolder {
@@ -35,12 +51,12 @@ impl McpToolScaffolder {
     ) -> Result<String> {
         let tools_dir = project_root.join("src").join("tools");
         if !tools_dir.exists() {
-            anyhow::bail!("Could not find src/tools directory at {}", tools_dir.display());
+            return Err(ToolError::NotFound(format!("Could not find src/tools directory at {}", tools_dir.display())).into());
         }
 
         let file_path = tools_dir.join(format!("{}.rs", tool_name_snake));
         if file_path.exists() {
-            anyhow::bail!("Tool file '{}' already exists.", file_path.display());
+            return Err(ToolError::InvalidInput(format!("Tool file '{}' already exists.", file_path.display())).into());
         }
 
         // 1. Generate the Tool Logic File
@@ -117,6 +133,8 @@ impl {struct_name} {{
         Ok(CallToolResult::success(vec![Content::text(result)]))
     }}
     ```
+6. **Verify**:
+   Call `verify_tool_registered` with `tool_name="{tool_name}"`; it names any step that is still missing.
     "#,
             file_name = tool_name_snake,
             struct_name = tool_struct_name,
@@ -127,4 +145,122 @@ impl {struct_name} {{
         Ok(instructions)
     }
 
+    /// Dependencies of a stdio rmcp server: (crate, features)
+    const SERVER_DEPENDENCIES: [(&'static str, &'static str); 5] = [
+        ("rmcp", "server,macros,transport-io"),
+        ("tokio", "macros,rt-multi-thread,io-std"),
+        ("serde", "derive"),
+        ("schemars", ""),
+        ("anyhow", ""),
+    ];
+
+    /// Creates a new binary crate set up as an rmcp server: `cargo new`, the rmcp
+    /// dependencies via `cargo add`, and a starter `main.rs` plus `src/tools/` module.
+    /// `timeout` applies to each cargo step; registry blips during `cargo add` are retried.
+    pub async fn init_server(&self, parent_path: PathBuf, name: &str, cancel: &CancellationToken, timeout: Duration) -> Result<String> {
+        if !parent_path.is_dir() {
+            return Err(ToolError::NotFound(format!("Parent directory '{}' does not exist", parent_path.display())).into());
+        }
+
+        // Also keeps the name from escaping `parent_path` (e.g. "../x")
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            return Err(ToolError::InvalidInput(format!("Invalid crate name '{}'. Use letters, digits, '-' and '_' only.", name)).into());
+        }
+
+        let project_root = parent_path.join(name);
+        if project_root.exists() {
+            return Err(ToolError::InvalidInput(format!("'{}' already exists. Choose another name or use `scaffold_new_tool` on the existing project.", project_root.display())).into());
+        }
+
+        // 1. cargo new
+        let mut cmd = cargo_command();
+        cmd.current_dir(&parent_path)
+            .arg("new")
+            .arg("--bin")
+            .arg(name);
+
+        let output = match output_with_timeout(cmd, cancel, timeout)
+            .instrument(tracing::info_span!("cargo_new", name))
+            .await
+            .context("Failed to execute 'cargo new'")?
+        {
+            ProcessOutcome::Completed(output) => output,
+            ProcessOutcome::TimedOut(limit) => return Err(ToolError::Timeout(format!("'cargo new' timed out after {}s", limit.as_secs())).into()),
+            ProcessOutcome::Cancelled => return Ok(format!("Creating '{}' cancelled by client.", name)),
+        };
+
+        if !output.status.success() {
+            return Err(ToolError::CommandFailed(format!("cargo new failed: {}", String::from_utf8_lossy(&output.stderr))).into());
+        }
+
+        // 2. cargo add (one call per crate, so features stay attached to the right one)
+        for (crate_name, features) in Self::SERVER_DEPENDENCIES {
+            let build = || {
+                let mut cmd = cargo_command();
+                cmd.current_dir(&project_root)
+                    .arg("add")
+                    .arg(crate_name);
+                if !features.is_empty() {
+                    cmd.arg("--features").arg(features);
+                }
+                cmd
+            };
+
+            let output = match output_with_retry(build, cancel, timeout)
+                .instrument(tracing::info_span!("cargo_add", crate_name))
+                .await
+                .context("Failed to execute 'cargo add'")?
+            {
+                ProcessOutcome::Completed(output) => output,
+                ProcessOutcome::TimedOut(limit) => return Err(ToolError::Timeout(format!(
+                    "Created '{}', but 'cargo add {}' timed out after {}s",
+                    project_root.display(),
+                    crate_name,
+                    limit.as_secs()
+                )).into()),
+                ProcessOutcome::Cancelled => return Ok(format!(
+                    "Created '{}', but adding the dependencies was cancelled by client at '{}'.",
+                    project_root.display(),
+                    crate_name
+                )),
+            };
+
+            if !output.status.success() {
+                return Err(ToolError::CommandFailed(format!(
+                    "Created '{}', but 'cargo add {}' failed: {}",
+                    project_root.display(),
+                    crate_name,
+                    String::from_utf8_lossy(&output.stderr)
+                )).into());
+            }
+        }
+
+        // 3. Starter files
+        let mut created = Vec::new();
+        for (relative, content) in McpPatterns::new().starter_files() {
+            let file_path = project_root.join(relative);
+            if let Some(parent) = file_path.parent() {
+                fs::create_dir_all(parent).await.context("Failed to create directory")?;
+            }
+            fs::write(&file_path, content).await.context("Failed to write starter file")?;
+            created.push(relative);
+        }
+
+        Ok(format!(
+            r#"Successfully created MCP server crate at `{root}`.
+
+Dependencies: {deps}
+Files:
+  - Cargo.toml
+{files}
+
+--- NEXT STEPS ---
+1. Run `check_code` on `{root}` to verify the starter compiles.
+2. Replace the example `greet` tool, or add new tools with `scaffold_new_tool`.
+3. Use `get_mcp_template` for prompts, resources or other transports."#,
+            root = project_root.display(),
+            deps = Self::SERVER_DEPENDENCIES.iter().map(|(c, _)| *c).collect::<Vec<_>>().join(", "),
+            files = created.iter().map(|f| format!("  - {}", f)).collect::<Vec<_>>().join("\n"),
+        ))
+    }
 }
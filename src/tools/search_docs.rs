@@ -10,7 +10,12 @@ use std::time::Instant;
 use std::sync::Arc;
 use rmcp::schemars::JsonSchema;
 use rmcp::schemars;
-use tokio::sync::RwLock;
+use tokio::sync::{Notify, RwLock};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, info_span, warn, Instrument};
+use crate::utils::text::levenshtein;
+use crate::utils::IgnoreList;
+use crate::utils::ToolError;
 
 // --- Public Data Structures ---
 
@@ -18,14 +23,84 @@ use tokio::sync::RwLock;
 pub struct DocSearchResult {
     pub title: String,
     pub description: String,
+    /// Text around the best-matching query term, highlighted as `**term**`.
+    /// Falls back to `description` when the match is only in the title.
+    pub snippet: String,
     pub path: String,
+    /// Doc set the page belongs to, e.g. `std`, `reference`, `book`
+    pub source: String,
     pub relevance_score: f64,
 }
 
+/// Results plus any query terms that were replaced by fuzzy matching (e.g. "hasmap -> hashmap").
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct DocSearchOutput {
+    pub results: Vec<DocSearchResult>,
+    pub corrections: Vec<String>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ReadStdSourceRequest {
+    #[schemars(description = "Docs path of the item as returned by 'search_rust_docs' (e.g., 'std/collections/hash/map/struct.HashMap.html')")]
+    pub path: String,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ListTypeMethodsRequest {
+    #[schemars(description = "Item path (e.g., 'std::collections::HashMap') or docs path from 'search_rust_docs' (e.g., 'std/collections/hash/map/struct.HashMap.html')")]
+    pub item: String,
+    #[schemars(description = "Optional: Also list methods from trait implementations (Clone, Iterator, ...). Default false (inherent methods only).")]
+    pub include_trait_methods: Option<bool>,
+}
+
+/// A method signature as rendered by rustdoc
+#[derive(Debug, Serialize)]
+pub struct MethodInfo {
+    pub name: String,
+    pub signature: String,
+    /// `None` for inherent methods, else the header of the trait impl (e.g. "impl<K, V, S> Clone for HashMap<K, V, S>")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_trait: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TypeMethods {
+    pub title: String,
+    pub path: String,
+    pub methods: Vec<MethodInfo>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct WaitForIndexRequest {
+    #[schemars(description = "Optional: Maximum seconds to wait (default 60, max 600)")]
+    pub timeout_secs: Option<u64>,
+}
+
+/// Snapshot of the background indexer, as reported to clients
+#[derive(Debug, Serialize, Clone)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum IndexStatus {
+    Initializing,
+    Ready { documents: usize },
+    Error { message: String },
+}
+
 #[derive(Deserialize, JsonSchema)]
 pub struct SearchDocsRequest {
     #[schemars(description = "Search query (e.g., 'Vec', 'HashMap', 'async')")]
     pub query: String,
+    #[schemars(description = "Optional: Drop results with a relevance score below this threshold")]
+    pub min_score: Option<f64>,
+    #[schemars(description = "Optional: Tolerate small typos by replacing unknown terms with the closest indexed term (default false)")]
+    pub fuzzy: Option<bool>,
+    #[schemars(description = "Optional: Only return pages below this module or type path, e.g. 'std::collections' or 'std::collections::hash_map::HashMap'")]
+    pub module_prefix: Option<String>,
+}
+
+impl From<Vec<DocSearchResult>> for DocSearchOutput {
+    fn from(results: Vec<DocSearchResult>) -> Self {
+        Self { results, corrections: Vec::new() }
+    }
 }
 
 // --- Internal Data Structures ---
@@ -33,18 +108,34 @@ pub struct SearchDocsRequest {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct IndexedDocument {
     path: String,
+    /// Top-level directory below the docs root (`std`, `reference`, `book`, ...)
+    source: String,
     title: String,
     description: String,
     term_frequencies: HashMap<String, f64>,
+    /// One context window per term that occurs in the indexed body text (not the title)
+    term_snippets: HashMap<String, String>,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
 struct SearchIndex {
+    /// Hash of the docs path and the indexed sources; a mismatch invalidates the cache
     docs_path_hash: u64,
     documents: Vec<IndexedDocument>,
+    /// Number of documents containing each term; kept so single pages can be reindexed
+    doc_counts: HashMap<String, usize>,
     idf: HashMap<String, f64>,
 }
 
+/// What `process_html_file` made of a page
+enum ParsedPage {
+    Document(IndexedDocument),
+    /// rustdoc re-export page that only redirects to the real item page
+    RedirectStub,
+    /// No indexable text
+    Empty,
+}
+
 /// Represents the current state of the search engine
 enum SearchState {
     Initializing,
@@ -58,106 +149,375 @@ enum SearchState {
 pub struct RustDocsSearcher {
     docs_path: PathBuf,
     state: Arc<RwLock<SearchState>>,
+    /// Fired once when indexing finishes (successfully or not)
+    ready: Arc<Notify>,
 }
 
 impl RustDocsSearcher {
-    /// Creates a new searcher.
+    /// Doc sets indexed when nothing else is configured
+    pub const DEFAULT_SOURCES: [&'static str; 1] = ["std"];
+
+    /// Creates a new searcher that indexes the given doc sets (directories below
+    /// `docs_path`, e.g. `std`, `reference`, `book`, `nomicon`).
     /// Returns immediately while the index builds in the background.
-    pub fn new(docs_path: PathBuf) -> Self {
+    pub fn new(docs_path: PathBuf, ignore: IgnoreList, sources: Vec<String>) -> Self {
         let state = Arc::new(RwLock::new(SearchState::Initializing));
+        let ready = Arc::new(Notify::new());
         let searcher = Self {
             docs_path: docs_path.clone(),
             state: state.clone(),
+            ready: ready.clone(),
         };
 
         // Spawn the heavy lifting in the background
+        let span = info_span!("docs_index", path = %docs_path.display());
         tokio::spawn(async move {
             let start = Instant::now();
-            eprintln!("[RustDocsSearcher] Background indexing started...");
+            info!("[RustDocsSearcher] Background indexing started...");
 
             // Run the synchronous indexing logic
             // We use a separate block/function to isolate the heavy logic
-            let result = Self::build_or_load_index(docs_path);
+            let result = Self::build_or_load_index(docs_path, &ignore, &sources);
 
             let mut guard = state.write().await;
             match result {
                 Ok(index) => {
-                    eprintln!("[RustDocsSearcher] Index ready in {:.2}s. {} documents.", start.elapsed().as_secs_f64(), index.documents.len());
+                    info!("[RustDocsSearcher] Index ready in {:.2}s. {} documents.", start.elapsed().as_secs_f64(), index.documents.len());
                     *guard = SearchState::Ready(index);
                 }
                 Err(e) => {
-                    eprintln!("[RustDocsSearcher] Indexing failed: {}", e);
+                    error!("[RustDocsSearcher] Indexing failed: {}", e);
                     *guard = SearchState::Error(e.to_string());
                 }
             }
-        });
+            drop(guard);
+            ready.notify_waiters();
+        }.instrument(span));
 
         searcher
     }
 
     /// Performs a search.
     /// If indexing is still running, returns a friendly "wait" message.
-    pub async fn search(&self, query: &str) -> Result<Vec<DocSearchResult>> {
+    /// Results scoring below `min_score` are dropped.
+    /// With `fuzzy`, query terms missing from the index are replaced by their closest match.
+    /// `module_prefix` (`std::collections`) restricts results to pages below that path.
+    pub async fn search(&self, query: &str, min_score: Option<f64>, fuzzy: bool, module_prefix: Option<&str>) -> Result<DocSearchOutput> {
         let state = self.state.read().await;
 
         match &*state {
             SearchState::Initializing => {
-                Ok(vec![DocSearchResult {
+                Ok(DocSearchOutput::from(vec![DocSearchResult {
                     title: "Indexing in progress...".to_string(),
                     description: "The documentation index is currently being built. Please try again in a few seconds.".to_string(),
+                    snippet: String::new(),
                     path: "".to_string(),
+                    source: String::new(),
                     relevance_score: 1.0,
-                }])
+                }]))
             },
             SearchState::Error(msg) => {
-                Ok(vec![DocSearchResult {
+                Ok(DocSearchOutput::from(vec![DocSearchResult {
                     title: "Search Unavailable".to_string(),
                     description: format!("Indexing failed: {}", msg),
+                    snippet: String::new(),
                     path: "".to_string(),
+                    source: String::new(),
                     relevance_score: 0.0,
-                }])
+                }]))
             },
             SearchState::Ready(index) => {
-                Self::perform_search(index, query)
+                Self::perform_search(index, query, min_score, fuzzy, module_prefix)
             }
         }
     }
 
+    pub async fn status(&self) -> IndexStatus {
+        match &*self.state.read().await {
+            SearchState::Initializing => IndexStatus::Initializing,
+            SearchState::Ready(index) => IndexStatus::Ready { documents: index.documents.len() },
+            SearchState::Error(msg) => IndexStatus::Error { message: msg.clone() },
+        }
+    }
+
+    /// Waits until indexing has finished, `timeout` elapsed or `cancel` fired,
+    /// and returns the status at that point.
+    pub async fn wait_until_ready(&self, timeout: std::time::Duration, cancel: &CancellationToken) -> IndexStatus {
+        // Register interest *before* checking, so a notification in between isn't lost
+        let notified = self.ready.notified();
+        tokio::pin!(notified);
+        notified.as_mut().enable();
+
+        let status = self.status().await;
+        if !matches!(status, IndexStatus::Initializing) {
+            return status;
+        }
+
+        tokio::select! {
+            _ = notified => {}
+            _ = tokio::time::sleep(timeout) => {}
+            _ = cancel.cancelled() => {}
+        }
+
+        self.status().await
+    }
+
+    /// Max. lines returned by `read_source`
+    const MAX_SOURCE_LINES: usize = 300;
+
+    /// Returns the source of a documented item. The item's doc page links to the
+    /// rendered source (`src/<crate>/<file>.rs.html#<from>-<to>`); that link is mapped
+    /// onto the toolchain's rust-src checkout at `src_root` (`.../rustlib/src/rust/library`).
+    pub fn read_source(&self, doc_path: &str, src_root: &Path) -> Result<String> {
+        let doc_file = self.docs_path.join(doc_path.trim_start_matches('/'));
+        // Reject paths that escape the docs directory
+        if doc_path.contains("..") || !doc_file.is_file() {
+            return Err(ToolError::NotFound(format!("Docs page '{}' not found. Use a 'path' from 'search_rust_docs' results.", doc_path)).into());
+        }
+
+        let html = Html::parse_document(&fs::read_to_string(&doc_file)?);
+        let src_selector = Selector::parse("a.src, a.srclink").map_err(|_| anyhow::anyhow!("Bad selector"))?;
+        let href = html.select(&src_selector)
+            .find_map(|el| el.value().attr("href"))
+            .ok_or_else(|| ToolError::NotFound(format!("The page '{}' has no source link (e.g. module or primitive pages).", doc_path)))?;
+
+        // "../../src/std/collections/hash/map.rs.html#213-215"
+        let (src_html, anchor) = href.split_once('#').unwrap_or((href, ""));
+        let relative = src_html.rsplit_once("src/").map(|(_, r)| r).unwrap_or(src_html);
+        let relative = relative.strip_suffix(".html").unwrap_or(relative);
+        let (krate, file) = relative.split_once('/')
+            .ok_or_else(|| anyhow::anyhow!("Unexpected source link '{}'", href))?;
+        let source_file = src_root.join(krate).join("src").join(file);
+
+        let content = fs::read_to_string(&source_file)
+            .map_err(|_| ToolError::NotFound(format!("Source file '{}' not found. Is rust-src up to date with rust-docs?", source_file.display())))?;
+        let lines: Vec<&str> = content.lines().collect();
+
+        let mut bounds = anchor.trim_start_matches('L').split('-').filter_map(|n| n.trim_start_matches('L').parse::<usize>().ok());
+        let start = bounds.next().unwrap_or(1).max(1);
+        let end = bounds.next().unwrap_or(start + 40);
+        let end = end.min(start + Self::MAX_SOURCE_LINES - 1).min(lines.len());
+
+        let mut snippet = format!("// {}:{}-{}\n", source_file.display(), start, end);
+        for (i, line) in lines.iter().enumerate().take(end).skip(start - 1) {
+            snippet.push_str(&format!("{:04} | {}\n", i + 1, line));
+        }
+        Ok(snippet)
+    }
+
+    /// Re-parses a single HTML page and replaces its entry in the in-memory index
+    /// (or removes it if the file is gone), then recomputes the IDF table from the
+    /// stored document counts. Much cheaper than a full rebuild when only a few pages
+    /// were regenerated, e.g. by `cargo doc`. The on-disk cache is not updated.
+    #[allow(dead_code)] // No caller until project docs (cargo doc output) are indexed
+    pub async fn reindex_file(&self, file: &Path) -> Result<()> {
+        let relative = file.strip_prefix(&self.docs_path)
+            .map_err(|_| ToolError::InvalidInput(format!("'{}' is not inside the docs directory", file.display())))?
+            .display()
+            .to_string();
+
+        let mut state = self.state.write().await;
+        let SearchState::Ready(index) = &mut *state else {
+            return Err(ToolError::InvalidInput("The docs index is not ready yet.".to_string()).into());
+        };
+
+        if let Some(pos) = index.documents.iter().position(|d| d.path == relative) {
+            let old = index.documents.swap_remove(pos);
+            for term in old.term_frequencies.keys() {
+                if let Some(count) = index.doc_counts.get_mut(term) {
+                    *count -= 1;
+                    if *count == 0 {
+                        index.doc_counts.remove(term);
+                    }
+                }
+            }
+        }
+
+        if file.is_file() {
+            if let ParsedPage::Document(doc) = Self::process_html_file(file, &self.docs_path)? {
+                for term in doc.term_frequencies.keys() {
+                    *index.doc_counts.entry(term.clone()).or_insert(0) += 1;
+                }
+                index.documents.push(doc);
+            }
+        }
+
+        index.idf = Self::compute_idf(&index.doc_counts, index.documents.len());
+        debug!("[RustDocsSearcher] Reindexed {}", relative);
+        Ok(())
+    }
+
+    /// Item kinds tried when resolving `std::a::Name` to `std/a/<kind>.Name.html`
+    const ITEM_KINDS: [&'static str; 6] = ["struct", "enum", "trait", "union", "primitive", "type"];
+
+    /// Extracts the method signatures (`.code-header`) of a single type or trait page.
+    /// Re-export pages that only redirect (e.g. `std/collections/struct.HashMap.html`) are followed.
+    pub fn list_methods(&self, item: &str, include_trait_methods: bool) -> Result<TypeMethods> {
+        let item = item.trim();
+        if item.contains("..") {
+            return Err(ToolError::InvalidInput(format!("Invalid item '{}'", item)).into());
+        }
+
+        let mut doc_file = self.resolve_item_page(item)?;
+
+        let mut html = Html::parse_document(&fs::read_to_string(&doc_file)?);
+        if let Some(target) = Self::redirect_target(&html) {
+            let redirected = Self::normalize(&doc_file.parent().unwrap_or(&self.docs_path).join(target));
+            if redirected.starts_with(&self.docs_path) && redirected.is_file() {
+                html = Html::parse_document(&fs::read_to_string(&redirected)?);
+                doc_file = redirected;
+            }
+        }
+
+        let bad_selector = |_| anyhow::anyhow!("Bad selector");
+        let title_selector = Selector::parse("h1.fqn, h1.main-heading").map_err(bad_selector)?;
+        let impl_selector = Selector::parse("#implementations-list > details, #implementations-list > .impl-items, #trait-implementations-list > details, #trait-implementations-list > .impl-items, #required-methods + .methods, #provided-methods + .methods").map_err(bad_selector)?;
+        let impl_header_selector = Selector::parse("summary .code-header, .impl .code-header").map_err(bad_selector)?;
+        let method_selector = Selector::parse(".method").map_err(bad_selector)?;
+        let header_selector = Selector::parse(".code-header").map_err(bad_selector)?;
+        let text_of = |el: scraper::ElementRef| el.text().collect::<String>().split_whitespace().collect::<Vec<_>>().join(" ");
+
+        let title = html.select(&title_selector).next()
+            .map(text_of)
+            .unwrap_or_else(|| item.to_string());
+
+        let mut methods: Vec<MethodInfo> = Vec::new();
+        for block in html.select(&impl_selector) {
+            let in_trait_impls = block.ancestors()
+                .filter_map(scraper::ElementRef::wrap)
+                .any(|el| el.value().id() == Some("trait-implementations-list"));
+            if in_trait_impls && !include_trait_methods {
+                continue;
+            }
+            let from_trait = if in_trait_impls {
+                block.select(&impl_header_selector).next().map(text_of)
+            } else {
+                None
+            };
+
+            for method in block.select(&method_selector) {
+                let Some(header) = method.select(&header_selector).next() else { continue };
+                let name = method.value().id()
+                    .and_then(|id| id.strip_prefix("method.").or_else(|| id.strip_prefix("tymethod.")))
+                    .map(|n| n.split('-').next().unwrap_or(n).to_string())
+                    .unwrap_or_default();
+                let signature = text_of(header);
+                if methods.iter().any(|m| m.signature == signature && m.from_trait == from_trait) {
+                    continue;
+                }
+                methods.push(MethodInfo { name, signature, from_trait: from_trait.clone() });
+            }
+        }
+
+        let path = doc_file.strip_prefix(&self.docs_path).unwrap_or(&doc_file).display().to_string();
+        Ok(TypeMethods { title, path, methods })
+    }
+
+    /// Maps a docs path (`.../struct.X.html`) or an item path (`std::a::X`) to its page.
+    fn resolve_item_page(&self, item: &str) -> Result<PathBuf> {
+        if item.ends_with(".html") {
+            let doc_file = self.docs_path.join(item.trim_start_matches('/'));
+            if doc_file.is_file() {
+                return Ok(doc_file);
+            }
+        } else if let Some((module, name)) = item.rsplit_once("::") {
+            let dir = self.docs_path.join(module.replace("::", "/"));
+            if let Some(page) = Self::ITEM_KINDS.iter()
+                .map(|kind| dir.join(format!("{}.{}.html", kind, name)))
+                .find(|page| page.is_file())
+            {
+                return Ok(page);
+            }
+        }
+
+        Err(ToolError::NotFound(format!(
+            "No docs page found for '{}'. Use a full path like 'std::collections::HashMap' or a 'path' from 'search_rust_docs' results.",
+            item
+        )).into())
+    }
+
+    /// A page with a refresh redirect and no heading or doc block of its own, like the
+    /// re-export stub `std/collections/struct.HashMap.html`
+    fn is_redirect_stub(html: &Html) -> bool {
+        let Ok(content_selector) = Selector::parse("h1, .docblock, main") else {
+            return false;
+        };
+        Self::redirect_target(html).is_some() && html.select(&content_selector).next().is_none()
+    }
+
+    /// Target of a `<meta http-equiv="refresh" content="0;URL=...">` redirect stub
+    fn redirect_target(html: &Html) -> Option<String> {
+        let selector = Selector::parse("meta[http-equiv=\"refresh\"]").ok()?;
+        let content = html.select(&selector).next()?.value().attr("content")?;
+        let (_, url) = content.split_once("URL=").or_else(|| content.split_once("url="))?;
+        Some(url.split('#').next().unwrap_or(url).to_string())
+    }
+
+    /// Resolves `.` and `..` components without touching the filesystem
+    fn normalize(path: &Path) -> PathBuf {
+        let mut normalized = PathBuf::new();
+        for component in path.components() {
+            match component {
+                std::path::Component::CurDir => {}
+                std::path::Component::ParentDir => { normalized.pop(); }
+                other => normalized.push(other),
+            }
+        }
+        normalized
+    }
+
     // --- Logic Wrappers (Static/Pure functions) ---
 
     /// Logic to load from cache or build fresh.
     /// This is synchronous code, but running inside the tokio::spawn wrapper.
-    fn build_or_load_index(docs_path: PathBuf) -> Result<SearchIndex> {
-        let path_hash = Self::get_path_hash(&docs_path);
+    fn build_or_load_index(docs_path: PathBuf, ignore: &IgnoreList, sources: &[String]) -> Result<SearchIndex> {
+        let path_hash = Self::get_index_hash(&docs_path, sources);
 
         // 1. Try Cache
         if let Ok(mut index) = Self::load_from_cache() {
             if index.docs_path_hash == path_hash {
                 return Ok(index);
             }
-            eprintln!("[RustDocsSearcher] Cache outdated. Rebuilding...");
+            info!("[RustDocsSearcher] Cache outdated. Rebuilding...");
         }
 
         // 2. Build Fresh
-        let index = Self::build_index_fresh(&docs_path)?;
+        let index = Self::build_index_fresh(&docs_path, ignore, sources)?;
 
         // 3. Save Cache
         if let Err(e) = Self::save_to_cache(&index) {
-            eprintln!("[RustDocsSearcher] Warning: Failed to save cache: {}", e);
+            warn!("[RustDocsSearcher] Failed to save cache: {}", e);
         }
 
         Ok(index)
     }
 
-    fn perform_search(index: &SearchIndex, query: &str) -> Result<Vec<DocSearchResult>> {
-        let query_terms = Self::tokenize(query);
+    fn perform_search(index: &SearchIndex, query: &str, min_score: Option<f64>, fuzzy: bool, module_prefix: Option<&str>) -> Result<DocSearchOutput> {
+        let mut query_terms = Self::tokenize(query);
         let mut results = Vec::new();
+        let mut corrections = Vec::new();
 
         if query_terms.is_empty() {
-            return Ok(results);
+            return Ok(DocSearchOutput::default());
+        }
+
+        if fuzzy {
+            for term in query_terms.iter_mut() {
+                if index.idf.contains_key(term.as_str()) {
+                    continue;
+                }
+                if let Some(closest) = Self::closest_term(index, term) {
+                    corrections.push(format!("{} -> {}", term, closest));
+                    *term = closest;
+                }
+            }
         }
 
         for doc in &index.documents {
+            if module_prefix.is_some_and(|prefix| !Self::matches_module_prefix(&doc.path, prefix)) {
+                continue;
+            }
+
             let mut score = 0.0;
             for term in &query_terms {
                 let tf = doc.term_frequencies.get(term).unwrap_or(&0.0);
@@ -165,11 +525,13 @@ impl RustDocsSearcher {
                 score += tf * idf;
             }
 
-            if score > 0.0 {
+            if score > 0.0 && score >= min_score.unwrap_or(0.0) {
                 results.push(DocSearchResult {
                     title: doc.title.clone(),
                     description: doc.description.clone(),
+                    snippet: Self::best_snippet(index, doc, &query_terms),
                     path: doc.path.clone(),
+                    source: doc.source.clone(),
                     relevance_score: score,
                 });
             }
@@ -177,20 +539,76 @@ impl RustDocsSearcher {
 
         results.sort_by(|a, b| b.relevance_score.partial_cmp(&a.relevance_score).unwrap());
         results.truncate(15);
-        Ok(results)
+        Ok(DocSearchOutput { results, corrections })
+    }
+
+    /// Whether the page at `doc_path` lies below the Rust path `prefix`. `std::collections`
+    /// matches everything in `std/collections/`; a last segment naming an item
+    /// (`std::collections::HashMap`) matches its page `struct.HashMap.html` in that module
+    /// or a submodule, since re-exported items are documented where they are defined.
+    fn matches_module_prefix(doc_path: &str, prefix: &str) -> bool {
+        let doc_path = doc_path.replace('\\', "/");
+        let segments: Vec<&str> = prefix.split("::").map(str::trim).filter(|s| !s.is_empty()).collect();
+        let Some((last, parents)) = segments.split_last() else {
+            return true;
+        };
+
+        if doc_path.starts_with(&format!("{}/", segments.join("/"))) {
+            return true;
+        }
+
+        let parent_dir = parents.join("/");
+        let (dir, file) = doc_path.rsplit_once('/').unwrap_or(("", &doc_path));
+        (dir == parent_dir || parent_dir.is_empty() || dir.starts_with(&format!("{}/", parent_dir)))
+            && file.strip_suffix(".html")
+                .and_then(|stem| stem.split_once('.'))
+                .is_some_and(|(_, name)| name == *last)
+    }
+
+    /// Snippet of the query term that contributes most to the score
+    fn best_snippet(index: &SearchIndex, doc: &IndexedDocument, query_terms: &[String]) -> String {
+        query_terms.iter()
+            .filter_map(|term| {
+                let snippet = doc.term_snippets.get(term)?;
+                let weight = doc.term_frequencies.get(term).unwrap_or(&0.0) * index.idf.get(term).unwrap_or(&0.0);
+                Some((weight, snippet))
+            })
+            .max_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(_, snippet)| snippet.clone())
+            .unwrap_or_else(|| doc.description.clone())
+    }
+
+    /// Closest vocabulary term within edit distance 1 (short terms) or 2.
+    /// Ties go to the more common term (lower IDF).
+    fn closest_term(index: &SearchIndex, term: &str) -> Option<String> {
+        let len = term.chars().count();
+        let max_distance = if len <= 4 { 1 } else { 2 };
+
+        index.idf.iter()
+            // Cheap length filter before the O(n*m) distance
+            .filter(|(candidate, _)| candidate.chars().count().abs_diff(len) <= max_distance)
+            .filter_map(|(candidate, idf)| {
+                let distance = levenshtein(term, candidate);
+                (distance <= max_distance).then_some((distance, *idf, candidate))
+            })
+            .min_by(|a, b| a.0.cmp(&b.0)
+                .then(a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+                .then(a.2.cmp(b.2)))
+            .map(|(_, _, candidate)| candidate.clone())
     }
 
     // --- Private Helpers (FileSystem & Parsing) ---
 
     fn get_cache_path() -> PathBuf {
-        std::env::temp_dir().join("mcp_rust_docs_v2.bin")
+        std::env::temp_dir().join("mcp_rust_docs_v6.bin")
     }
 
-    fn get_path_hash(path: &Path) -> u64 {
+    fn get_index_hash(path: &Path, sources: &[String]) -> u64 {
         use std::hash::{Hash, Hasher};
         use std::collections::hash_map::DefaultHasher;
         let mut hasher = DefaultHasher::new();
         path.hash(&mut hasher);
+        sources.hash(&mut hasher);
         hasher.finish()
     }
 
@@ -213,14 +631,21 @@ impl RustDocsSearcher {
         Ok(())
     }
 
-    fn build_index_fresh(docs_path: &Path) -> Result<SearchIndex> {
+    fn build_index_fresh(docs_path: &Path, ignore: &IgnoreList, sources: &[String]) -> Result<SearchIndex> {
         let mut all_html_files = Vec::new();
-        // Only index `std` to keep it manageable, or remove .join("std") for full docs
-        Self::find_html_files(&docs_path.join("std"), &mut all_html_files)?;
+        // Only the configured doc sets (default: `std`) to keep it manageable
+        for source in sources {
+            let dir = docs_path.join(source);
+            if !dir.is_dir() {
+                warn!("[RustDocsSearcher] Doc set '{}' not found at {}", source, dir.display());
+                continue;
+            }
+            Self::find_html_files(&dir, ignore, &mut all_html_files)?;
+        }
 
         if all_html_files.is_empty() {
             // Fallback: try root if std doesn't exist
-            Self::find_html_files(docs_path, &mut all_html_files)?;
+            Self::find_html_files(docs_path, ignore, &mut all_html_files)?;
         }
 
         let total_docs = all_html_files.len() as f64;
@@ -232,39 +657,57 @@ impl RustDocsSearcher {
         let mut doc_counts: HashMap<String, usize> = HashMap::new();
 
         let mut processed = 0;
+        let mut skipped_stubs = 0;
         for file_path in &all_html_files {
-            if let Ok(Some(indexed_doc)) = Self::process_html_file(file_path, docs_path) {
-                for term in indexed_doc.term_frequencies.keys() {
-                    *doc_counts.entry(term.clone()).or_insert(0) += 1;
+            match Self::process_html_file(file_path, docs_path) {
+                Ok(ParsedPage::Document(indexed_doc)) => {
+                    for term in indexed_doc.term_frequencies.keys() {
+                        *doc_counts.entry(term.clone()).or_insert(0) += 1;
+                    }
+                    documents.push(indexed_doc);
                 }
-                documents.push(indexed_doc);
+                Ok(ParsedPage::RedirectStub) => skipped_stubs += 1,
+                Ok(ParsedPage::Empty) | Err(_) => {}
             }
             processed += 1;
-            // Optional: Log progress occasionally
+            // Log progress occasionally
             if processed % 1000 == 0 {
-                // eprintln!("Indexed {} files...", processed);
+                debug!("[RustDocsSearcher] Indexed {} files...", processed);
             }
         }
 
-        let mut idf = HashMap::new();
-        for (term, count) in doc_counts {
-            let val = (total_docs / count as f64).ln();
-            idf.insert(term, val);
-        }
+        info!("[RustDocsSearcher] Skipped {} redirect stubs out of {} HTML files.", skipped_stubs, all_html_files.len());
+
+        let idf = Self::compute_idf(&doc_counts, documents.len());
 
         Ok(SearchIndex {
-            docs_path_hash: Self::get_path_hash(docs_path),
+            docs_path_hash: Self::get_index_hash(docs_path, sources),
             documents,
+            doc_counts,
             idf,
         })
     }
 
-    fn find_html_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    fn compute_idf(doc_counts: &HashMap<String, usize>, total_docs: usize) -> HashMap<String, f64> {
+        doc_counts.iter()
+            .map(|(term, count)| (term.clone(), (total_docs as f64 / *count as f64).ln()))
+            .collect()
+    }
+
+    fn find_html_files(dir: &Path, ignore: &IgnoreList, files: &mut Vec<PathBuf>) -> Result<()> {
         if !dir.is_dir() { return Ok(()); }
         for entry in fs::read_dir(dir)? {
-            let path = entry?.path();
-            if path.is_dir() {
-                Self::find_html_files(&path, files)?;
+            let entry = entry?;
+            // `DirEntry::file_type` does not follow symlinks: linked directories are
+            // skipped so a link cycle can't recurse forever
+            let file_type = entry.file_type()?;
+            let path = entry.path();
+            if file_type.is_symlink() {
+                continue;
+            } else if file_type.is_dir() {
+                if !ignore.is_ignored(&entry.file_name().to_string_lossy()) {
+                    Self::find_html_files(&path, ignore, files)?;
+                }
             } else if path.extension().and_then(|s| s.to_str()) == Some("html") {
                 files.push(path);
             }
@@ -272,26 +715,37 @@ impl RustDocsSearcher {
         Ok(())
     }
 
-    fn process_html_file(file_path: &Path, root_path: &Path) -> Result<Option<IndexedDocument>> {
+    fn process_html_file(file_path: &Path, root_path: &Path) -> Result<ParsedPage> {
         // Read file
         let content = fs::read_to_string(file_path)?;
         let document = Html::parse_document(&content);
+        if Self::is_redirect_stub(&document) {
+            return Ok(ParsedPage::RedirectStub);
+        }
 
-        // Selectors
+        // Selectors: rustdoc pages first, then mdBook pages (reference, book, nomicon)
         let title_selector = Selector::parse("h1.fqn, h1.main-heading").map_err(|_| anyhow::anyhow!("Bad selector"))?;
         let desc_selector = Selector::parse(".docblock p").map_err(|_| anyhow::anyhow!("Bad selector"))?;
+        let book_title_selector = Selector::parse("main h1").map_err(|_| anyhow::anyhow!("Bad selector"))?;
+        let book_desc_selector = Selector::parse("main p").map_err(|_| anyhow::anyhow!("Bad selector"))?;
 
         // Extract Title
         let title = document.select(&title_selector).next()
+            .or_else(|| document.select(&book_title_selector).next())
             .map(|el| el.text().collect::<String>().trim().to_string())
             .unwrap_or_else(|| file_path.file_stem().and_then(|s| s.to_str()).unwrap_or("").to_string());
 
         // Extract Description
         let description = document.select(&desc_selector).next()
+            .or_else(|| document.select(&book_desc_selector).next())
             .map(|el| el.text().collect::<String>())
             .map(|text| {
                 let trimmed = text.trim();
-                if trimmed.len() > 200 { format!("{}...", &trimmed[..200]) } else { trimmed.to_string() }
+                // Cut on a char boundary: book prose is full of typographic quotes and dashes
+                match trimmed.char_indices().nth(200) {
+                    Some((end, _)) => format!("{}...", &trimmed[..end]),
+                    None => trimmed.to_string(),
+                }
             })
             .unwrap_or_default();
 
@@ -302,7 +756,16 @@ impl RustDocsSearcher {
         let terms = Self::tokenize(&full_text);
 
         let term_count = terms.len();
-        if term_count == 0 { return Ok(None); }
+        if term_count == 0 { return Ok(ParsedPage::Empty); }
+
+        let mut term_snippets = HashMap::new();
+        for term in Self::tokenize(&description) {
+            if !term_snippets.contains_key(&term) {
+                if let Some(snippet) = Self::make_snippet(&description, &term) {
+                    term_snippets.insert(term, snippet);
+                }
+            }
+        }
 
         let mut term_frequencies = HashMap::new();
         for term in terms {
@@ -312,16 +775,60 @@ impl RustDocsSearcher {
             *freq /= term_count as f64;
         }
 
-        let relative_path = file_path.strip_prefix(root_path).unwrap_or(file_path).display().to_string();
+        let relative = file_path.strip_prefix(root_path).unwrap_or(file_path);
+        // Pages directly in the docs root (e.g. index.html) have no doc set
+        let source = match relative.components().count() {
+            0 | 1 => String::new(),
+            _ => relative.components().next().map(|c| c.as_os_str().to_string_lossy().to_string()).unwrap_or_default(),
+        };
 
-        Ok(Some(IndexedDocument {
-            path: relative_path,
+        Ok(ParsedPage::Document(IndexedDocument {
+            path: relative.display().to_string(),
+            source,
             title,
             description,
             term_frequencies,
+            term_snippets,
         }))
     }
 
+    /// Characters of context kept on each side of the highlighted term
+    const SNIPPET_CONTEXT: usize = 60;
+
+    /// Cuts a window around the first whole-word occurrence of `term` (case-insensitive)
+    /// and highlights it as `**word**`. Cuts only on char boundaries.
+    fn make_snippet(text: &str, term: &str) -> Option<String> {
+        let mut start = None;
+        let mut found = None;
+        for (i, c) in text.char_indices().chain(std::iter::once((text.len(), ' '))) {
+            match (c.is_alphanumeric(), start) {
+                (true, None) => start = Some(i),
+                (false, Some(s)) => {
+                    if text[s..i].to_lowercase() == term {
+                        found = Some((s, i));
+                        break;
+                    }
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+        let (word_start, word_end) = found?;
+
+        let before: Vec<(usize, char)> = text[..word_start].char_indices().collect();
+        let from = before.len().checked_sub(Self::SNIPPET_CONTEXT).map_or(0, |i| before[i].0);
+        let to = text[word_end..].char_indices().nth(Self::SNIPPET_CONTEXT).map_or(text.len(), |(i, _)| word_end + i);
+
+        Some(format!(
+            "{}{}**{}**{}{}",
+            if from > 0 { "..." } else { "" },
+            text[from..word_start].trim_start(),
+            &text[word_start..word_end],
+            text[word_end..to].trim_end(),
+            if to < text.len() { "..." } else { "" },
+        ))
+    }
+
     fn tokenize(text: &str) -> Vec<String> {
         text.to_lowercase()
             .split(|c: char| !c.is_alphanumeric())
@@ -329,4 +836,104 @@ impl RustDocsSearcher {
             .map(String::from)
             .collect()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_support::TempDir;
+
+    #[test]
+    fn test_make_snippet() {
+        let text = "A contiguous growable array type, written as `Vec<T>`, short for 'vector'.";
+        assert_eq!(
+            RustDocsSearcher::make_snippet(text, "growable").unwrap(),
+            "A contiguous **growable** array type, written as `Vec<T>`, short for 'vector'."
+        );
+        // Whole words only, case-insensitive
+        assert_eq!(RustDocsSearcher::make_snippet(text, "vec").unwrap().matches("**Vec**").count(), 1);
+        assert!(RustDocsSearcher::make_snippet(text, "grow").is_none());
+
+        let long = format!("{} needle {}", "ä".repeat(100), "ö".repeat(100));
+        let snippet = RustDocsSearcher::make_snippet(&long, "needle").unwrap();
+        assert!(snippet.starts_with("...") && snippet.ends_with("..."));
+        assert!(snippet.contains("**needle**"));
+    }
+
+    #[test]
+    fn test_matches_module_prefix() {
+        let page = "std/collections/hash_map/struct.HashMap.html";
+        assert!(RustDocsSearcher::matches_module_prefix(page, "std::collections"));
+        assert!(RustDocsSearcher::matches_module_prefix(page, "std::collections::hash_map::HashMap"));
+        assert!(RustDocsSearcher::matches_module_prefix(page, "std::collections::hash_map::"));
+        assert!(RustDocsSearcher::matches_module_prefix(page, "std::collections::HashMap"));
+        assert!(!RustDocsSearcher::matches_module_prefix(page, "std::vec::HashMap"));
+        assert!(!RustDocsSearcher::matches_module_prefix(page, "std::collections::hash_map::HashMa"));
+        assert!(!RustDocsSearcher::matches_module_prefix("std/vec/struct.Vec.html", "std::collections"));
+        assert!(!RustDocsSearcher::matches_module_prefix("std/collections_extra/index.html", "std::collections"));
+        assert!(RustDocsSearcher::matches_module_prefix("std/vec/struct.Vec.html", ""));
+    }
+
+    #[test]
+    fn test_build_index_skips_redirect_stubs() {
+        let docs = TempDir::new("stubs");
+        let dir = docs.join("std/collections/hash_map");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("struct.HashMap.html"),
+            r#"<h1 class="main-heading">Struct HashMap</h1><div class="docblock"><p>A hash map implemented with quadratic probing.</p></div>"#,
+        ).unwrap();
+        std::fs::write(
+            docs.join("std/collections/struct.HashMap.html"),
+            r#"<!DOCTYPE html><html><head><meta http-equiv="refresh" content="0;URL=hash_map/struct.HashMap.html"></head><body><p>Redirecting to <a href="hash_map/struct.HashMap.html">hash_map/struct.HashMap.html</a>...</p></body></html>"#,
+        ).unwrap();
+
+        let index = RustDocsSearcher::build_index_fresh(docs.path(), &IgnoreList::new(None), &["std".to_string()]).unwrap();
+        assert_eq!(index.documents.len(), 1);
+        assert_eq!(PathBuf::from(&index.documents[0].path), PathBuf::from("std/collections/hash_map/struct.HashMap.html"));
+    }
+
+    #[test]
+    fn test_redirect_target() {
+        let stub = Html::parse_document(r#"<html><head><meta http-equiv="refresh" content="0;URL=../../std/collections/hash_map/struct.HashMap.html"></head></html>"#);
+        let target = RustDocsSearcher::redirect_target(&stub).unwrap();
+        assert_eq!(target, "../../std/collections/hash_map/struct.HashMap.html");
+
+        let resolved = RustDocsSearcher::normalize(&Path::new("/docs/std/collections").join(target));
+        assert_eq!(resolved, PathBuf::from("/docs/std/collections/hash_map/struct.HashMap.html"));
+    }
+
+    #[tokio::test]
+    async fn test_reindex_file() {
+        let docs = TempDir::new("reindex");
+        let page = docs.join("struct.Widget.html");
+        std::fs::write(&page, r#"<h1 class="main-heading">Widget</h1><div class="docblock"><p>A sprocket holder.</p></div>"#).unwrap();
+
+        let searcher = RustDocsSearcher {
+            docs_path: docs.path().to_path_buf(),
+            state: Arc::new(RwLock::new(SearchState::Ready(SearchIndex {
+                docs_path_hash: 0,
+                documents: Vec::new(),
+                doc_counts: HashMap::new(),
+                idf: HashMap::new(),
+            }))),
+            ready: Arc::new(Notify::new()),
+        };
+
+        // A term in every document has an IDF of 0, so index a second page
+        let other = docs.join("struct.Other.html");
+        std::fs::write(&other, r#"<h1 class="main-heading">Other</h1><div class="docblock"><p>Unrelated text.</p></div>"#).unwrap();
+        searcher.reindex_file(&other).await.unwrap();
+
+        searcher.reindex_file(&page).await.unwrap();
+        let results = searcher.search("sprocket", None, false, None).await.unwrap().results;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].path, "struct.Widget.html");
+        assert_eq!(results[0].source, "");
+
+        // Changed page replaces the old entry
+        std::fs::write(&page, r#"<h1 class="main-heading">Widget</h1><div class="docblock"><p>A gear holder.</p></div>"#).unwrap();
+        searcher.reindex_file(&page).await.unwrap();
+        assert!(searcher.search("sprocket", None, false, None).await.unwrap().results.is_empty());
+    }
+}
@@ -1,10 +1,15 @@
 // src/tools/surgeon.rs
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use anyhow::{Context, Result};
+use regex::Regex;
 use rmcp::schemars::JsonSchema;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use tokio::fs;
 use rmcp::schemars;
+use similar::TextDiff;
+use syn::spanned::Spanned;
+use crate::utils::process::capped_output;
+use crate::utils::ToolError;
 
 #[derive(Deserialize, JsonSchema)]
 pub struct PatchFileRequest {
@@ -14,6 +19,111 @@ pub struct PatchFileRequest {
     pub original_snippet: String,
     #[schemars(description = "The new code to insert")]
     pub modified_snippet: String,
+    #[schemars(description = "Optional: Treat 'original_snippet' as a regex and replace ALL matches in the file. 'modified_snippet' may use $1, ${name} captures. Default false (literal, first match).")]
+    pub regex: Option<bool>,
+    #[schemars(description = "Optional: Fail instead of replacing the first match if the snippet occurs more than once; the error lists the line of every match. Literal mode only. Default false.")]
+    pub require_unique: Option<bool>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct PatchFilesRequest {
+    #[schemars(description = "List of patch operations ({path, original_snippet, modified_snippet}). Applied only if ALL of them match.")]
+    pub operations: Vec<PatchFileRequest>,
+}
+
+/// Result of a multi-file patch for a single file
+#[derive(Debug, Serialize)]
+pub struct PatchSummary {
+    pub path: String,
+    pub operations: usize,
+    pub diff: String,
+    pub warnings: Vec<String>,
+}
+
+/// Result of `apply_edits`
+#[derive(Debug)]
+pub struct AppliedEdits {
+    /// Fixes applied per file, in order of first appearance
+    pub per_file: Vec<(PathBuf, usize)>,
+    /// Fixes left out because an edit overlapped an earlier fix or was out of range
+    pub skipped: usize,
+}
+
+/// Folder (inside the project root) holding per-edit backups
+pub const BACKUP_DIR: &str = ".mcp-backups";
+const MAX_BACKUPS_PER_FILE: usize = 10;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct RestoreFileRequest {
+    #[schemars(description = "Absolute path to the file to restore from its most recent backup")]
+    pub path: String,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct DiffFilesRequest {
+    #[schemars(description = "Absolute path of the original file")]
+    pub old_path: String,
+    #[schemars(description = "Absolute path of the file to compare against")]
+    pub new_path: String,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ReplaceFunctionRequest {
+    #[schemars(description = "Absolute path to the Rust file")]
+    pub path: String,
+    #[schemars(description = "Function name, optionally qualified to disambiguate (e.g. 'run', 'TestRunner::run', 'utils::parse')")]
+    pub name: String,
+    #[schemars(description = "New body as a block ('{ ... }', braces optional) or, with 'whole_item', the complete function including signature and attributes")]
+    pub new_source: String,
+    #[schemars(description = "Optional: Replace the whole item (attributes, doc comments, signature and body) instead of only the body (default false)")]
+    pub whole_item: Option<bool>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct SuggestAnchorRequest {
+    #[schemars(description = "Absolute path to the file")]
+    pub path: String,
+    #[schemars(description = "First line you want to change (1-based)")]
+    pub start_line: usize,
+    #[schemars(description = "Optional: Last line you want to change (inclusive, default: start_line)")]
+    pub end_line: Option<usize>,
+}
+
+/// Smallest snippet around the requested lines that occurs exactly once in the file
+#[derive(Debug, Serialize)]
+pub struct PatchAnchor {
+    pub snippet: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    /// True if lines outside the requested range had to be included
+    pub extended: bool,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct CreateDirRequest {
+    #[schemars(description = "Absolute path of the directory; missing parents are created too (mkdir -p)")]
+    pub path: String,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct TouchFileRequest {
+    #[schemars(description = "Absolute path of the file, e.g. '/project/src/tools/net/http.rs'; missing parent directories are created")]
+    pub path: String,
+}
+
+/// Result of `create_dir` / `touch_file`
+#[derive(Debug, Serialize)]
+pub struct CreatedPath {
+    pub path: String,
+    /// False if it already existed (it is left unchanged)
+    pub created: bool,
+}
+
+/// A function found by `replace_function`, with the spans needed to edit it
+struct FunctionLocation {
+    qualified_name: String,
+    item_span: proc_macro2::Span,
+    body_span: proc_macro2::Span,
 }
 
 pub struct FileSurgeon;
@@ -23,19 +133,97 @@ impl FileSurgeon {
         Self
     }
 
+    /// Computes an `original_snippet` for `patch_file`: the requested lines, extended
+    /// one line at a time (below, then above) until the snippet is unique in the file.
+    pub async fn suggest_anchor(&self, path: PathBuf, start_line: usize, end_line: usize) -> Result<PatchAnchor> {
+        if !path.exists() {
+            return Err(ToolError::NotFound(format!("File '{}' not found", path.display())).into());
+        }
+
+        // patch_file converts '\n' to '\r\n' itself, so the anchor uses plain newlines
+        let content = fs::read_to_string(&path)
+            .await
+            .context("Failed to read file")?
+            .replace("\r\n", "\n");
+        let line_count = content.lines().count();
+
+        if start_line == 0 || start_line > end_line || end_line > line_count {
+            return Err(ToolError::InvalidInput(format!(
+                "Invalid line range {}-{}: '{}' has {} lines.",
+                start_line, end_line, path.display(), line_count
+            )).into());
+        }
+
+        let (start, end) = Self::find_anchor(&content, start_line, end_line)
+            .ok_or_else(|| ToolError::InvalidInput(format!(
+                "No anchor found for lines {}-{}: the file contains only whitespace.",
+                start_line, end_line
+            )))?;
+
+        Ok(PatchAnchor {
+            snippet: Self::lines_between(&content, start, end),
+            start_line: start,
+            end_line: end,
+            extended: (start, end) != (start_line, end_line),
+        })
+    }
+
+    /// Widens `start..=end` (1-based) until the joined lines occur exactly once in `content`.
+    fn find_anchor(content: &str, mut start: usize, mut end: usize) -> Option<(usize, usize)> {
+        let line_count = content.lines().count();
+        let is_unique = |start: usize, end: usize| {
+            let snippet = Self::lines_between(content, start, end);
+            // Overlapping occurrences count too ("x\nx" is twice in "x\nx\nx")
+            match (content.find(&snippet), snippet.chars().next()) {
+                (Some(first), Some(c)) => !snippet.trim().is_empty() && !content[first + c.len_utf8()..].contains(&snippet),
+                _ => false,
+            }
+        };
+
+        loop {
+            if is_unique(start, end) {
+                return Some((start, end));
+            }
+            if start == 1 && end == line_count {
+                return None;
+            }
+            if end < line_count {
+                end += 1;
+                if is_unique(start, end) {
+                    return Some((start, end));
+                }
+            }
+            if start > 1 {
+                start -= 1;
+            }
+        }
+    }
+
+    /// Lines `start..=end` (1-based) joined with '\n', without a trailing newline
+    fn lines_between(content: &str, start: usize, end: usize) -> String {
+        content.lines()
+            .skip(start - 1)
+            .take(end + 1 - start)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
     /// Replaces the *first* occurrence of `search` with `replace` in the file at `path`.
     ///
     /// Improvements over standard replacement:
     /// 1. Checks for "Near Misses" (whitespace errors) to guide the AI.
     /// 2. handles line-ending normalization.
+    ///
+    /// With `require_unique`, a snippet that occurs more than once is an error and nothing is written.
     pub async fn patch_file(
         &self,
         path: PathBuf,
         search: &str,
         replace: &str,
+        require_unique: bool,
     ) -> Result<String> {
         if !path.exists() {
-            anyhow::bail!("File '{}' not found", path.display());
+            return Err(ToolError::NotFound(format!("File '{}' not found", path.display())).into());
         }
 
         // 1. Read file (Async)
@@ -43,6 +231,124 @@ impl FileSurgeon {
             .await
             .context("Failed to read file")?;
 
+        let (new_content, count) = self.apply_patch(&path, &original_content, search, replace, require_unique)?;
+
+        // Backup first, so a single bad patch can be reverted with `restore_file`
+        self.backup(&path, &original_content).await?;
+
+        // Atomic Write (write to string first, then flush to disk)
+        fs::write(&path, new_content)
+            .await
+            .context("Failed to write to file")?;
+
+        let mut msg = format!("Successfully patched '{}'.", path.display());
+        if count > 1 {
+            msg.push_str(&format!(
+                "\nWARNING: The search snippet was found {} times. Only the FIRST occurrence was replaced. \
+                If you intended to change a specific instance, include more surrounding context in your search snippet.",
+                count
+            ));
+        }
+        Ok(msg)
+    }
+
+    /// Applies several patches across files transactionally: every operation is validated
+    /// in memory first, and files are only written if *all* of them match.
+    /// Operations on the same file are applied in order.
+    pub async fn patch_files(&self, operations: Vec<PatchFileRequest>) -> Result<Vec<PatchSummary>> {
+        if operations.is_empty() {
+            return Err(ToolError::InvalidInput("No patch operations given.".to_string()).into());
+        }
+
+        // canonical path -> (original content, current content, summary); Vec keeps the order stable.
+        // Canonical keys make `src/a.rs` and `src/../src/a.rs` share one staged copy.
+        let mut staged: Vec<(PathBuf, String, String, PatchSummary)> = Vec::new();
+
+        // 1. Validate everything in memory
+        for (i, op) in operations.iter().enumerate() {
+            let path = PathBuf::from(&op.path);
+            let fail = |e: anyhow::Error| e.context(format!(
+                "Operation #{} ('{}') failed. NO files were changed",
+                i + 1, op.path
+            ));
+
+            if op.regex.unwrap_or(false) {
+                return Err(fail(ToolError::InvalidInput("Regex mode is not supported in multi-file patches. Use literal snippets.".to_string()).into()));
+            }
+
+            if !path.exists() {
+                return Err(fail(ToolError::NotFound(format!("File '{}' not found", path.display())).into()));
+            }
+            let key = fs::canonicalize(&path).await.context("Failed to resolve path").map_err(fail)?;
+
+            let idx = match staged.iter().position(|(p, _, _, _)| *p == key) {
+                Some(idx) => idx,
+                None => {
+                    let content = fs::read_to_string(&key).await.context("Failed to read file").map_err(fail)?;
+                    staged.push((key, content.clone(), content, PatchSummary {
+                        path: op.path.clone(),
+                        operations: 0,
+                        diff: String::new(),
+                        warnings: Vec::new(),
+                    }));
+                    staged.len() - 1
+                }
+            };
+
+            let (_, _, content, summary) = &mut staged[idx];
+            let (new_content, count) = self
+                .apply_patch(&path, content, &op.original_snippet, &op.modified_snippet, op.require_unique.unwrap_or(false))
+                .map_err(fail)?;
+
+            *content = new_content;
+            summary.operations += 1;
+            summary.diff.push_str(&Self::snippet_diff(&op.original_snippet, &op.modified_snippet));
+            if count > 1 {
+                summary.warnings.push(format!(
+                    "Operation #{}: snippet found {} times, only the FIRST occurrence was replaced.",
+                    i + 1, count
+                ));
+            }
+        }
+
+        // 2. All matched -> backup originals, then write
+        let files: Vec<(&Path, &str, &str)> = staged.iter()
+            .map(|(path, original, content, _)| (path.as_path(), original.as_str(), content.as_str()))
+            .collect();
+        self.write_all(&files).await?;
+
+        Ok(staged.into_iter().map(|(_, _, _, summary)| summary).collect())
+    }
+
+    /// Backs up and writes `(path, original, new)` for every file. A failed write restores
+    /// the files written before it, so the batch is all-or-nothing.
+    async fn write_all(&self, files: &[(&Path, &str, &str)]) -> Result<()> {
+        for (path, original, _) in files {
+            self.backup(path, original).await?;
+        }
+
+        for (written, (path, _, content)) in files.iter().enumerate() {
+            if let Err(e) = fs::write(path, content).await {
+                let mut not_restored = Vec::new();
+                for (path, original, _) in &files[..written] {
+                    if fs::write(path, original).await.is_err() {
+                        not_restored.push(path.display().to_string());
+                    }
+                }
+                let state = if not_restored.is_empty() {
+                    "Earlier files were restored, NO files were changed".to_string()
+                } else {
+                    format!("Could not restore {} (use `restore_file`)", not_restored.join(", "))
+                };
+                return Err(anyhow::Error::new(e).context(format!("Failed to write '{}'. {}", path.display(), state)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Pure matching logic shared by `patch_file` and `patch_files`.
+    /// Returns the new content and how often the snippet occurred.
+    fn apply_patch(&self, path: &Path, original_content: &str, search: &str, replace: &str, require_unique: bool) -> Result<(String, usize)> {
         // 2. Normalize Line Endings
         // If the file uses \r\n (Windows), ensure the search string also uses \r\n,
         // otherwise exact string matching will fail even if it looks correct.
@@ -56,42 +362,494 @@ impl FileSurgeon {
         if original_content.contains(&search_normalized) {
             let count = original_content.matches(&search_normalized).count();
 
-            // Perform the replacement (Limit 1 to be safe)
-            let new_content = original_content.replacen(&search_normalized, replace, 1);
-
-            // Atomic Write (write to string first, then flush to disk)
-            fs::write(&path, new_content)
-                .await
-                .context("Failed to write to file")?;
-
-            let mut msg = format!("Successfully patched '{}'.", path.display());
-            if count > 1 {
-                msg.push_str(&format!(
-                    "\nWARNING: The search snippet was found {} times. Only the FIRST occurrence was replaced. \
-                    If you intended to change a specific instance, include more surrounding context in your search snippet.",
-                    count
-                ));
+            if require_unique && count > 1 {
+                let lines: Vec<String> = original_content.match_indices(&search_normalized)
+                    .map(|(offset, _)| (original_content[..offset].matches('\n').count() + 1).to_string())
+                    .collect();
+                return Err(ToolError::InvalidInput(format!(
+                    "The snippet occurs {} times in '{}' (starting at lines {}) and 'require_unique' is set. Nothing was changed.\n\
+                    Action: Include more surrounding context (see `suggest_patch_anchor`) so the snippet matches exactly one place.",
+                    count,
+                    path.display(),
+                    lines.join(", ")
+                )).into());
             }
-            return Ok(msg);
+
+            // Perform the replacement (Limit 1 to be safe)
+            return Ok((original_content.replacen(&search_normalized, replace, 1), count));
         }
 
         // 4. Diagnostic: Check for Whitespace Errors (The "Near Miss" Check)
         // This is critical for AI agents. They often mix up spaces/tabs.
-        if self.matches_ignoring_whitespace(&original_content, &search_normalized) {
-            anyhow::bail!(
+        if self.matches_ignoring_whitespace(original_content, &search_normalized) {
+            // Show the agent the actual lines, so it can fix the snippet without re-reading the file
+            let region = match self.locate_near_miss(original_content, &search_normalized) {
+                Some((start, end)) => format!(
+                    "\n\nActual file content (near miss at lines {}-{}):\n{}",
+                    start, end, Self::render_region(original_content, start, end)
+                ),
+                None => String::new(),
+            };
+
+            return Err(ToolError::InvalidInput(format!(
                 "Exact match failed, BUT the code was found when ignoring whitespace.\n\
                 Diagnostic: Your 'original_snippet' has incorrect indentation or line breaks compared to the actual file.\n\
-                Action: Read the file again, copy the lines EXACTLY (including leading spaces), and try again."
-            );
+                Action: Copy the lines below EXACTLY (including leading spaces, without the line number prefix), and try again.{}",
+                region
+            )).into());
         }
 
         // 5. Fail
-        anyhow::bail!(
+        Err(ToolError::InvalidInput(format!(
             "Could not find the snippet in '{}'. \
             The code you are trying to replace does not exist, or it has been modified since you last read it.\n\
             Action: Use `read_file` (or `get_project_structure`) to verify the file content.",
             path.display()
-        );
+        )).into())
+    }
+
+    /// Reverts `path` to its most recent backup. The used backup is consumed,
+    /// so calling this repeatedly steps further back.
+    pub async fn restore_file(&self, path: PathBuf) -> Result<String> {
+        let backups = Self::list_backups(&path).await?;
+
+        let latest = backups.last().ok_or_else(|| ToolError::NotFound(format!(
+            "No backup found for '{}'. Backups are only created by patch_file/patch_files.",
+            path.display()
+        )))?;
+
+        let content = fs::read_to_string(latest)
+            .await
+            .context("Failed to read backup")?;
+        fs::write(&path, content)
+            .await
+            .context("Failed to restore file")?;
+        fs::remove_file(latest).await.context("Failed to remove used backup")?;
+
+        Ok(format!(
+            "Restored '{}' from backup. {} older backup(s) remaining.",
+            path.display(),
+            backups.len() - 1
+        ))
+    }
+
+    /// Saves `content` (the state before an edit) into `.mcp-backups/` and prunes old backups.
+    async fn backup(&self, path: &Path, content: &str) -> Result<()> {
+        let (dir, prefix) = Self::backup_location(path);
+        fs::create_dir_all(&dir).await.context("Failed to create backup directory")?;
+
+        // Keep backups out of `git add .` without touching the project's .gitignore
+        let gitignore = dir.join(".gitignore");
+        if !gitignore.exists() {
+            fs::write(&gitignore, "*\n").await.context("Failed to write backup .gitignore")?;
+        }
+
+        // Zero-padded timestamp, so lexical order == chronological order
+        let millis = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or_default();
+        let backup_path = dir.join(format!("{}.{:020}.bak", prefix, millis));
+        fs::write(&backup_path, content).await.context("Failed to write backup")?;
+
+        // Keep only the newest MAX_BACKUPS_PER_FILE
+        let backups = Self::list_backups(path).await?;
+        if backups.len() > MAX_BACKUPS_PER_FILE {
+            for old in &backups[..backups.len() - MAX_BACKUPS_PER_FILE] {
+                let _ = fs::remove_file(old).await;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// All backups of `path`, oldest first.
+    async fn list_backups(path: &Path) -> Result<Vec<PathBuf>> {
+        let (dir, prefix) = Self::backup_location(path);
+        let mut backups = Vec::new();
+
+        if !dir.exists() {
+            return Ok(backups);
+        }
+
+        let mut entries = fs::read_dir(&dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let name = entry.file_name().to_string_lossy().to_string();
+            // <prefix>.<timestamp>.bak
+            if let Some(rest) = name.strip_prefix(&format!("{}.", prefix)) {
+                if rest.len() == 24 && rest.ends_with(".bak") {
+                    backups.push(entry.path());
+                }
+            }
+        }
+
+        backups.sort();
+        Ok(backups)
+    }
+
+    /// Backups live in `<project root>/.mcp-backups/`, named after the file's path
+    /// relative to the root (`src/main.rs` -> `src__main.rs`).
+    /// The root is the nearest ancestor with a Cargo.toml or .git, else the file's folder.
+    fn backup_location(path: &Path) -> (PathBuf, String) {
+        let parent = path.parent().unwrap_or(Path::new("."));
+        let root = parent
+            .ancestors()
+            .find(|dir| dir.join("Cargo.toml").exists() || dir.join(".git").exists())
+            .unwrap_or(parent);
+
+        let relative = path.strip_prefix(root).unwrap_or(path);
+        let prefix = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .collect::<Vec<_>>()
+            .join("__");
+
+        (root.join(BACKUP_DIR), prefix)
+    }
+
+    /// Minimal `-`/`+` rendering of one replacement
+    fn snippet_diff(original: &str, modified: &str) -> String {
+        let mut diff = String::new();
+        for line in original.lines() {
+            diff.push_str(&format!("- {}\n", line));
+        }
+        for line in modified.lines() {
+            diff.push_str(&format!("+ {}\n", line));
+        }
+        diff
+    }
+
+    /// Unified diff (3 lines of context) between two arbitrary files, e.g. an old and a
+    /// new implementation. Works outside version control; output is capped like command output.
+    pub async fn diff_files(&self, old_path: PathBuf, new_path: PathBuf) -> Result<String> {
+        for path in [&old_path, &new_path] {
+            if !path.is_file() {
+                return Err(ToolError::NotFound(format!("File '{}' not found", path.display())).into());
+            }
+        }
+
+        let old = fs::read_to_string(&old_path)
+            .await
+            .with_context(|| format!("Failed to read '{}' (binary file?)", old_path.display()))?;
+        let new = fs::read_to_string(&new_path)
+            .await
+            .with_context(|| format!("Failed to read '{}' (binary file?)", new_path.display()))?;
+
+        if old == new {
+            return Ok("Files are identical.".to_string());
+        }
+
+        let diff = TextDiff::from_lines(&old, &new)
+            .unified_diff()
+            .context_radius(3)
+            .header(&old_path.display().to_string(), &new_path.display().to_string())
+            .to_string();
+
+        Ok(capped_output(diff.as_bytes()))
+    }
+
+    /// `mkdir -p`: creates `path` and its missing parents.
+    pub async fn create_dir(&self, path: PathBuf) -> Result<CreatedPath> {
+        Self::require_absolute(&path)?;
+        if path.is_dir() {
+            return Ok(CreatedPath { path: path.display().to_string(), created: false });
+        }
+        if path.exists() {
+            return Err(ToolError::InvalidInput(format!("'{}' exists and is not a directory", path.display())).into());
+        }
+
+        fs::create_dir_all(&path)
+            .await
+            .with_context(|| format!("Failed to create directory '{}'", path.display()))?;
+        Ok(CreatedPath { path: path.display().to_string(), created: true })
+    }
+
+    /// Creates an empty file (and its missing parents). An existing file is not modified.
+    pub async fn touch_file(&self, path: PathBuf) -> Result<CreatedPath> {
+        Self::require_absolute(&path)?;
+        if path.is_dir() {
+            return Err(ToolError::InvalidInput(format!("'{}' is a directory", path.display())).into());
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .await
+                .with_context(|| format!("Failed to create directory '{}'", parent.display()))?;
+        }
+
+        // create_new: never truncates a file that appeared in the meantime
+        let created = match fs::OpenOptions::new().write(true).create_new(true).open(&path).await {
+            Ok(_) => true,
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => false,
+            Err(e) => return Err(anyhow::Error::new(e).context(format!("Failed to create '{}'", path.display()))),
+        };
+        Ok(CreatedPath { path: path.display().to_string(), created })
+    }
+
+    fn require_absolute(path: &Path) -> Result<()> {
+        if !path.is_absolute() {
+            return Err(ToolError::InvalidInput(format!("'{}' is not an absolute path", path.display())).into());
+        }
+        Ok(())
+    }
+
+    /// Applies fixes, each a list of byte-range replacements `(file, start, end, replacement)`
+    /// that belong together (e.g. one compiler suggestion adding a `use` and changing the call
+    /// site). All files are read and edited in memory first and then written together, so
+    /// either every file is changed or none.
+    pub async fn apply_edits(&self, fixes: Vec<Vec<(PathBuf, usize, usize, String)>>) -> Result<AppliedEdits> {
+        let mut originals: Vec<(PathBuf, String)> = Vec::new();
+        for path in fixes.iter().flatten().map(|(path, ..)| path) {
+            if originals.iter().any(|(known, _)| known == path) {
+                continue;
+            }
+            let original = fs::read_to_string(path)
+                .await
+                .with_context(|| format!("Failed to read '{}'. NO files were changed", path.display()))?;
+            originals.push((path.clone(), original));
+        }
+
+        let (edits, counts, skipped) = Self::select_fixes(&originals, fixes);
+        let edited: Vec<String> = originals.iter()
+            .zip(edits)
+            .map(|((_, original), edits)| Self::edit_content(original, edits))
+            .collect();
+
+        let changed: Vec<(&Path, &str, &str)> = originals.iter()
+            .zip(&edited)
+            .filter(|((_, original), content)| original != *content)
+            .map(|((path, original), content)| (path.as_path(), original.as_str(), content.as_str()))
+            .collect();
+        self.write_all(&changed).await?;
+
+        let per_file = originals.into_iter().map(|(path, _)| path).zip(counts).collect();
+        Ok(AppliedEdits { per_file, skipped })
+    }
+
+    /// Picks the fixes that can be applied to `files`: every edit in range and on char
+    /// boundaries, none overlapping an edit of an earlier fix. A fix is taken or skipped as a
+    /// whole, so a multi-span suggestion is never half-applied. Returns the accepted edits and
+    /// the number of fixes per file (indexed like `files`), plus the number skipped.
+    fn select_fixes(
+        files: &[(PathBuf, String)],
+        fixes: Vec<Vec<(PathBuf, usize, usize, String)>>,
+    ) -> (Vec<Vec<(usize, usize, String)>>, Vec<usize>, usize) {
+        let mut accepted: Vec<Vec<(usize, usize, String)>> = vec![Vec::new(); files.len()];
+        let mut counts = vec![0; files.len()];
+        let mut skipped = 0;
+        let mut seen = Vec::new();
+
+        for mut fix in fixes {
+            // The same suggestion can be reported twice (e.g. for the lib and a test target)
+            fix.sort();
+            fix.dedup();
+            if seen.contains(&fix) {
+                continue;
+            }
+            seen.push(fix.clone());
+
+            let mut placed: Vec<(usize, (usize, usize, String))> = Vec::new();
+            let fits = fix.into_iter().all(|(path, start, end, replacement)| {
+                let Some(i) = files.iter().position(|(known, _)| *known == path) else { return false };
+                let content = &files[i].1;
+                // Two insertions at one point are ambiguous as well
+                let overlaps = accepted[i].iter()
+                    .chain(placed.iter().filter(|(j, _)| *j == i).map(|(_, edit)| edit))
+                    .any(|(s, e, _)| (start < *e && *s < end) || start == *s);
+                let valid = start <= end
+                    && end <= content.len()
+                    && content.is_char_boundary(start)
+                    && content.is_char_boundary(end);
+                if valid && !overlaps {
+                    placed.push((i, (start, end, replacement)));
+                }
+                valid && !overlaps
+            });
+            if !fits {
+                skipped += 1;
+                continue;
+            }
+
+            let mut touched = Vec::new();
+            for (i, edit) in placed {
+                accepted[i].push(edit);
+                if !touched.contains(&i) {
+                    touched.push(i);
+                }
+            }
+            for i in touched {
+                counts[i] += 1;
+            }
+        }
+
+        (accepted, counts, skipped)
+    }
+
+    /// Applies non-overlapping edits bottom-up, so earlier offsets stay valid.
+    fn edit_content(original_content: &str, mut edits: Vec<(usize, usize, String)>) -> String {
+        edits.sort_by(|a, b| b.0.cmp(&a.0).then(b.1.cmp(&a.1)));
+
+        let mut content = original_content.to_string();
+        for (start, end, replacement) in edits {
+            content.replace_range(start..end, &replacement);
+        }
+        content
+    }
+
+    /// Replaces the body (or with `whole_item` the complete item) of the function `name`.
+    /// The function is located via its `syn` span, so nested braces can't confuse the
+    /// boundaries. The new code is validated, formatted with `prettyplease` and indented
+    /// like the original; the rest of the file stays untouched.
+    pub async fn replace_function(&self, path: PathBuf, name: &str, new_source: &str, whole_item: bool) -> Result<String> {
+        if !path.exists() {
+            return Err(ToolError::NotFound(format!("File '{}' not found", path.display())).into());
+        }
+
+        let original_content = fs::read_to_string(&path)
+            .await
+            .context("Failed to read file")?;
+
+        let syntax = syn::parse_file(&original_content)
+            .context("Failed to parse the file. Fix syntax errors before using replace_function.")?;
+
+        let mut functions = Vec::new();
+        collect_functions(&syntax.items, "", &mut functions);
+
+        let name = name.trim();
+        let candidates: Vec<&FunctionLocation> = functions.iter()
+            .filter(|f| f.qualified_name == name || f.qualified_name.ends_with(&format!("::{}", name)))
+            .collect();
+
+        let function = match candidates.as_slice() {
+            [function] => *function,
+            [] => return Err(ToolError::NotFound(format!(
+                "Function '{}' not found in '{}'. Available: {}",
+                name,
+                path.display(),
+                functions.iter().map(|f| f.qualified_name.as_str()).collect::<Vec<_>>().join(", ")
+            )).into()),
+            many => return Err(ToolError::InvalidInput(format!(
+                "'{}' is ambiguous. Use a qualified name: {}",
+                name,
+                many.iter()
+                    .map(|f| format!("{} (line {})", f.qualified_name, f.item_span.start().line))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )).into()),
+        };
+
+        let span = if whole_item { function.item_span } else { function.body_span };
+        let (start, end) = span_offsets(&original_content, span.start(), span.end())?;
+
+        let formatted = if whole_item {
+            Self::format_item(new_source)?
+        } else {
+            Self::format_body(new_source)?
+        };
+
+        // Continuation lines get the indentation of the line the replaced code starts on
+        let line_start = original_content[..start].rfind('\n').map_or(0, |i| i + 1);
+        let indent: String = original_content[line_start..start].chars().take_while(|c| c.is_whitespace()).collect();
+        let replacement = formatted.lines()
+            .enumerate()
+            .map(|(i, line)| if i == 0 || line.is_empty() { line.to_string() } else { format!("{}{}", indent, line) })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let mut new_content = original_content.clone();
+        new_content.replace_range(start..end, &replacement);
+
+        if let Err(e) = syn::parse_file(&new_content) {
+            return Err(ToolError::InvalidInput(format!(
+                "The file would not parse after the replacement ({}). No changes were made.",
+                e
+            )).into());
+        }
+
+        if new_content == original_content {
+            return Ok(format!("'{}' is already up to date. No changes were made.", function.qualified_name));
+        }
+
+        self.backup(&path, &original_content).await?;
+        fs::write(&path, &new_content)
+            .await
+            .context("Failed to write to file")?;
+
+        let first_line = span.start().line;
+        Ok(format!(
+            "Replaced the {} of '{}' in '{}' (now lines {}-{}).",
+            if whole_item { "item" } else { "body" },
+            function.qualified_name,
+            path.display(),
+            first_line,
+            first_line + replacement.lines().count().saturating_sub(1)
+        ))
+    }
+
+    /// Validates `source` as a block (the braces may be omitted) and pretty-prints it.
+    fn format_body(source: &str) -> Result<String> {
+        let block: syn::Block = match syn::parse_str(source.trim()) {
+            Ok(block) => block,
+            Err(_) => syn::parse_str(&format!("{{\n{}\n}}", source)).map_err(|e| ToolError::InvalidInput(format!(
+                "'new_source' is not a valid function body: {}",
+                e
+            )))?,
+        };
+
+        // prettyplease formats whole files, so the block is wrapped in a dummy function
+        let mut wrapper: syn::ItemFn = syn::parse_str("fn __replace_function() {}").expect("valid dummy fn");
+        wrapper.block = Box::new(block);
+        let printed = prettyplease::unparse(&syn::File { shebang: None, attrs: Vec::new(), items: vec![syn::Item::Fn(wrapper)] });
+
+        let body_start = printed.find('{').expect("printed fn has a body");
+        Ok(printed[body_start..].trim_end().to_string())
+    }
+
+    /// Validates `source` as a complete function and pretty-prints it.
+    fn format_item(source: &str) -> Result<String> {
+        let item: syn::ItemFn = syn::parse_str(source.trim()).map_err(|e| ToolError::InvalidInput(format!(
+            "'new_source' is not a valid function item: {}",
+            e
+        )))?;
+
+        let printed = prettyplease::unparse(&syn::File { shebang: None, attrs: Vec::new(), items: vec![syn::Item::Fn(item)] });
+        Ok(printed.trim_end().to_string())
+    }
+
+    /// Replaces *all* matches of the regex `pattern` with `replacement` (supports `$1`/`${name}`).
+    pub async fn patch_file_regex(
+        &self,
+        path: PathBuf,
+        pattern: &str,
+        replacement: &str,
+    ) -> Result<String> {
+        if !path.exists() {
+            return Err(ToolError::NotFound(format!("File '{}' not found", path.display())).into());
+        }
+
+        let re = Regex::new(pattern)
+            .map_err(|e| ToolError::InvalidInput(format!("Invalid regex in 'original_snippet': {}", e)))?;
+
+        let original_content = fs::read_to_string(&path)
+            .await
+            .context("Failed to read file")?;
+
+        let count = re.find_iter(&original_content).count();
+        if count == 0 {
+            return Err(ToolError::InvalidInput(format!(
+                "The regex matched nothing in '{}'. No changes were made.\n\
+                Action: Use `read_file` to verify the content, and remember to escape special characters like ( ) {{ }} . *",
+                path.display()
+            )).into());
+        }
+
+        let new_content = re.replace_all(&original_content, replacement);
+
+        self.backup(&path, &original_content).await?;
+        fs::write(&path, new_content.as_ref())
+            .await
+            .context("Failed to write to file")?;
+
+        Ok(format!("Successfully patched '{}'. Replaced {} match(es).", path.display(), count))
     }
 
     /// Helper: returns true if `snippet` exists in `content` when all whitespace is collapsed.
@@ -107,4 +865,286 @@ impl FileSurgeon {
 
         norm_content.contains(&norm_snippet)
     }
-}
\ No newline at end of file
+
+    /// Helper: finds the (1-based, inclusive) line range in `content` that matches `snippet`
+    /// when whitespace is ignored. Blank lines are skipped on both sides.
+    fn locate_near_miss(&self, content: &str, snippet: &str) -> Option<(usize, usize)> {
+        let normalize = |s: &str| s.split_whitespace().collect::<Vec<&str>>().join(" ");
+
+        let file_lines: Vec<(usize, String)> = content.lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, normalize(line)))
+            .filter(|(_, line)| !line.is_empty())
+            .collect();
+        let snippet_lines: Vec<String> = snippet.lines()
+            .map(normalize)
+            .filter(|line| !line.is_empty())
+            .collect();
+
+        let first = snippet_lines.first()?;
+
+        // 1. Same lines, different indentation
+        if let Some(window) = file_lines
+            .windows(snippet_lines.len())
+            .find(|w| w.iter().zip(&snippet_lines).all(|((_, a), b)| a == b))
+        {
+            return Some((window[0].0, window[window.len() - 1].0));
+        }
+
+        // 2. Different line breaks: anchor on the first snippet line
+        file_lines.iter()
+            .find(|(_, line)| line.contains(first.as_str()))
+            .map(|(n, _)| (*n, *n + snippet_lines.len() - 1))
+    }
+
+    /// Renders lines `start..=end` plus 2 lines of context, numbered like `read_file`.
+    fn render_region(content: &str, start: usize, end: usize) -> String {
+        const CONTEXT: usize = 2;
+        const MAX_LINES: usize = 40;
+
+        let from = start.saturating_sub(CONTEXT).max(1);
+        let to = (end + CONTEXT).min(from + MAX_LINES - 1);
+
+        content.lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line))
+            .filter(|(n, _)| *n >= from && *n <= to)
+            .map(|(n, line)| format!("{:04} | {}", n, line))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Collects free functions, methods and default trait methods, recursing into inline modules.
+/// Names are qualified with the module path and the impl/trait type (`module::Type::method`).
+fn collect_functions(items: &[syn::Item], prefix: &str, out: &mut Vec<FunctionLocation>) {
+    let qualify = |name: String| if prefix.is_empty() { name } else { format!("{}::{}", prefix, name) };
+
+    for item in items {
+        match item {
+            syn::Item::Fn(f) => out.push(FunctionLocation {
+                qualified_name: qualify(f.sig.ident.to_string()),
+                item_span: f.span(),
+                body_span: f.block.span(),
+            }),
+            syn::Item::Impl(i) => {
+                let type_name = match &*i.self_ty {
+                    syn::Type::Path(p) => p.path.segments.last().map(|s| s.ident.to_string()),
+                    _ => None,
+                };
+                let owner = qualify(type_name.unwrap_or_else(|| "impl".to_string()));
+                for impl_item in &i.items {
+                    if let syn::ImplItem::Fn(f) = impl_item {
+                        out.push(FunctionLocation {
+                            qualified_name: format!("{}::{}", owner, f.sig.ident),
+                            item_span: f.span(),
+                            body_span: f.block.span(),
+                        });
+                    }
+                }
+            }
+            syn::Item::Trait(t) => {
+                let owner = qualify(t.ident.to_string());
+                for trait_item in &t.items {
+                    if let syn::TraitItem::Fn(f) = trait_item {
+                        if let Some(block) = &f.default {
+                            out.push(FunctionLocation {
+                                qualified_name: format!("{}::{}", owner, f.sig.ident),
+                                item_span: f.span(),
+                                body_span: block.span(),
+                            });
+                        }
+                    }
+                }
+            }
+            syn::Item::Mod(m) => {
+                if let Some((_, content)) = &m.content {
+                    collect_functions(content, &qualify(m.ident.to_string()), out);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Converts a span's start and end to byte offsets. Without proc-macro2's
+/// `span-locations` feature every span reports line 0, which would otherwise map both ends
+/// to EOF and append the replacement instead of replacing anything.
+fn span_offsets(content: &str, start: proc_macro2::LineColumn, end: proc_macro2::LineColumn) -> Result<(usize, usize)> {
+    if start.line == 0 {
+        return Err(ToolError::InvalidInput(
+            "Source locations are unavailable (proc-macro2 was built without `span-locations`). No changes were made.".to_string()
+        ).into());
+    }
+    Ok((line_col_to_offset(content, start), line_col_to_offset(content, end)))
+}
+
+/// Converts a span position (1-based line, 0-based column in chars) to a byte offset.
+fn line_col_to_offset(content: &str, position: proc_macro2::LineColumn) -> usize {
+    let mut offset = 0;
+    for (i, line) in content.split_inclusive('\n').enumerate() {
+        if i + 1 == position.line {
+            return offset + line.char_indices().nth(position.column).map_or(line.len(), |(idx, _)| idx);
+        }
+        offset += line.len();
+    }
+    content.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_support::TempDir;
+
+    #[test]
+    fn test_locate_near_miss() {
+        let surgeon = FileSurgeon::new();
+        let content = "fn main() {\n    let a = 1;\n\n    let b = 2;\n}\n";
+
+        // Wrong indentation
+        assert_eq!(surgeon.locate_near_miss(content, "let a = 1;\nlet b = 2;"), Some((2, 4)));
+        // Not present at all
+        assert_eq!(surgeon.locate_near_miss(content, "let c = 3;"), None);
+    }
+
+    #[test]
+    fn test_find_anchor() {
+        let content = "fn a() {\n    x += 1;\n}\n\nfn b() {\n    x += 1;\n}\n";
+
+        // Unique on its own
+        assert_eq!(FileSurgeon::find_anchor(content, 1, 1), Some((1, 1)));
+        // "    x += 1;" occurs twice: the closing brace doesn't help, the signature does
+        assert_eq!(FileSurgeon::find_anchor(content, 6, 6), Some((5, 7)));
+        assert_eq!(FileSurgeon::lines_between(content, 5, 7), "fn b() {\n    x += 1;\n}");
+        // Overlapping repeats: only the whole file is unique
+        assert_eq!(FileSurgeon::find_anchor("x\nx\nx\n", 2, 2), Some((1, 3)));
+        assert_eq!(FileSurgeon::find_anchor("\n\n", 1, 1), None);
+    }
+
+    #[tokio::test]
+    async fn test_replace_function() {
+        let dir = TempDir::new("replace_fn");
+        let file = dir.join("lib.rs");
+        std::fs::write(&file, "struct A;\n\nimpl A {\n    fn run(&self) -> u32 {\n        if true { 1 } else { 2 }\n    }\n}\n\nfn run() {}\n").unwrap();
+
+        let surgeon = FileSurgeon::new();
+
+        // Plain name matches both functions
+        assert!(surgeon.replace_function(file.clone(), "run", "{ 3 }", false).await.is_err());
+        // Body without braces is accepted
+        surgeon.replace_function(file.clone(), "A::run", "let x = 3;\nx", false).await.unwrap();
+        // Invalid bodies are rejected without touching the file
+        assert!(surgeon.replace_function(file.clone(), "A::run", "{ let = ; }", false).await.is_err());
+
+        let content = std::fs::read_to_string(&file).unwrap();
+        assert_eq!(content, "struct A;\n\nimpl A {\n    fn run(&self) -> u32 {\n        let x = 3;\n        x\n    }\n}\n\nfn run() {}\n");
+
+        // The whole item is replaced in place, not appended
+        surgeon.replace_function(file.clone(), "A::run", "fn run(&self) -> u32 { 7 }", true).await.unwrap();
+        let content = std::fs::read_to_string(&file).unwrap();
+        assert_eq!(content, "struct A;\n\nimpl A {\n    fn run(&self) -> u32 {\n        7\n    }\n}\n\nfn run() {}\n");
+    }
+
+    #[test]
+    fn test_span_offsets() {
+        use proc_macro2::LineColumn;
+        let content = "fn a() {}\nfn b() {}\n";
+        let at = |line, column| LineColumn { line, column };
+
+        assert_eq!(span_offsets(content, at(2, 0), at(2, 9)).unwrap(), (10, 19));
+        assert_eq!(&content[10..19], "fn b() {}");
+        // Line 0 means span-locations is off: refuse instead of appending at EOF
+        let err = span_offsets(content, at(0, 0), at(0, 0)).unwrap_err().to_string();
+        assert!(err.contains("span-locations"));
+    }
+
+    #[test]
+    fn test_apply_patch_require_unique() {
+        let surgeon = FileSurgeon::new();
+        let path = Path::new("lib.rs");
+        let content = "let a = 1;\nlet b = 2;\nlet a = 1;\n";
+
+        let (patched, count) = surgeon.apply_patch(path, content, "let a = 1;", "let a = 3;", false).unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(patched, "let a = 3;\nlet b = 2;\nlet a = 1;\n");
+
+        let err = surgeon.apply_patch(path, content, "let a = 1;", "let a = 3;", true).unwrap_err();
+        assert!(err.to_string().contains("starting at lines 1, 3"));
+        assert!(surgeon.apply_patch(path, content, "let b = 2;", "let b = 3;", true).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_apply_edits() {
+        let dir = TempDir::new("apply_edits");
+        std::fs::write(dir.join("a.rs"), "let x = 1;\n").unwrap();
+        std::fs::write(dir.join("b.rs"), "let mut y = 2;\n").unwrap();
+
+        let surgeon = FileSurgeon::new();
+        let edit = |file: &str, start, end, replacement: &str| (dir.join(file), start, end, replacement.to_string());
+        let applied = surgeon.apply_edits(vec![
+            vec![edit("a.rs", 8, 9, "10")],
+            // Overlaps the first fix in a.rs, so its b.rs half must not be applied either
+            vec![edit("b.rs", 0, 0, "// half\n"), edit("a.rs", 4, 9, "z = 0")],
+            vec![edit("b.rs", 4, 8, "")],
+            // Reported twice, applied once
+            vec![edit("b.rs", 4, 8, "")],
+        ]).await.unwrap();
+        assert_eq!(applied.per_file, vec![(dir.join("a.rs"), 1), (dir.join("b.rs"), 1)]);
+        assert_eq!(applied.skipped, 1);
+        assert_eq!(std::fs::read_to_string(dir.join("a.rs")).unwrap(), "let x = 10;\n");
+        assert_eq!(std::fs::read_to_string(dir.join("b.rs")).unwrap(), "let y = 2;\n");
+
+        // A missing file fails the batch before anything is written
+        let err = surgeon.apply_edits(vec![
+            vec![edit("a.rs", 8, 10, "3")],
+            vec![edit("missing.rs", 0, 0, "x")],
+        ]).await.unwrap_err();
+        assert!(format!("{:#}", err).contains("NO files were changed"));
+        assert_eq!(std::fs::read_to_string(dir.join("a.rs")).unwrap(), "let x = 10;\n");
+    }
+
+    #[tokio::test]
+    async fn test_patch_files_merges_path_spellings() {
+        let dir = TempDir::new("patch_aliases");
+        std::fs::create_dir_all(dir.join("src")).unwrap();
+        let file = dir.join("src/a.rs");
+        std::fs::write(&file, "let a = 1;\nlet b = 2;\n").unwrap();
+
+        let op = |path: PathBuf, from: &str, to: &str| PatchFileRequest {
+            path: path.display().to_string(),
+            original_snippet: from.to_string(),
+            modified_snippet: to.to_string(),
+            regex: None,
+            require_unique: None,
+        };
+        let summaries = FileSurgeon::new().patch_files(vec![
+            op(file.clone(), "let a = 1;", "let a = 10;"),
+            op(dir.join("src/../src/a.rs"), "let b = 2;", "let b = 20;"),
+        ]).await.unwrap();
+
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].operations, 2);
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "let a = 10;\nlet b = 20;\n");
+    }
+
+    #[tokio::test]
+    async fn test_create_dir_and_touch_file() {
+        let dir = TempDir::new("create_paths");
+        let surgeon = FileSurgeon::new();
+
+        let nested = dir.join("src/tools/net");
+        assert!(surgeon.create_dir(nested.clone()).await.unwrap().created);
+        assert!(!surgeon.create_dir(nested.clone()).await.unwrap().created);
+
+        let file = dir.join("src/other/http.rs");
+        assert!(surgeon.touch_file(file.clone()).await.unwrap().created);
+        std::fs::write(&file, "fn a() {}\n").unwrap();
+        // Existing files are left alone
+        assert!(!surgeon.touch_file(file.clone()).await.unwrap().created);
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "fn a() {}\n");
+
+        assert!(surgeon.create_dir(file).await.is_err());
+        assert!(surgeon.touch_file(nested).await.is_err());
+        assert!(surgeon.create_dir(PathBuf::from("relative/dir")).await.is_err());
+    }
+}
@@ -0,0 +1,118 @@
+// src/tools/symbols.rs
+use std::path::Path;
+use anyhow::Result;
+use rmcp::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use rmcp::schemars;
+use syn::visit::{self, Visit};
+use crate::utils::IgnoreList;
+use crate::utils::walk::rust_files;
+use crate::utils::ToolError;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct FindReferencesRequest {
+    #[schemars(description = "Absolute path to the project root (all .rs files below it are searched)")]
+    pub path: String,
+    #[schemars(description = "Identifier to search for (e.g., 'patch_file', 'RustPaths')")]
+    pub name: String,
+}
+
+/// A single usage site of an identifier.
+#[derive(Debug, Serialize, Clone)]
+pub struct Reference {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    pub kind: String, // "path", "method_call", "field"
+    pub text: String, // The source line, trimmed
+}
+
+pub struct SymbolIndex {
+    ignore: IgnoreList,
+}
+
+impl SymbolIndex {
+    /// Stop collecting after this many hits to keep the response small
+    const MAX_REFERENCES: usize = 500;
+
+    pub fn new(ignore: IgnoreList) -> Self {
+        Self { ignore }
+    }
+
+    /// Finds usages of `name` in all `.rs` files below `root`.
+    ///
+    /// This is purely syntactic: it matches by identifier (last path segment, method
+    /// name or field name) without type resolution, so unrelated items or shadowed
+    /// bindings with the same name are reported too.
+    pub fn find_references(&self, root: &Path, name: &str) -> Result<Vec<Reference>> {
+        if !root.exists() {
+            return Err(ToolError::NotFound(format!("Path '{}' does not exist", root.display())).into());
+        }
+
+        let mut references = Vec::new();
+
+        for file in rust_files(root, &self.ignore) {
+            // Files that don't parse (e.g. work in progress) are skipped, not fatal
+            let Ok(content) = std::fs::read_to_string(&file) else { continue };
+            let Ok(syntax) = syn::parse_file(&content) else { continue };
+
+            let mut visitor = ReferenceVisitor { name, hits: Vec::new() };
+            visitor.visit_file(&syntax);
+
+            let lines: Vec<&str> = content.lines().collect();
+            let display = file.strip_prefix(root).unwrap_or(&file).display().to_string();
+
+            for (line, column, kind) in visitor.hits {
+                references.push(Reference {
+                    file: display.clone(),
+                    line,
+                    column: column + 1,
+                    kind: kind.to_string(),
+                    text: lines.get(line.saturating_sub(1)).map(|l| l.trim().to_string()).unwrap_or_default(),
+                });
+
+                if references.len() >= Self::MAX_REFERENCES {
+                    return Ok(references);
+                }
+            }
+        }
+
+        Ok(references)
+    }
+}
+
+/// Collects (line, column, kind) for every syntactic use of `name`.
+struct ReferenceVisitor<'a> {
+    name: &'a str,
+    hits: Vec<(usize, usize, &'static str)>,
+}
+
+impl<'a, 'ast> Visit<'ast> for ReferenceVisitor<'a> {
+    fn visit_path(&mut self, path: &'ast syn::Path) {
+        if let Some(last) = path.segments.last() {
+            if last.ident == self.name {
+                let start = last.ident.span().start();
+                self.hits.push((start.line, start.column, "path"));
+            }
+        }
+        visit::visit_path(self, path);
+    }
+
+    fn visit_expr_method_call(&mut self, call: &'ast syn::ExprMethodCall) {
+        if call.method == self.name {
+            let start = call.method.span().start();
+            self.hits.push((start.line, start.column, "method_call"));
+        }
+        visit::visit_expr_method_call(self, call);
+    }
+
+    fn visit_expr_field(&mut self, field: &'ast syn::ExprField) {
+        if let syn::Member::Named(ident) = &field.member {
+            if ident == self.name {
+                let start = ident.span().start();
+                self.hits.push((start.line, start.column, "field"));
+            }
+        }
+        visit::visit_expr_field(self, field);
+    }
+}
@@ -1,17 +1,48 @@
 // src/tools/testing.rs
 use std::path::PathBuf;
-use tokio::process::Command;
+use std::time::Duration;
 use anyhow::{Context, Result};
 use rmcp::schemars::JsonSchema;
 use serde::Deserialize;
+use tokio_util::sync::CancellationToken;
+use tracing::Instrument;
+use tokio::sync::mpsc::UnboundedSender;
+use crate::utils::process::{capped_output, cargo_command, output_streaming, output_with_timeout, ProcessOutcome};
+use crate::utils::{FeatureFlags, ToolError};
 use::rmcp::schemars;
 
 #[derive(Deserialize, JsonSchema)]
 pub struct RunTestsRequest {
     #[schemars(description = "Absolute path to the project root")]
     pub path: String,
+    #[schemars(description = "Optional: Workspace member (package name or directory, see 'list_workspace_members') to test instead of the root")]
+    pub member: Option<String>,
     #[schemars(description = "Optional filter: Name of the test or module (e.g., 'tests::my_test')")]
     pub filter: Option<String>,
+    #[schemars(description = "Optional: Build and run the tests with optimizations (--release). Default false.")]
+    pub release: Option<bool>,
+    #[schemars(description = "Optional: Also run #[ignore] tests (--include-ignored). These are often slow or need special environments (network, databases). Default false.")]
+    pub include_ignored: Option<bool>,
+    #[schemars(description = "Optional: Activate all features (--all-features). Default false.")]
+    pub all_features: Option<bool>,
+    #[schemars(description = "Optional: Disable the default features (--no-default-features). Default false.")]
+    pub no_default_features: Option<bool>,
+    #[schemars(description = "Optional: Features to activate, e.g. [\"serde\", \"tokio/full\"] (--features)")]
+    pub features: Option<Vec<String>>,
+    #[schemars(description = "Optional: Timeout in seconds; cargo is killed afterwards (default: server's --command-timeout-secs)")]
+    pub timeout_secs: Option<u64>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct RunDoctestRequest {
+    #[schemars(description = "Absolute path to the project root")]
+    pub path: String,
+    #[schemars(description = "Optional: Workspace member (package name or directory, see 'list_workspace_members') to test instead of the root")]
+    pub member: Option<String>,
+    #[schemars(description = "Item path whose doc examples to run, e.g. 'parser::Parser::parse' or 'Config'. Matched as a substring of the doctest names ('src/lib.rs - parser::Parser::parse (line 42)').")]
+    pub item: String,
+    #[schemars(description = "Optional: Timeout in seconds; cargo is killed afterwards (default: server's --command-timeout-secs)")]
+    pub timeout_secs: Option<u64>,
 }
 
 pub struct TestRunner;
@@ -22,49 +53,180 @@ impl TestRunner {
     }
 
     /// Runs cargo test in the specified directory.
-    /// Returns the combined stdout/stderr output.
-    pub async fn run(&self, project_path: PathBuf, filter: Option<String>) -> Result<String> {
+    /// Returns the combined stdout/stderr output, or a short notice if `cancel` fired.
+    /// If `progress` is set, output lines are forwarded live while the tests run.
+    pub async fn run(
+        &self,
+        project_path: PathBuf,
+        filter: Option<String>,
+        release: bool,
+        include_ignored: bool,
+        features: &FeatureFlags,
+        cancel: &CancellationToken,
+        timeout: Duration,
+        progress: Option<UnboundedSender<String>>,
+    ) -> Result<String> {
         // 1. Validation
         if !project_path.exists() {
-            anyhow::bail!("Path '{}' does not exist", project_path.display());
+            return Err(ToolError::NotFound(format!("Path '{}' does not exist", project_path.display())).into());
         }
 
         // Simple heuristic: It needs a Cargo.toml to be a testable project
         if !project_path.join("Cargo.toml").exists() {
-            anyhow::bail!("No Cargo.toml found at '{}'. cannot run tests.", project_path.display());
+            return Err(ToolError::NotFound(format!("No Cargo.toml found at '{}'. cannot run tests.", project_path.display())).into());
         }
 
         // 2. Build Command
-        let mut cmd = Command::new("cargo");
+        let mut cmd = cargo_command();
         cmd.current_dir(&project_path)
             .arg("test")
             .arg("--color").arg("never"); // Optimization: Plain text output for AI
 
+        // Some bugs only show up with optimizations
+        if release {
+            cmd.arg("--release");
+        }
+        features.apply(&mut cmd);
+
         // 3. Apply Filter (e.g. "tests::test_authentication")
+        let mut has_filter = false;
         if let Some(test_name) = filter {
             if !test_name.trim().is_empty() {
                 cmd.arg(&test_name);
+                has_filter = true;
             }
         }
 
+        // Test-harness flags go after `--`
+        if include_ignored {
+            cmd.arg("--").arg("--include-ignored");
+        }
+
         // 4. Execute
         // We capture output regardless of success/failure.
         // A failed test returns a non-zero exit code, but we WANT that output.
-        let output = cmd.output().await.context("Failed to execute 'cargo test'")?;
+        let span = tracing::info_span!("cargo_test", path = %project_path.display(), release);
+        let outcome = match progress {
+            Some(lines) => output_streaming(cmd, cancel, timeout, lines).instrument(span).await,
+            None => output_with_timeout(cmd, cancel, timeout).instrument(span).await,
+        };
+
+        let output = match outcome.context("Failed to execute 'cargo test'")?
+        {
+            ProcessOutcome::Completed(output) => output,
+            ProcessOutcome::Cancelled => return Ok("Tests cancelled by client. No results.".to_string()),
+            ProcessOutcome::TimedOut(limit) => return Err(ToolError::Timeout(format!("'cargo test' timed out after {}s", limit.as_secs())).into()),
+        };
 
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let stderr = String::from_utf8_lossy(&output.stderr);
+        let raw_stdout = String::from_utf8_lossy(&output.stdout);
+        let ignored = Self::ignored_count(&raw_stdout);
+        let filtered_out_all = has_filter && output.status.success() && Self::executed_count(&raw_stdout) == 0;
+        let stdout = capped_output(&output.stdout);
+        let stderr = capped_output(&output.stderr);
 
         // 5. Format Output
-        let status_msg = if output.status.success() {
-            "Tests passed!"
+        let mut status_msg = if filtered_out_all {
+            "No tests run: nothing matched the filter. Check the test name (e.g. 'tests::my_test').".to_string()
+        } else if output.status.success() {
+            "Tests passed!".to_string()
         } else {
-            "Tests failed."
+            "Tests failed.".to_string()
         };
+        if release {
+            status_msg.push_str(" (release build)");
+        }
+        if ignored > 0 && !include_ignored {
+            status_msg.push_str(&format!(
+                "\nNote: {} test(s) are marked #[ignore] and were NOT run. Use 'include_ignored' to run them.",
+                ignored
+            ));
+        }
 
         Ok(format!(
             "{}\n\n=== STDOUT ===\n{}\n=== STDERR ===\n{}",
             status_msg, stdout, stderr
         ))
     }
+
+    /// Runs only the doc examples whose doctest name contains `item` (`cargo test --doc <item>`).
+    pub async fn run_doctest(&self, project_path: PathBuf, item: &str, cancel: &CancellationToken, timeout: Duration) -> Result<String> {
+        if !project_path.join("Cargo.toml").exists() {
+            return Err(ToolError::NotFound(format!("No Cargo.toml found at '{}'. cannot run doctests.", project_path.display())).into());
+        }
+
+        // Doctest names are relative to the crate root: "src/lib.rs - parser::Parser (line 3)"
+        let item = item.trim().trim_start_matches("crate::");
+        if item.is_empty() {
+            return Err(ToolError::InvalidInput("'item' must name the documented item, e.g. 'parser::Parser::parse'.".to_string()).into());
+        }
+
+        let mut cmd = cargo_command();
+        cmd.current_dir(&project_path)
+            .arg("test")
+            .arg("--doc")
+            .arg("--color").arg("never")
+            .arg(item);
+
+        let span = tracing::info_span!("cargo_doctest", path = %project_path.display(), item);
+        let output = match output_with_timeout(cmd, cancel, timeout).instrument(span).await.context("Failed to execute 'cargo test --doc'")?
+        {
+            ProcessOutcome::Completed(output) => output,
+            ProcessOutcome::Cancelled => return Ok("Doctests cancelled by client. No results.".to_string()),
+            ProcessOutcome::TimedOut(limit) => return Err(ToolError::Timeout(format!("'cargo test --doc' timed out after {}s", limit.as_secs())).into()),
+        };
+
+        let stdout = capped_output(&output.stdout);
+        let stderr = capped_output(&output.stderr);
+
+        let status_msg = if output.status.success() && Self::executed_count(&String::from_utf8_lossy(&output.stdout)) == 0 {
+            format!(
+                "No doctest matches '{}'. Doc examples only exist on public items of library crates; check the item path (without the crate name).",
+                item
+            )
+        } else if output.status.success() {
+            format!("Doctests for '{}' passed!", item)
+        } else {
+            format!("Doctests for '{}' failed.", item)
+        };
+
+        Ok(format!(
+            "{}\n\n=== STDOUT ===\n{}\n=== STDERR ===\n{}",
+            status_msg, stdout, stderr
+        ))
+    }
+
+    /// Sums "N passed" and "N failed" over all `test result:` summary lines; 0 means no test ran.
+    fn executed_count(stdout: &str) -> usize {
+        Self::summary_count(stdout, " passed") + Self::summary_count(stdout, " failed")
+    }
+
+    /// Sums "N ignored" over all `test result:` summary lines (one per test binary).
+    fn ignored_count(stdout: &str) -> usize {
+        Self::summary_count(stdout, " ignored")
+    }
+
+    fn summary_count(stdout: &str, suffix: &str) -> usize {
+        stdout.lines()
+            .filter(|line| line.starts_with("test result:"))
+            .flat_map(|line| line.split(';'))
+            .filter_map(|part| part.trim().strip_suffix(suffix))
+            .filter_map(|n| n.rsplit(' ').next()?.parse::<usize>().ok())
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary_counts() {
+        let stdout = "running 2 tests\ntest result: ok. 2 passed; 0 failed; 1 ignored; 0 measured; 0 filtered out; finished in 0.01s\n\
+                      test result: ok. 0 passed; 0 failed; 0 ignored; 0 measured; 5 filtered out; finished in 0.00s\n";
+        assert_eq!(TestRunner::executed_count(stdout), 2);
+        assert_eq!(TestRunner::ignored_count(stdout), 1);
+
+        let none = "test result: ok. 0 passed; 0 failed; 0 ignored; 0 measured; 3 filtered out; finished in 0.00s\n";
+        assert_eq!(TestRunner::executed_count(none), 0);
+    }
 }
\ No newline at end of file
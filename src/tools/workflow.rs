@@ -0,0 +1,263 @@
+// src/tools/workflow.rs
+use std::collections::HashMap;
+use std::path::PathBuf;
+use anyhow::Result;
+use rmcp::schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use rmcp::schemars;
+use tokio_util::sync::CancellationToken;
+use crate::tools::surgeon::PatchFileRequest;
+use crate::tools::{CargoChecker, CodePolisher, FileSurgeon, GitController};
+use crate::utils::process::command_timeout;
+use crate::utils::{FeatureFlags, ToolError};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct CheckedEditRequest {
+    #[schemars(description = "Absolute path to the project root (must be a git repository)")]
+    pub path: String,
+    #[schemars(description = "The patch to apply ({path, original_snippet, modified_snippet, regex}), as for 'patch_file'")]
+    pub patch: PatchFileRequest,
+    #[schemars(description = "Optional: Message of the safety commit made before patching (default 'WIP: Before checked edit'). It contains the tracked files and the patched file; untracked files are left out.")]
+    pub message: Option<String>,
+    #[schemars(description = "Optional: Run 'cargo fmt' after patching (default true)")]
+    pub fmt: Option<bool>,
+    #[schemars(description = "Optional: Timeout in seconds for each git/cargo step (default: server's --command-timeout-secs)")]
+    pub timeout_secs: Option<u64>,
+}
+
+/// Outcome of one step of the edit loop
+#[derive(Debug, Serialize)]
+pub struct EditStep {
+    pub step: String,
+    pub ok: bool,
+    pub output: serde_json::Value,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CheckedEditReport {
+    /// `applied`, `reverted` (check failed, back at `base_commit`), `patch_failed` or `aborted`
+    pub status: String,
+    /// Commit the edit started from; `reverted` resets to it
+    pub base_commit: Option<String>,
+    pub steps: Vec<EditStep>,
+}
+
+/// The safe-edit loop (commit -> patch -> fmt -> check -> revert on errors) as one operation.
+pub struct CheckedEditor;
+
+impl CheckedEditor {
+    pub fn new() -> Self { Self }
+
+    /// Commits the current state, applies the patch, formats and checks the project.
+    /// If the check reports errors (or does not finish), the project is reset to the
+    /// safety commit, so the tree is either patched and compiling or unchanged.
+    pub async fn run(
+        &self,
+        request: CheckedEditRequest,
+        git: &GitController,
+        surgeon: &FileSurgeon,
+        polisher: &CodePolisher,
+        checker: &CargoChecker,
+        cancel: &CancellationToken,
+    ) -> Result<CheckedEditReport> {
+        let CheckedEditRequest { path, patch, message, fmt, timeout_secs } = request;
+        let timeout = command_timeout(timeout_secs);
+        let root = PathBuf::from(path);
+        if !root.join(".git").exists() {
+            return Err(ToolError::InvalidInput(format!(
+                "'{}' is not a git repository; run_checked_edit needs git to revert a failed edit.",
+                root.display()
+            )).into());
+        }
+
+        let mut report = CheckedEditReport {
+            status: "aborted".to_string(),
+            base_commit: None,
+            steps: Vec::new(),
+        };
+
+        // 1. Safety commit. Without it a revert would discard the caller's uncommitted work.
+        // Only tracked files (and the patch target, so a revert covers it) are staged: a plain
+        // `git add .` would sweep build output or local secrets into the WIP commit.
+        let message = message.unwrap_or_else(|| "WIP: Before checked edit".to_string());
+        let file = PathBuf::from(&patch.path);
+        match git.commit_tracked(root.clone(), message, std::slice::from_ref(&file), cancel, timeout).await {
+            Ok(output) => report.steps.push(Self::step("commit", true, &output)),
+            Err(e) => {
+                report.steps.push(Self::step("commit", false, &format!("{:#}", e)));
+                return Ok(report);
+            }
+        }
+        report.base_commit = git.head(&root, cancel, timeout).await?;
+        let Some(base) = report.base_commit.clone() else {
+            return Ok(report);
+        };
+
+        // 2. Patch. A failed patch writes nothing, so there is nothing to revert.
+        let patched = if patch.regex.unwrap_or(false) {
+            surgeon.patch_file_regex(file, &patch.original_snippet, &patch.modified_snippet).await
+        } else {
+            surgeon.patch_file(file, &patch.original_snippet, &patch.modified_snippet, patch.require_unique.unwrap_or(false)).await
+        };
+        match patched {
+            Ok(output) => report.steps.push(Self::step("patch", true, &output)),
+            Err(e) => {
+                report.steps.push(Self::step("patch", false, &e.to_string()));
+                report.status = "patch_failed".to_string();
+                return Ok(report);
+            }
+        }
+
+        // 3. Format. A failure here is usually a syntax error, which the check reports in detail.
+        if fmt.unwrap_or(true) {
+            let formatted = polisher.run_fmt(root.clone(), cancel, timeout).await;
+            report.steps.push(match formatted {
+                Ok(output) => Self::step("fmt", !output.starts_with('❌'), &output),
+                Err(e) => Self::step("fmt", false, &e.to_string()),
+            });
+        }
+
+        // 4. Check, reverting unless it passed
+        let passed = match checker.check(root.clone(), false, &FeatureFlags::default(), &HashMap::new(), cancel, timeout).await {
+            Ok(result) => {
+                let passed = !result.has_errors && !result.cancelled;
+                report.steps.push(EditStep {
+                    step: "check".to_string(),
+                    ok: passed,
+                    output: serde_json::to_value(&result)?,
+                });
+                passed
+            }
+            Err(e) => {
+                report.steps.push(Self::step("check", false, &e.to_string()));
+                false
+            }
+        };
+
+        if passed {
+            report.status = "applied".to_string();
+        } else {
+            // The revert must finish even when the client cancelled the check that triggered it
+            report.steps.push(match git.reset_to(root, &base, &CancellationToken::new(), timeout).await {
+                Ok(output) => Self::step("revert", true, &output),
+                Err(e) => Self::step("revert", false, &format!("{:#}", e)),
+            });
+            report.status = "reverted".to_string();
+        }
+
+        Ok(report)
+    }
+
+    /// Step whose output is JSON (e.g. from `commit`) or plain text
+    fn step(name: &str, ok: bool, output: &str) -> EditStep {
+        EditStep {
+            step: name.to_string(),
+            ok,
+            output: serde_json::from_str(output).unwrap_or_else(|_| serde_json::Value::String(output.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_support::TempDir;
+
+    const LIB: &str = "pub fn answer() -> u32 {\n    42\n}\n";
+
+    fn git(dir: &TempDir, args: &[&str]) -> String {
+        let output = std::process::Command::new("git").current_dir(dir.path()).args(args).output().unwrap();
+        assert!(output.status.success(), "git {:?} failed", args);
+        String::from_utf8_lossy(&output.stdout).to_string()
+    }
+
+    /// Committed one-file crate plus an untracked file that must stay out of the safety commit
+    fn project(name: &str) -> TempDir {
+        let dir = TempDir::new(name);
+        std::fs::create_dir_all(dir.join("src")).unwrap();
+        std::fs::write(dir.join("Cargo.toml"), "[package]\nname = \"demo\"\nversion = \"0.1.0\"\nedition = \"2021\"\n").unwrap();
+        std::fs::write(dir.join("src/lib.rs"), LIB).unwrap();
+        git(&dir, &["init", "-q"]);
+        git(&dir, &["config", "user.name", "Test"]);
+        git(&dir, &["config", "user.email", "test@example.com"]);
+        git(&dir, &["add", "."]);
+        git(&dir, &["commit", "-q", "-m", "init"]);
+        std::fs::write(dir.join("secret.env"), "TOKEN=1\n").unwrap();
+        dir
+    }
+
+    async fn edit(dir: &TempDir, from: &str, to: &str) -> CheckedEditReport {
+        let request = CheckedEditRequest {
+            path: dir.path().display().to_string(),
+            patch: PatchFileRequest {
+                path: dir.join("src/lib.rs").display().to_string(),
+                original_snippet: from.to_string(),
+                modified_snippet: to.to_string(),
+                regex: None,
+                require_unique: None,
+            },
+            message: None,
+            fmt: None,
+            timeout_secs: Some(120),
+        };
+        CheckedEditor::new()
+            .run(request, &GitController::new(), &FileSurgeon::new(), &CodePolisher::new(), &CargoChecker::new(), &CancellationToken::new())
+            .await
+            .unwrap()
+    }
+
+    fn steps(report: &CheckedEditReport) -> Vec<(&str, bool)> {
+        report.steps.iter().map(|s| (s.step.as_str(), s.ok)).collect()
+    }
+
+    #[tokio::test]
+    async fn test_checked_edit_applied() {
+        let dir = project("edit_applied");
+        let report = edit(&dir, "42", "43").await;
+
+        assert_eq!(report.status, "applied");
+        assert_eq!(steps(&report), vec![("commit", true), ("patch", true), ("fmt", true), ("check", true)]);
+        assert!(std::fs::read_to_string(dir.join("src/lib.rs")).unwrap().contains("43"));
+        assert!(!git(&dir, &["ls-files"]).contains("secret.env"));
+    }
+
+    #[tokio::test]
+    async fn test_checked_edit_reverted() {
+        let dir = project("edit_reverted");
+        let report = edit(&dir, "42", "\"forty-two\"").await;
+
+        assert_eq!(report.status, "reverted");
+        assert_eq!(steps(&report).last(), Some(&("revert", true)));
+        assert_eq!(std::fs::read_to_string(dir.join("src/lib.rs")).unwrap(), LIB);
+        // The reset leaves untracked files alone
+        assert!(dir.join("secret.env").exists());
+    }
+
+    #[tokio::test]
+    async fn test_checked_edit_patch_failed() {
+        let dir = project("edit_patch_failed");
+        let report = edit(&dir, "fn missing()", "fn other()").await;
+
+        assert_eq!(report.status, "patch_failed");
+        assert_eq!(steps(&report), vec![("commit", true), ("patch", false)]);
+        assert_eq!(std::fs::read_to_string(dir.join("src/lib.rs")).unwrap(), LIB);
+    }
+
+    #[tokio::test]
+    async fn test_checked_edit_aborted() {
+        let dir = project("edit_aborted");
+        // A rejecting hook makes the safety commit fail
+        let hook = dir.join(".git/hooks/pre-commit");
+        std::fs::write(&hook, "#!/bin/sh\nexit 1\n").unwrap();
+        std::fs::set_permissions(&hook, std::os::unix::fs::PermissionsExt::from_mode(0o755)).unwrap();
+        std::fs::write(dir.join("src/lib.rs"), LIB.replace("42", "41")).unwrap();
+
+        let report = edit(&dir, "41", "43").await;
+
+        assert_eq!(report.status, "aborted");
+        assert_eq!(steps(&report), vec![("commit", false)]);
+        assert!(report.steps[0].output.as_str().unwrap().contains("'git commit' failed"));
+        assert!(report.base_commit.is_none());
+        assert!(std::fs::read_to_string(dir.join("src/lib.rs")).unwrap().contains("41"));
+    }
+}
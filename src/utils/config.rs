@@ -0,0 +1,173 @@
+use std::env;
+use std::time::Duration;
+
+/// Server-wide settings, read once at startup from CLI flags and environment variables.
+/// CLI flags take precedence over environment variables.
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    /// Maximum number of cargo processes (check/test/fmt/clippy/add) running at the same time
+    pub max_cargo_jobs: usize,
+    /// Directory names skipped by walking tools; `None` means `walk::default_ignores()`
+    pub ignore_dirs: Option<Vec<String>>,
+    /// Per-stream cap for command output returned to the client
+    pub max_output_bytes: usize,
+    /// Total attempts for cargo commands that fail with a transient network error (1 = no retry)
+    pub cargo_attempts: u32,
+    /// Default limit for every external command (cargo, git, rustfmt); requests may override it
+    pub command_timeout: Duration,
+    /// Refuse every tool that changes files, the manifest or git history
+    pub read_only: bool,
+    /// Subcommands `cargo_command` may run; `None` means its built-in default list
+    pub cargo_allowlist: Option<Vec<String>>,
+    /// Doc sets below the toolchain's `share/doc/rust/html` to index (`std`, `reference`, `book`, `nomicon`, ...)
+    pub doc_sources: Vec<String>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            max_cargo_jobs: std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1),
+            ignore_dirs: None,
+            max_output_bytes: crate::utils::process::DEFAULT_MAX_OUTPUT_BYTES,
+            cargo_attempts: crate::utils::process::DEFAULT_CARGO_ATTEMPTS,
+            command_timeout: crate::utils::process::DEFAULT_COMMAND_TIMEOUT,
+            read_only: false,
+            cargo_allowlist: None,
+            doc_sources: crate::tools::RustDocsSearcher::DEFAULT_SOURCES.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+impl ServerConfig {
+    /// Reads the configuration from `std::env::args()` and the environment
+    pub fn load() -> Self {
+        let args: Vec<String> = env::args().skip(1).collect();
+        Self::from_sources(&args, |key| env::var(key).ok())
+    }
+
+    fn from_sources(args: &[String], get_env: impl Fn(&str) -> Option<String>) -> Self {
+        let mut config = Self::default();
+
+        // 1. Environment
+        if let Some(jobs) = get_env("MCP_MAX_CARGO_JOBS").and_then(|v| v.parse().ok()) {
+            config.max_cargo_jobs = jobs;
+        }
+
+        // 2. CLI (overrides environment)
+        if let Some(jobs) = Self::flag_value(args, "--max-cargo-jobs").and_then(|v| v.parse().ok()) {
+            config.max_cargo_jobs = jobs;
+        }
+
+        if let Some(bytes) = get_env("MCP_MAX_OUTPUT_BYTES").and_then(|v| v.parse().ok()) {
+            config.max_output_bytes = bytes;
+        }
+        if let Some(bytes) = Self::flag_value(args, "--max-output-bytes").and_then(|v| v.parse().ok()) {
+            config.max_output_bytes = bytes;
+        }
+
+        if let Some(attempts) = get_env("MCP_CARGO_ATTEMPTS").and_then(|v| v.parse().ok()) {
+            config.cargo_attempts = attempts;
+        }
+        if let Some(attempts) = Self::flag_value(args, "--cargo-attempts").and_then(|v| v.parse().ok()) {
+            config.cargo_attempts = attempts;
+        }
+
+        if let Some(secs) = get_env("MCP_COMMAND_TIMEOUT_SECS").and_then(|v| v.parse().ok()) {
+            config.command_timeout = Duration::from_secs(secs);
+        }
+        if let Some(secs) = Self::flag_value(args, "--command-timeout-secs").and_then(|v| v.parse().ok()) {
+            config.command_timeout = Duration::from_secs(secs);
+        }
+
+        if get_env("MCP_READ_ONLY").is_some_and(|v| v == "1" || v.eq_ignore_ascii_case("true")) {
+            config.read_only = true;
+        }
+        if args.iter().any(|arg| arg == "--read-only") {
+            config.read_only = true;
+        }
+
+        // Comma-separated, e.g. "target,.git,vendor"
+        let parse_list = |v: String| -> Vec<String> {
+            v.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect()
+        };
+        if let Some(dirs) = get_env("MCP_IGNORE_DIRS") {
+            config.ignore_dirs = Some(parse_list(dirs));
+        }
+        if let Some(dirs) = Self::flag_value(args, "--ignore-dirs") {
+            config.ignore_dirs = Some(parse_list(dirs));
+        }
+        if let Some(subcommands) = get_env("MCP_CARGO_ALLOWLIST") {
+            config.cargo_allowlist = Some(parse_list(subcommands));
+        }
+        if let Some(subcommands) = Self::flag_value(args, "--cargo-allowlist") {
+            config.cargo_allowlist = Some(parse_list(subcommands));
+        }
+        if let Some(sources) = get_env("MCP_DOC_SOURCES") {
+            config.doc_sources = parse_list(sources);
+        }
+        if let Some(sources) = Self::flag_value(args, "--doc-sources") {
+            config.doc_sources = parse_list(sources);
+        }
+
+        // A limit of 0 would block every cargo tool forever
+        config.max_cargo_jobs = config.max_cargo_jobs.max(1);
+        config.cargo_attempts = config.cargo_attempts.max(1);
+
+        config
+    }
+
+    /// Supports both `--flag value` and `--flag=value`
+    fn flag_value(args: &[String], flag: &str) -> Option<String> {
+        let prefix = format!("{}=", flag);
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            if arg == flag {
+                return iter.next().cloned();
+            }
+            if let Some(value) = arg.strip_prefix(&prefix) {
+                return Some(value.to_string());
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cli_overrides_env() {
+        let args = vec!["--max-cargo-jobs".to_string(), "2".to_string()];
+        let config = ServerConfig::from_sources(&args, |_| Some("8".to_string()));
+        assert_eq!(config.max_cargo_jobs, 2);
+
+        let args = vec!["--max-cargo-jobs=0".to_string()];
+        let config = ServerConfig::from_sources(&args, |_| None);
+        assert_eq!(config.max_cargo_jobs, 1);
+
+        let config = ServerConfig::from_sources(&[], |_| Some("3".to_string()));
+        assert_eq!(config.max_cargo_jobs, 3);
+    }
+
+    #[test]
+    fn test_read_only_flag() {
+        assert!(!ServerConfig::from_sources(&[], |_| None).read_only);
+        assert!(ServerConfig::from_sources(&["--read-only".to_string()], |_| None).read_only);
+        assert!(ServerConfig::from_sources(&[], |key| (key == "MCP_READ_ONLY").then(|| "true".to_string())).read_only);
+    }
+
+    #[test]
+    fn test_ignore_dirs_list() {
+        let args = vec!["--ignore-dirs".to_string(), "vendor, ,dist".to_string()];
+        let config = ServerConfig::from_sources(&args, |_| None);
+        assert_eq!(config.ignore_dirs, Some(vec!["vendor".to_string(), "dist".to_string()]));
+
+        let ignore = crate::utils::IgnoreList::new(config.ignore_dirs.as_deref());
+        assert!(ignore.is_ignored("vendor"));
+        assert!(ignore.is_ignored("target"));
+        assert!(!ignore.is_ignored("node_modules"));
+    }
+}
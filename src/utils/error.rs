@@ -0,0 +1,96 @@
+use std::fmt;
+use rmcp::model::ErrorCode;
+use rmcp::ErrorData as McpError;
+
+/// Failure categories that clients can branch on.
+///
+/// Tool modules keep returning `anyhow::Result` and raise these via
+/// `Err(ToolError::NotFound(..).into())`; `to_mcp_error` recovers the category at the
+/// tool boundary. Errors without a category (I/O, parsing, ...) become `internal`.
+#[derive(Debug)]
+pub enum ToolError {
+    /// A file, crate, project or item does not exist
+    NotFound(String),
+    /// The arguments are malformed or don't match the current state (e.g. a patch snippet)
+    InvalidInput(String),
+    /// An external command (cargo, git, rustfmt, ...) ran but failed
+    CommandFailed(String),
+    /// An external command was killed after its time limit
+    Timeout(String),
+}
+
+impl ToolError {
+    /// Stable identifier sent to clients as `data.kind`
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ToolError::NotFound(_) => "not_found",
+            ToolError::InvalidInput(_) => "invalid_input",
+            ToolError::CommandFailed(_) => "command_failed",
+            ToolError::Timeout(_) => "timeout",
+        }
+    }
+
+    fn code(&self) -> ErrorCode {
+        match self {
+            ToolError::NotFound(_) => ErrorCode::RESOURCE_NOT_FOUND,
+            ToolError::InvalidInput(_) => ErrorCode::INVALID_PARAMS,
+            ToolError::CommandFailed(_) | ToolError::Timeout(_) => ErrorCode::INTERNAL_ERROR,
+        }
+    }
+}
+
+impl fmt::Display for ToolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ToolError::NotFound(msg)
+            | ToolError::InvalidInput(msg)
+            | ToolError::CommandFailed(msg)
+            | ToolError::Timeout(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl std::error::Error for ToolError {}
+
+/// Converts a tool error into an MCP error with a matching code and `data: { "kind": ... }`.
+/// The message includes the full context chain (`outer: inner`).
+pub fn to_mcp_error(err: anyhow::Error) -> McpError {
+    let message = format!("{:#}", err);
+
+    if let Some(tool_error) = err.chain().find_map(|e| e.downcast_ref::<ToolError>()) {
+        return McpError::new(tool_error.code(), message, Some(serde_json::json!({ "kind": tool_error.kind() })));
+    }
+
+    // Plain I/O "not found" (e.g. from `read_to_string(..).context(..)`) is still a not-found
+    let io_not_found = err.chain()
+        .filter_map(|e| e.downcast_ref::<std::io::Error>())
+        .any(|e| e.kind() == std::io::ErrorKind::NotFound);
+    if io_not_found {
+        return McpError::new(ErrorCode::RESOURCE_NOT_FOUND, message, Some(serde_json::json!({ "kind": "not_found" })));
+    }
+
+    McpError::new(ErrorCode::INTERNAL_ERROR, message, Some(serde_json::json!({ "kind": "internal" })))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_to_mcp_error_kinds() {
+        let err: anyhow::Result<()> = Err(ToolError::NotFound("File 'x' not found".into()));
+        let mcp = to_mcp_error(err.context("Operation #1 failed").unwrap_err());
+        assert_eq!(mcp.code, ErrorCode::RESOURCE_NOT_FOUND);
+        assert_eq!(mcp.message, "Operation #1 failed: File 'x' not found");
+        assert_eq!(mcp.data, Some(serde_json::json!({ "kind": "not_found" })));
+
+        let mcp = to_mcp_error(ToolError::InvalidInput("bad".into()).into());
+        assert_eq!(mcp.code, ErrorCode::INVALID_PARAMS);
+
+        let mcp = to_mcp_error(anyhow::anyhow!("boom"));
+        assert_eq!(mcp.code, ErrorCode::INTERNAL_ERROR);
+        assert_eq!(mcp.data, Some(serde_json::json!({ "kind": "internal" })));
+    }
+}
@@ -0,0 +1,61 @@
+use tokio::process::Command;
+
+/// Feature selection for the cargo-running tools (check, test, clippy), taken from the
+/// optional `all_features`, `no_default_features` and `features` request fields.
+#[derive(Debug, Clone, Default)]
+pub struct FeatureFlags {
+    pub all_features: bool,
+    pub no_default_features: bool,
+    pub features: Vec<String>,
+}
+
+impl FeatureFlags {
+    pub fn new(all_features: Option<bool>, no_default_features: Option<bool>, features: Option<Vec<String>>) -> Self {
+        Self {
+            all_features: all_features.unwrap_or(false),
+            no_default_features: no_default_features.unwrap_or(false),
+            features: features
+                .unwrap_or_default()
+                .into_iter()
+                .map(|f| f.trim().to_string())
+                .filter(|f| !f.is_empty())
+                .collect(),
+        }
+    }
+
+    /// `--all-features`, `--no-default-features` and `--features a,b` as cargo arguments
+    pub fn args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if self.all_features {
+            args.push("--all-features".to_string());
+        }
+        if self.no_default_features {
+            args.push("--no-default-features".to_string());
+        }
+        if !self.features.is_empty() {
+            args.push("--features".to_string());
+            args.push(self.features.join(","));
+        }
+        args
+    }
+
+    /// Adds the flags to a cargo command (before any `--`)
+    pub fn apply(&self, cmd: &mut Command) {
+        cmd.args(self.args());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feature_args() {
+        assert!(FeatureFlags::default().args().is_empty());
+
+        let flags = FeatureFlags::new(None, Some(true), Some(vec!["serde".into(), " ".into(), "tokio/full".into()]));
+        assert_eq!(flags.args(), vec!["--no-default-features", "--features", "serde,tokio/full"]);
+
+        assert_eq!(FeatureFlags::new(Some(true), None, None).args(), vec!["--all-features"]);
+    }
+}
@@ -0,0 +1,58 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use tokio_util::sync::CancellationToken;
+use crate::utils::process::{capped_output, cargo_command, output_with_timeout, ProcessOutcome};
+use crate::utils::ToolError;
+
+// Minimal view of `cargo metadata --no-deps --format-version 1`
+#[derive(Deserialize)]
+pub struct Metadata {
+    pub workspace_root: PathBuf,
+    pub packages: Vec<MetadataPackage>,
+}
+
+#[derive(Deserialize)]
+pub struct MetadataPackage {
+    pub name: String,
+    pub version: String,
+    pub manifest_path: PathBuf,
+    #[serde(default)]
+    pub targets: Vec<MetadataTarget>,
+}
+
+#[derive(Deserialize)]
+pub struct MetadataTarget {
+    pub name: String,
+    pub kind: Vec<String>,
+    pub src_path: String,
+    #[serde(default, rename = "required-features")]
+    pub required_features: Vec<String>,
+}
+
+/// Runs `cargo metadata --no-deps` in `dir`. Like every cargo call it is bounded by
+/// `timeout` and stops when `cancel` fires; callers hold a cargo permit around it.
+pub async fn cargo_metadata(dir: &Path, cancel: &CancellationToken, timeout: Duration) -> Result<Metadata> {
+    let mut cmd = cargo_command();
+    cmd.current_dir(dir)
+        .args(["metadata", "--no-deps", "--format-version", "1"]);
+
+    let output = match output_with_timeout(cmd, cancel, timeout)
+        .await
+        .context("Failed to execute 'cargo metadata'")?
+    {
+        ProcessOutcome::Completed(output) => output,
+        ProcessOutcome::Cancelled => return Err(ToolError::CommandFailed("'cargo metadata' was cancelled by client.".to_string()).into()),
+        ProcessOutcome::TimedOut(limit) => return Err(ToolError::Timeout(format!("'cargo metadata' timed out after {}s", limit.as_secs())).into()),
+    };
+
+    if !output.status.success() {
+        return Err(ToolError::CommandFailed(format!(
+            "'cargo metadata' failed:\n{}",
+            capped_output(&output.stderr)
+        )).into());
+    }
+
+    serde_json::from_slice(&output.stdout).context("Failed to parse 'cargo metadata' output")
+}
@@ -1,3 +1,16 @@
 pub mod paths;
+pub mod config;
+pub mod process;
+pub mod text;
+pub mod walk;
+pub mod error;
+pub mod features;
+pub mod metadata;
+#[cfg(test)]
+pub mod test_support;
 
-pub use paths::RustPaths;
\ No newline at end of file
+pub use paths::RustPaths;
+pub use config::ServerConfig;
+pub use walk::IgnoreList;
+pub use error::ToolError;
+pub use features::FeatureFlags;
\ No newline at end of file
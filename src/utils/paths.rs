@@ -6,6 +6,11 @@ pub struct RustPaths {
     pub docs_path: Option<PathBuf>,
     pub cargo_registry: Option<PathBuf>,
     pub rustup_home: Option<PathBuf>,
+    pub rust_src_path: Option<PathBuf>,
+    /// Das cargo-Binary, das alle cargo-basierten Tools verwenden
+    pub cargo_bin: Option<PathBuf>,
+    pub rustc_version: Option<String>,
+    pub cargo_version: Option<String>,
 }
 
 impl RustPaths {
@@ -14,14 +19,37 @@ impl RustPaths {
         let rustup_home = Self::find_rustup_home();
         let docs_path = Self::find_rust_docs(&rustup_home);
         let cargo_registry = Self::find_cargo_registry();
+        let rust_src_path = Self::find_rust_src(&rustup_home);
+        let cargo_bin = Self::find_cargo_bin();
+        let rustc_version = Self::tool_version(std::ffi::OsStr::new("rustc"));
+        let cargo_version = cargo_bin.as_deref().and_then(|bin| Self::tool_version(bin.as_os_str()));
 
         Self {
             docs_path,
             cargo_registry,
             rustup_home,
+            rust_src_path,
+            cargo_bin,
+            rustc_version,
+            cargo_version,
         }
     }
 
+    /// Liest `<program> --version` einmalig beim Start (None, falls nicht im PATH)
+    fn tool_version(program: &std::ffi::OsStr) -> Option<String> {
+        let output = std::process::Command::new(program)
+            .arg("--version")
+            .output()
+            .ok()?;
+
+        if !output.status.success() {
+            return None;
+        }
+
+        let version = String::from_utf8_lossy(&output.stdout);
+        version.lines().next().map(|l| l.trim().to_string())
+    }
+
     /// Findet RUSTUP_HOME (normalerweise ~/.rustup)
     fn find_rustup_home() -> Option<PathBuf> {
         // Erst Umgebungsvariable prüfen
@@ -74,6 +102,47 @@ impl RustPaths {
         None
     }
 
+    /// Findet die Quellen der Standardbibliothek (rust-src Komponente)
+    fn find_rust_src(rustup_home: &Option<PathBuf>) -> Option<PathBuf> {
+        let toolchains_dir = rustup_home.as_ref()?.join("toolchains");
+        let entries = fs::read_dir(toolchains_dir).ok()?;
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let is_stable = path.file_name()
+                .map(|n| n.to_string_lossy().starts_with("stable"))
+                .unwrap_or(false);
+
+            if is_stable {
+                let src_path = path.join("lib/rustlib/src/rust/library");
+                if src_path.exists() {
+                    return Some(src_path);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Findet das cargo-Binary: bevorzugt den von rustup verwalteten Proxy in
+    /// CARGO_HOME/bin (bzw. ~/.cargo/bin), sonst das erste cargo im PATH
+    fn find_cargo_bin() -> Option<PathBuf> {
+        let exe = format!("cargo{}", std::env::consts::EXE_SUFFIX);
+
+        let cargo_home = std::env::var("CARGO_HOME").ok()
+            .map(PathBuf::from)
+            .or_else(|| dirs::home_dir().map(|home| home.join(".cargo")));
+        if let Some(bin) = cargo_home.map(|home| home.join("bin").join(&exe)).filter(|bin| bin.is_file()) {
+            return Some(bin);
+        }
+
+        // Fallback: PATH durchsuchen
+        let path = std::env::var_os("PATH")?;
+        std::env::split_paths(&path)
+            .map(|dir| dir.join(&exe))
+            .find(|bin| bin.is_file())
+    }
+
     /// Findet das Cargo Registry Verzeichnis
     fn find_cargo_registry() -> Option<PathBuf> {
         // Erst CARGO_HOME prüfen
@@ -117,12 +186,30 @@ impl RustPaths {
                                      .unwrap_or_else(|| "NOT INSTALLED (run: rustup component add rust-docs)".to_string())
         ));
 
+        report.push_str(&format!("  Rust Sources: {}\n",
+                                 self.rust_src_path.as_ref()
+                                     .map(|p| p.display().to_string())
+                                     .unwrap_or_else(|| "NOT INSTALLED (run: rustup component add rust-src)".to_string())
+        ));
+
         report.push_str(&format!("  Cargo Registry: {}\n",
                                  self.cargo_registry.as_ref()
                                      .map(|p| p.display().to_string())
                                      .unwrap_or_else(|| "NOT FOUND".to_string())
         ));
 
+        report.push_str(&format!("  rustc: {}\n",
+                                 self.rustc_version.as_deref().unwrap_or("NOT FOUND")
+        ));
+
+        report.push_str(&format!("  cargo: {}\n",
+                                 match (&self.cargo_bin, &self.cargo_version) {
+                                     (Some(bin), Some(version)) => format!("{} ({})", version, bin.display()),
+                                     (Some(bin), None) => format!("{} (version unknown)", bin.display()),
+                                     (None, _) => "NOT FOUND on PATH (install Rust via https://rustup.rs)".to_string(),
+                                 }
+        ));
+
         report
     }
 }
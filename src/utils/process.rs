@@ -0,0 +1,535 @@
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::future::Future;
+use std::path::PathBuf;
+use std::process::{Output, Stdio};
+use std::sync::OnceLock;
+use std::time::Duration;
+use anyhow::{Context, Result};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::Command;
+use tokio::sync::mpsc::UnboundedSender;
+use tokio_util::sync::CancellationToken;
+use crate::utils::text::truncate_middle;
+use crate::utils::ToolError;
+
+/// Upper bound for captured stdout/stderr (per stream) when streaming output.
+pub const MAX_CAPTURE_BYTES: usize = 1024 * 1024;
+
+/// Default for `--max-output-bytes`: how much of a stream is returned to the client.
+pub const DEFAULT_MAX_OUTPUT_BYTES: usize = 64 * 1024;
+
+static MAX_OUTPUT_BYTES: OnceLock<usize> = OnceLock::new();
+
+/// Sets the cap used by `capped_output`. Called once at startup; later calls are ignored.
+pub fn set_max_output_bytes(max_bytes: usize) {
+    let _ = MAX_OUTPUT_BYTES.set(max_bytes);
+}
+
+/// Decodes command output for a tool response, truncating the middle if it exceeds
+/// the configured cap so a single verbose build can't flood the client.
+pub fn capped_output(bytes: &[u8]) -> String {
+    let max_bytes = *MAX_OUTPUT_BYTES.get().unwrap_or(&DEFAULT_MAX_OUTPUT_BYTES);
+    truncate_middle(&String::from_utf8_lossy(bytes), max_bytes)
+}
+
+static CARGO_BIN: OnceLock<PathBuf> = OnceLock::new();
+
+/// Sets the cargo binary used by `cargo_command` (found by `RustPaths::discover`).
+/// Called once at startup; later calls are ignored.
+pub fn set_cargo_bin(path: PathBuf) {
+    let _ = CARGO_BIN.set(path);
+}
+
+/// A `cargo` command using the binary discovered at startup, or plain `cargo` from PATH.
+pub fn cargo_command() -> Command {
+    match CARGO_BIN.get() {
+        Some(bin) => Command::new(bin),
+        None => Command::new("cargo"),
+    }
+}
+
+/// Error for a command that could not be started. A missing binary becomes a
+/// `ToolError::NotFound` that says what to install instead of a bare "No such file or directory".
+pub fn spawn_error(program: impl AsRef<OsStr>, err: std::io::Error) -> anyhow::Error {
+    let name = std::path::Path::new(program.as_ref())
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    if err.kind() != std::io::ErrorKind::NotFound {
+        return anyhow::Error::new(err).context(format!("Failed to start '{}'", name));
+    }
+    if name == "cargo" {
+        return ToolError::NotFound(
+            "cargo not found on PATH — is Rust installed? Install it via https://rustup.rs (or add ~/.cargo/bin to PATH) and restart the server.".to_string()
+        ).into();
+    }
+    ToolError::NotFound(format!("'{}' not found on PATH. Install it (see 'health_check') and restart the server.", name)).into()
+}
+
+/// Default for `--command-timeout-secs`: upper bound for any external command
+/// unless the tool (bench, examples) or the request sets its own.
+pub const DEFAULT_COMMAND_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+static COMMAND_TIMEOUT: OnceLock<Duration> = OnceLock::new();
+
+/// Sets the default used by `command_timeout`. Called once at startup; later calls are ignored.
+pub fn set_command_timeout(timeout: Duration) {
+    let _ = COMMAND_TIMEOUT.set(timeout);
+}
+
+/// The per-request override (`timeout_secs`) if given, else the configured default.
+pub fn command_timeout(requested_secs: Option<u64>) -> Duration {
+    requested_secs
+        .map(Duration::from_secs)
+        .unwrap_or_else(|| *COMMAND_TIMEOUT.get().unwrap_or(&DEFAULT_COMMAND_TIMEOUT))
+}
+
+/// Default for `--cargo-attempts`.
+pub const DEFAULT_CARGO_ATTEMPTS: u32 = 3;
+
+/// Delay before the first retry; doubled for every further attempt.
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+
+static CARGO_ATTEMPTS: OnceLock<u32> = OnceLock::new();
+
+/// Sets the attempts used by `output_with_retry`. Called once at startup; later calls are ignored.
+pub fn set_cargo_attempts(attempts: u32) {
+    let _ = CARGO_ATTEMPTS.set(attempts.max(1));
+}
+
+/// Signatures of registry/network blips in cargo's stderr (lowercase).
+const TRANSIENT_ERRORS: &[&str] = &[
+    "spurious network error",
+    "failed to download",
+    "failed to fetch",
+    "failed to update registry",
+    "failed to query replaced source registry",
+    "network failure",
+    "operation timed out",
+    "connection timed out",
+    "connection reset",
+    "connection refused",
+    "could not resolve host",
+    "couldn't resolve host",
+    "ssl connect error",
+    "http2 framing layer",
+];
+
+/// True if a failed cargo run looks like a network problem rather than a real error.
+/// Compile errors are never transient, even if a network warning was printed as well.
+pub fn is_transient_failure(stderr: &str) -> bool {
+    let stderr = stderr.to_lowercase();
+    if stderr.contains("could not compile") || stderr.contains("error[e") {
+        return false;
+    }
+    TRANSIENT_ERRORS.iter().any(|pattern| stderr.contains(pattern))
+}
+
+/// Cargo and rustc settings a client may still pass: they only add compiler flags.
+const ENV_FLAG_VARIABLES: [&str; 2] = ["CARGO_ENCODED_RUSTFLAGS", "CARGO_ENCODED_RUSTDOCFLAGS"];
+
+/// Prefixes of variables that can swap the executed program (`RUSTC_WRAPPER`, `RUSTC`,
+/// `CARGO_BUILD_RUSTC_WRAPPER`, `CARGO_TARGET_<triple>_RUNNER`, `CARGO_HOME`, ...)
+const ENV_RESERVED_PREFIXES: [&str; 3] = ["CARGO_", "RUSTC", "RUSTUP_"];
+
+/// Further variables that decide which binaries or libraries get loaded
+const ENV_RESERVED_NAMES: [&str; 6] = ["CARGO", "RUSTDOC", "PATH", "LD_PRELOAD", "LD_LIBRARY_PATH", "DYLD_INSERT_LIBRARIES"];
+
+/// Rejects variable names the OS cannot represent (empty, `=` or NUL) before they reach
+/// `Command::envs`, so a bad request gets a clear message instead of a spawn failure.
+/// Variables that make cargo run another program are refused as well: `env` is reachable
+/// from `check_code` in read-only mode and would otherwise bypass `cargo_command`'s allowlist.
+pub fn validate_env(env: &HashMap<String, String>) -> Result<()> {
+    for (key, value) in env {
+        if key.is_empty() || key.contains('=') || key.contains('\0') || value.contains('\0') {
+            return Err(ToolError::InvalidInput(format!("Invalid environment variable '{}'. Names must be non-empty and contain no '=' or NUL.", key)).into());
+        }
+
+        let name = key.to_ascii_uppercase();
+        let reserved = ENV_RESERVED_NAMES.contains(&name.as_str())
+            || ENV_RESERVED_PREFIXES.iter().any(|prefix| name.starts_with(prefix));
+        if reserved && !ENV_FLAG_VARIABLES.contains(&name.as_str()) {
+            return Err(ToolError::InvalidInput(format!(
+                "Environment variable '{}' is not allowed: it can change which programs cargo runs. Use RUSTFLAGS, RUSTDOCFLAGS or CARGO_ENCODED_RUSTFLAGS for compiler flags.",
+                key
+            )).into());
+        }
+    }
+    Ok(())
+}
+
+/// Whether cargo runs offline for the whole process (`CARGO_NET_OFFLINE=true`).
+pub fn env_offline() -> bool {
+    std::env::var("CARGO_NET_OFFLINE").is_ok_and(|v| v == "true")
+}
+
+/// Runs the command produced by `build` via `output_with_timeout` and retries with exponential
+/// backoff (500ms, 1s, 2s, ...) while it fails with a transient network error.
+/// `timeout` applies to each attempt. Offline runs (`--offline` or `CARGO_NET_OFFLINE=true`)
+/// are never retried.
+pub async fn output_with_retry(build: impl Fn() -> Command, cancel: &CancellationToken, timeout: Duration) -> Result<ProcessOutcome> {
+    let first = build();
+    let attempts = cargo_attempts(&first);
+
+    retry_transient(attempts, RETRY_BASE_DELAY, cancel, {
+        let mut first = Some(first);
+        move || output_with_timeout(first.take().unwrap_or_else(&build), cancel, timeout)
+    }).await
+}
+
+/// Like `output_with_retry`, but hands every stdout line to `on_line` as it arrives instead of
+/// capturing it, so memory stays bounded no matter how much the child prints. `on_line` also
+/// receives the 1-based attempt number, letting callers drop what a retried attempt produced.
+/// The returned `Output` has an empty stdout; stderr is captured as usual.
+pub async fn stdout_lines_with_retry(
+    build: impl Fn() -> Command,
+    cancel: &CancellationToken,
+    timeout: Duration,
+    on_line: &(dyn Fn(u32, &str) + Sync),
+) -> Result<ProcessOutcome> {
+    let first = build();
+    let attempts = cargo_attempts(&first);
+
+    retry_transient(attempts, RETRY_BASE_DELAY, cancel, {
+        let mut first = Some(first);
+        let mut attempt = 0;
+        move || {
+            attempt += 1;
+            let current = attempt;
+            let cmd = first.take().unwrap_or_else(&build);
+            with_deadline(cancel, timeout, move |token| async move {
+                stream_with_cancel(cmd, &token, false, |stream, line| {
+                    if stream == Stream::Stdout {
+                        on_line(current, &line);
+                    }
+                }).await
+            })
+        }
+    }).await
+}
+
+/// Attempts allowed for a cargo invocation; offline runs are never retried.
+fn cargo_attempts(cmd: &Command) -> u32 {
+    let offline = cmd.as_std().get_args().any(|arg| arg == "--offline") || env_offline();
+    if offline { 1 } else { *CARGO_ATTEMPTS.get().unwrap_or(&DEFAULT_CARGO_ATTEMPTS) }
+}
+
+/// Retry loop behind `output_with_retry`, separate so the policy can be tested without cargo.
+async fn retry_transient<F, Fut>(attempts: u32, base_delay: Duration, cancel: &CancellationToken, mut run: F) -> Result<ProcessOutcome>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<ProcessOutcome>>,
+{
+    let mut attempt = 1;
+    loop {
+        let outcome = run().await?;
+
+        let retry = match &outcome {
+            ProcessOutcome::Completed(output) => {
+                !output.status.success()
+                    && attempt < attempts
+                    && is_transient_failure(&String::from_utf8_lossy(&output.stderr))
+            }
+            _ => false,
+        };
+        if !retry {
+            return Ok(outcome);
+        }
+
+        let delay = base_delay * 2u32.saturating_pow(attempt - 1);
+        tracing::warn!("Transient cargo failure (attempt {}/{}), retrying in {:?}", attempt, attempts, delay);
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => {}
+            _ = cancel.cancelled() => return Ok(ProcessOutcome::Cancelled),
+        }
+        attempt += 1;
+    }
+}
+
+/// Result of running a child process that may be cancelled by the client.
+pub enum ProcessOutcome {
+    Completed(Output),
+    Cancelled,
+    TimedOut(Duration),
+}
+
+/// Runs `cmd` to completion, capturing stdout/stderr, unless `cancel` fires first.
+/// On cancellation the child and its whole process group (e.g. rustc jobs spawned
+/// by cargo) are killed and no partial output is returned.
+async fn output_with_cancel(mut cmd: Command, cancel: &CancellationToken) -> Result<ProcessOutcome> {
+    cmd.stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    // Own process group, so cancelling also reaches grandchildren
+    #[cfg(unix)]
+    cmd.process_group(0);
+
+    let child = cmd.spawn().map_err(|e| spawn_error(cmd.as_std().get_program(), e))?;
+    let pid = child.id();
+
+    tokio::select! {
+        output = child.wait_with_output() => {
+            Ok(ProcessOutcome::Completed(output.context("Failed to wait for process")?))
+        }
+        _ = cancel.cancelled() => {
+            kill_process_group(pid);
+            Ok(ProcessOutcome::Cancelled)
+        }
+    }
+}
+
+/// Runs `cmd` like `output_with_cancel`, but additionally kills the process (group) after
+/// `timeout`. This is the entry point for every tool that spawns a child process; use
+/// `command_timeout` for the configured default.
+pub async fn output_with_timeout(cmd: Command, cancel: &CancellationToken, timeout: Duration) -> Result<ProcessOutcome> {
+    with_deadline(cancel, timeout, |token| async move { output_with_cancel(cmd, &token).await }).await
+}
+
+/// Like `output_with_timeout`, but reads stdout/stderr line by line and forwards every
+/// line to `lines` as it arrives (e.g. for progress notifications).
+pub async fn output_streaming(
+    cmd: Command,
+    cancel: &CancellationToken,
+    timeout: Duration,
+    lines: UnboundedSender<String>,
+) -> Result<ProcessOutcome> {
+    with_deadline(cancel, timeout, |token| async move {
+        stream_with_cancel(cmd, &token, true, |_, line| {
+            let _ = lines.send(line);
+        }).await
+    }).await
+}
+
+/// Runs `run` with a child token that fires on client cancellation *or* after `timeout`,
+/// and reports the latter as `TimedOut`.
+async fn with_deadline<F, Fut>(cancel: &CancellationToken, timeout: Duration, run: F) -> Result<ProcessOutcome>
+where
+    F: FnOnce(CancellationToken) -> Fut,
+    Fut: Future<Output = Result<ProcessOutcome>>,
+{
+    let token = cancel.child_token();
+    let timer_token = token.clone();
+    let timer = tokio::spawn(async move {
+        tokio::time::sleep(timeout).await;
+        timer_token.cancel();
+    });
+
+    let outcome = run(token).await;
+    timer.abort();
+
+    match outcome? {
+        ProcessOutcome::Cancelled if !cancel.is_cancelled() => Ok(ProcessOutcome::TimedOut(timeout)),
+        other => Ok(other),
+    }
+}
+
+/// Which pipe a streamed line came from.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Stream {
+    Stdout,
+    Stderr,
+}
+
+/// Streaming counterpart of `output_with_cancel`: every line is passed to `on_line` as it arrives.
+/// Captured output is capped at `MAX_CAPTURE_BYTES` per stream; forwarding continues past the cap.
+/// Stderr is always captured, stdout only with `capture_stdout`.
+async fn stream_with_cancel(
+    mut cmd: Command,
+    cancel: &CancellationToken,
+    capture_stdout: bool,
+    mut on_line: impl FnMut(Stream, String),
+) -> Result<ProcessOutcome> {
+    cmd.stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    #[cfg(unix)]
+    cmd.process_group(0);
+
+    let mut child = cmd.spawn().map_err(|e| spawn_error(cmd.as_std().get_program(), e))?;
+    let pid = child.id();
+
+    let stdout = child.stdout.take().context("stdout not captured")?;
+    let stderr = child.stderr.take().context("stderr not captured")?;
+
+    let read_all = async {
+        let mut out_lines = BufReader::new(stdout).lines();
+        let mut err_lines = BufReader::new(stderr).lines();
+        let mut out_buf = CappedBuffer::default();
+        let mut err_buf = CappedBuffer::default();
+        let (mut out_done, mut err_done) = (false, false);
+
+        while !(out_done && err_done) {
+            tokio::select! {
+                line = out_lines.next_line(), if !out_done => match line? {
+                    Some(line) => {
+                        if capture_stdout {
+                            out_buf.push_line(&line);
+                        }
+                        on_line(Stream::Stdout, line);
+                    }
+                    None => out_done = true,
+                },
+                line = err_lines.next_line(), if !err_done => match line? {
+                    Some(line) => {
+                        err_buf.push_line(&line);
+                        on_line(Stream::Stderr, line);
+                    }
+                    None => err_done = true,
+                },
+            }
+        }
+
+        let status = child.wait().await.context("Failed to wait for process")?;
+        Ok::<_, anyhow::Error>(Output {
+            status,
+            stdout: out_buf.into_bytes(),
+            stderr: err_buf.into_bytes(),
+        })
+    };
+
+    tokio::select! {
+        output = read_all => Ok(ProcessOutcome::Completed(output?)),
+        _ = cancel.cancelled() => {
+            kill_process_group(pid);
+            Ok(ProcessOutcome::Cancelled)
+        }
+    }
+}
+
+/// Line buffer that stops growing at `MAX_CAPTURE_BYTES` and remembers that it did.
+#[derive(Default)]
+struct CappedBuffer {
+    data: String,
+    dropped_lines: usize,
+}
+
+impl CappedBuffer {
+    fn push_line(&mut self, line: &str) {
+        if self.data.len() + line.len() + 1 > MAX_CAPTURE_BYTES {
+            self.dropped_lines += 1;
+            return;
+        }
+        self.data.push_str(line);
+        self.data.push('\n');
+    }
+
+    fn into_bytes(mut self) -> Vec<u8> {
+        if self.dropped_lines > 0 {
+            self.data.push_str(&format!("[... {} lines not captured (output cap reached) ...]\n", self.dropped_lines));
+        }
+        self.data.into_bytes()
+    }
+}
+
+/// Kills the process group led by `pid`. The child itself is also killed by
+/// `kill_on_drop` once its future is dropped.
+fn kill_process_group(pid: Option<u32>) {
+    #[cfg(unix)]
+    if let Some(pid) = pid {
+        // SAFETY: Sending a signal has no memory-safety implications.
+        unsafe {
+            libc::kill(-(pid as i32), libc::SIGKILL);
+        }
+    }
+
+    #[cfg(not(unix))]
+    let _ = pid;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    fn failed_with(stderr: &str) -> Result<ProcessOutcome> {
+        let output = std::process::Command::new("sh").args(["-c", "exit 101"]).output()?;
+        Ok(ProcessOutcome::Completed(Output { stderr: stderr.as_bytes().to_vec(), ..output }))
+    }
+
+    #[test]
+    fn test_is_transient_failure() {
+        assert!(is_transient_failure("warning: spurious network error (2 tries remaining): [28] Timeout was reached"));
+        assert!(is_transient_failure("error: failed to download from `https://index.crates.io/...`"));
+        assert!(!is_transient_failure("error: could not compile `demo` (bin \"demo\") due to 1 previous error"));
+        assert!(!is_transient_failure("error: the crate `nope` could not be found in registry index."));
+    }
+
+    #[test]
+    fn test_validate_env() {
+        let ok = HashMap::from([("RUSTFLAGS".to_string(), "--cfg tokio_unstable".to_string())]);
+        assert!(validate_env(&ok).is_ok());
+        assert!(validate_env(&HashMap::new()).is_ok());
+
+        let bad = HashMap::from([("A=B".to_string(), "1".to_string())]);
+        assert!(validate_env(&bad).is_err());
+
+        for name in ["RUSTDOCFLAGS", "CARGO_ENCODED_RUSTFLAGS", "RUST_BACKTRACE", "MY_FEATURE"] {
+            assert!(validate_env(&HashMap::from([(name.to_string(), "1".to_string())])).is_ok(), "{}", name);
+        }
+        for name in [
+            "RUSTC_WRAPPER", "RUSTC", "RUSTC_WORKSPACE_WRAPPER", "CARGO_BUILD_RUSTC_WRAPPER",
+            "CARGO_TARGET_X86_64_UNKNOWN_LINUX_GNU_RUNNER", "CARGO_HOME", "rustc_wrapper", "PATH", "LD_PRELOAD",
+        ] {
+            let err = validate_env(&HashMap::from([(name.to_string(), "/tmp/x".to_string())])).unwrap_err();
+            assert!(err.to_string().contains("is not allowed"), "{}", name);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retry_only_transient() {
+        let cancel = CancellationToken::new();
+
+        let calls = Cell::new(0);
+        let outcome = retry_transient(3, Duration::ZERO, &cancel, || {
+            calls.set(calls.get() + 1);
+            async { failed_with("error: failed to download") }
+        }).await.unwrap();
+        assert!(matches!(outcome, ProcessOutcome::Completed(_)));
+        assert_eq!(calls.get(), 3);
+
+        let calls = Cell::new(0);
+        retry_transient(3, Duration::ZERO, &cancel, || {
+            calls.set(calls.get() + 1);
+            async { failed_with("error: could not compile `demo`") }
+        }).await.unwrap();
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_output_with_timeout_kills() {
+        let mut cmd = Command::new("sleep");
+        cmd.arg("5");
+        let outcome = output_with_timeout(cmd, &CancellationToken::new(), Duration::from_millis(100)).await.unwrap();
+        assert!(matches!(outcome, ProcessOutcome::TimedOut(limit) if limit == Duration::from_millis(100)));
+    }
+
+    #[tokio::test]
+    async fn test_stdout_lines_are_streamed() {
+        let seen = std::sync::Mutex::new(Vec::new());
+        let build = || {
+            let mut cmd = Command::new("sh");
+            cmd.args(["-c", "echo one; echo two; echo oops >&2"]);
+            cmd
+        };
+        let on_line = |attempt: u32, line: &str| seen.lock().unwrap().push((attempt, line.to_string()));
+
+        let outcome = stdout_lines_with_retry(build, &CancellationToken::new(), Duration::from_secs(5), &on_line).await.unwrap();
+        let ProcessOutcome::Completed(output) = outcome else { panic!("process did not complete") };
+        assert!(output.stdout.is_empty());
+        assert_eq!(String::from_utf8_lossy(&output.stderr), "oops\n");
+        assert_eq!(*seen.lock().unwrap(), vec![(1, "one".to_string()), (1, "two".to_string())]);
+    }
+
+    #[tokio::test]
+    async fn test_missing_program_is_not_found() {
+        let cmd = Command::new("/nonexistent/bin/cargo");
+        let err = output_with_timeout(cmd, &CancellationToken::new(), Duration::from_secs(5)).await.err().unwrap();
+        let tool_error = err.downcast_ref::<ToolError>().unwrap();
+        assert_eq!(tool_error.kind(), "not_found");
+        assert!(tool_error.to_string().starts_with("cargo not found on PATH"));
+    }
+}
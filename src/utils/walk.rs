@@ -0,0 +1,53 @@
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Directories that are never walked, whatever the configuration says.
+pub const ALWAYS_IGNORED: [&str; 2] = ["target", ".git"];
+
+/// Directory names skipped by all walking tools unless overridden via `--ignore-dirs`.
+pub fn default_ignores() -> Vec<String> {
+    ["target", ".git", "node_modules", ".idea", ".vscode", ".mcp-backups"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+}
+
+/// The set of directory names skipped when walking a tree (structure, search, indexing).
+/// Matching is by exact file name, not by path.
+#[derive(Debug, Clone)]
+pub struct IgnoreList {
+    dirs: Vec<String>,
+}
+
+impl IgnoreList {
+    /// `configured` replaces the default list; `ALWAYS_IGNORED` is added either way.
+    pub fn new(configured: Option<&[String]>) -> Self {
+        let mut dirs = configured.map(|c| c.to_vec()).unwrap_or_else(default_ignores);
+        for always in ALWAYS_IGNORED {
+            if !dirs.iter().any(|d| d == always) {
+                dirs.push(always.to_string());
+            }
+        }
+        Self { dirs }
+    }
+
+    pub fn is_ignored(&self, name: &str) -> bool {
+        self.dirs.iter().any(|d| d == name)
+    }
+
+    pub fn dirs(&self) -> &[String] {
+        &self.dirs
+    }
+}
+
+/// All `.rs` files below `root`, skipping ignored folders (build output, VCS, ...).
+pub fn rust_files(root: &Path, ignore: &IgnoreList) -> Vec<PathBuf> {
+    WalkDir::new(root)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|e| !(e.file_type().is_dir() && ignore.is_ignored(&e.file_name().to_string_lossy())))
+        .flatten()
+        .filter(|e| e.file_type().is_file() && e.path().extension().and_then(|s| s.to_str()) == Some("rs"))
+        .map(|e| e.into_path())
+        .collect()
+}